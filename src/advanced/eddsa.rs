@@ -0,0 +1,248 @@
+//! EdDSA scalar and point arithmetic
+//!
+//! Scalars are 32 byte little endian numbers modulo the order L of the curve's prime order
+//! subgroup, points are encoded like EdDSA public keys.
+//!
+//! [Official documentation](https://monocypher.org/manual/advanced/eddsa)
+
+use monocypher_sys as ffi;
+
+// The order L of the prime order subgroup, little endian.
+const L: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+// The y coordinates of the points of order 1, 2, 4 and 8, with the sign bit cleared. Includes
+// the non canonical encodings p and p + 1 of 0 and 1.
+const SMALL_ORDER_POINTS: [[u8; 32]; 7] = [
+    [0x00; 32],
+    [
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ],
+    [
+        0x26, 0xe8, 0x95, 0x8f, 0xc2, 0xb2, 0x27, 0xb0, 0x45, 0xc3, 0xf4, 0x89, 0xf2, 0xef, 0x98,
+        0xf0, 0xd5, 0xdf, 0xac, 0x05, 0xd3, 0xc6, 0x33, 0x39, 0xb1, 0x38, 0x02, 0x88, 0x6d, 0x53,
+        0xfc, 0x05,
+    ],
+    [
+        0xc7, 0x17, 0x6a, 0x70, 0x3d, 0x4d, 0xd8, 0x4f, 0xba, 0x3c, 0x0b, 0x76, 0x0d, 0x10, 0x67,
+        0x0f, 0x2a, 0x20, 0x53, 0xfa, 0x2c, 0x39, 0xcc, 0xc6, 0x4e, 0xc7, 0xfd, 0x77, 0x92, 0xac,
+        0x03, 0x7a,
+    ],
+    [
+        0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xed, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xee, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+];
+
+/// Clamps 32 random bytes into a secret scalar, like EdDSA does with the hash of its seed.
+///
+/// The three lowest bits are cleared, the highest bit is cleared and the second highest set.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::advanced::eddsa::trim_scalar;
+///
+/// let scalar = trim_scalar(&[0xff; 32]);
+/// assert_eq!(scalar[0], 0xf8);
+/// assert_eq!(scalar[31], 0x7f);
+/// ```
+pub fn trim_scalar(input: &[u8; 32]) -> [u8; 32] {
+    let mut scalar = [0u8; 32];
+    unsafe { ffi::crypto_eddsa_trim_scalar(scalar.as_mut_ptr(), input.as_ptr()) };
+    scalar
+}
+
+/// Reduces a 64 byte number, such as a hash, modulo L.
+pub fn reduce(expanded: &[u8; 64]) -> [u8; 32] {
+    let mut reduced = [0u8; 32];
+    unsafe { ffi::crypto_eddsa_reduce(reduced.as_mut_ptr(), expanded.as_ptr()) };
+    reduced
+}
+
+/// Multiplies the base point with a scalar.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::advanced::eddsa::{scalarbase, trim_scalar};
+///
+/// let secret = trim_scalar(&[7u8; 32]);
+/// let public = scalarbase(&secret);
+/// ```
+pub fn scalarbase(scalar: &[u8; 32]) -> [u8; 32] {
+    let mut point = [0u8; 32];
+    unsafe { ffi::crypto_eddsa_scalarbase(point.as_mut_ptr(), scalar.as_ptr()) };
+    point
+}
+
+/// Computes `a * b + c` modulo L.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::advanced::eddsa::mul_add;
+///
+/// let mut a = [0u8; 32];
+/// let mut b = [0u8; 32];
+/// let mut c = [0u8; 32];
+/// (a[0], b[0], c[0]) = (2, 3, 4);
+/// assert_eq!(mul_add(&a, &b, &c)[0], 10);
+/// ```
+pub fn mul_add(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> [u8; 32] {
+    let mut r = [0u8; 32];
+    unsafe { ffi::crypto_eddsa_mul_add(r.as_mut_ptr(), a.as_ptr(), b.as_ptr(), c.as_ptr()) };
+    r
+}
+
+/// Checks whether a scalar is fully reduced, that is below L.
+///
+/// Runs in variable time, it is meant for public values such as the second half of signatures.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::advanced::eddsa::{is_canonical_scalar, reduce};
+///
+/// assert!(is_canonical_scalar(&reduce(&[0xff; 64])));
+/// assert!(!is_canonical_scalar(&[0xff; 32]));
+/// ```
+pub fn is_canonical_scalar(scalar: &[u8; 32]) -> bool {
+    // Compare from the most significant byte.
+    scalar.iter().rev().lt(L.iter().rev())
+}
+
+/// Checks whether a point uses the unique encoding of its coordinates.
+///
+/// The y coordinate must be below p = 2^255 - 19, and the sign bit must be clear for the two
+/// points with x = 0. The point is not checked to be on the curve. Runs in variable time.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::advanced::eddsa::{is_canonical_point, scalarbase};
+///
+/// assert!(is_canonical_point(&scalarbase(&[1u8; 32])));
+/// assert!(!is_canonical_point(&[0xff; 32]));
+/// ```
+pub fn is_canonical_point(point: &[u8; 32]) -> bool {
+    let mut y = *point;
+    y[31] &= 0x7f;
+    // y >= p only if all but the lowest byte of y are the highest possible.
+    let below_p = !(y[0] >= 0xed && y[1..31].iter().all(|&b| b == 0xff) && y[31] == 0x7f);
+    let x_is_zero = y == SMALL_ORDER_POINTS[1] || y == SMALL_ORDER_POINTS[4];
+    below_p && !(x_is_zero && point[31] & 0x80 != 0)
+}
+
+/// Checks whether a point has a small order, 1, 2, 4 or 8.
+///
+/// Multiplying such a point with a multiple of 8, as the cofactor of the curve, yields the
+/// neutral element. Non canonical encodings of these points are detected too. Runs in variable
+/// time.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::advanced::eddsa::{has_small_order, scalarbase};
+///
+/// let mut neutral = [0u8; 32];
+/// neutral[0] = 1;
+/// assert!(has_small_order(&neutral));
+/// assert!(!has_small_order(&scalarbase(&[1u8; 32])));
+/// ```
+pub fn has_small_order(point: &[u8; 32]) -> bool {
+    let mut y = *point;
+    y[31] &= 0x7f;
+    SMALL_ORDER_POINTS.contains(&y)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scalar(n: u8) -> [u8; 32] {
+        let mut scalar = [0u8; 32];
+        scalar[0] = n;
+        scalar
+    }
+
+    #[test]
+    fn trim() {
+        let scalar = trim_scalar(&[0xff; 32]);
+        assert_eq!(scalar[0], 0xf8);
+        assert_eq!(scalar[1..31], [0xff; 30]);
+        assert_eq!(scalar[31], 0x7f);
+        assert_eq!(trim_scalar(&[0; 32])[31], 0x40);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(mul_add(&scalar(2), &scalar(3), &scalar(4)), scalar(10));
+
+        let mut expanded = [0u8; 64];
+        expanded[0] = 5;
+        assert_eq!(reduce(&expanded), scalar(5));
+    }
+
+    #[test]
+    fn canonical_scalar() {
+        let mut l_minus_1 = L;
+        l_minus_1[0] -= 1;
+        assert!(is_canonical_scalar(&l_minus_1));
+        assert!(is_canonical_scalar(&scalar(0)));
+        assert!(!is_canonical_scalar(&L));
+        let mut l_plus_256 = L;
+        l_plus_256[1] += 1;
+        assert!(!is_canonical_scalar(&l_plus_256));
+    }
+
+    #[test]
+    fn canonical_point() {
+        assert!(is_canonical_point(&scalarbase(&scalar(1))));
+        // p - 1 is the highest canonical y.
+        assert!(is_canonical_point(&SMALL_ORDER_POINTS[4]));
+        assert!(!is_canonical_point(&SMALL_ORDER_POINTS[5]));
+        assert!(!is_canonical_point(&SMALL_ORDER_POINTS[6]));
+        // x = 0 with the sign bit set.
+        let mut negative_zero = SMALL_ORDER_POINTS[1];
+        negative_zero[31] |= 0x80;
+        assert!(!is_canonical_point(&negative_zero));
+    }
+
+    #[test]
+    fn small_order() {
+        for point in SMALL_ORDER_POINTS {
+            assert!(has_small_order(&point));
+            let mut negated = point;
+            negated[31] |= 0x80;
+            assert!(has_small_order(&negated));
+        }
+        assert!(!has_small_order(&scalarbase(&scalar(1))));
+    }
+
+    #[test]
+    fn scalarbase_is_linear() {
+        // (2 * 3 + 4) * B computed directly and via the scalar.
+        let direct = scalarbase(&scalar(10));
+        assert_eq!(
+            direct,
+            scalarbase(&mul_add(&scalar(2), &scalar(3), &scalar(4)))
+        );
+        assert_ne!(direct, scalarbase(&scalar(11)));
+    }
+}
@@ -0,0 +1,8 @@
+//! Low level building blocks
+//!
+//! These functions give no security guarantees on their own, they are meant for custom
+//! protocols built by people who know what the building blocks do.
+//!
+//! [Official documentation](https://monocypher.org/manual/advanced/)
+
+pub mod eddsa;
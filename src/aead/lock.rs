@@ -1,8 +1,29 @@
 //! Authenticated encryption w/o additional data
 
+#[cfg(feature = "rayon")]
+use crate::aead::parallel;
+use crate::aead::{padding, MessageKeys, PaddingScheme};
+use crate::utils;
 use monocypher_sys as ffi;
 use std::mem;
 
+/// Encrypt and authenticate plaintext.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::lock::easy;
+///
+/// let plaintext = "plaintext";
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+///
+/// let cymac = easy(plaintext.as_bytes(), key, nonce);
+/// ```
+pub fn easy(plain_text: &[u8], key: [u8; 32], nonce: [u8; 24]) -> (Vec<u8>, [u8; 16]) {
+    aead(plain_text, key, nonce, &[])
+}
+
 /// Encrypt and authenticate plaintext with additional data.
 ///
 /// # Example
@@ -35,6 +56,95 @@ pub fn aead(plain_text: &[u8], key: [u8; 32], nonce: [u8; 24], ad: &[u8]) -> (Ve
     }
 }
 
+/// Pad, encrypt and authenticate plaintext with additional data.
+///
+/// The padding hides the exact length of the plaintext.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{lock::aead_padded, PaddingScheme};
+///
+/// let plaintext = "plaintext";
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+/// let ad = "data";
+///
+/// let cymac = aead_padded(plaintext.as_bytes(), key, nonce, ad.as_bytes(), PaddingScheme::Padme);
+/// ```
+pub fn aead_padded(
+    plain_text: &[u8],
+    key: [u8; 32],
+    nonce: [u8; 24],
+    ad: &[u8],
+    scheme: PaddingScheme,
+) -> (Vec<u8>, [u8; 16]) {
+    let mut padded = padding::pad(plain_text, scheme);
+    let ret = aead(&padded, key, nonce, ad);
+    utils::wipe(&mut padded);
+    ret
+}
+
+/// Encrypt and authenticate plaintext with additional data that are split into several parts.
+///
+/// The parts are processed as if they were concatenated, so the result is the same as calling
+/// [`aead`] on the joined buffers.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::lock::aead_vectored;
+///
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+/// let header: [&[u8]; 2] = ["version".as_bytes(), "type".as_bytes()];
+///
+/// let cymac = aead_vectored(&["plain".as_bytes(), "text".as_bytes()], key, nonce, &header);
+/// ```
+pub fn aead_vectored(
+    plain_text: &[&[u8]],
+    key: [u8; 32],
+    nonce: [u8; 24],
+    ad: &[&[u8]],
+) -> (Vec<u8>, [u8; 16]) {
+    let mut cipher_text = plain_text.concat();
+    let keys = MessageKeys::new(&key, &nonce);
+    keys.apply_keystream(&mut cipher_text);
+    let mac = keys.mac(ad, &cipher_text);
+    (cipher_text, mac)
+}
+
+/// Encrypt and authenticate a large plaintext in parallel chunks.
+///
+/// The result is an envelope of independently keyed chunks and a summary MAC, see
+/// [`parallel`](crate::aead::parallel) for the format. It can only be decrypted with
+/// [`unlock::aead_parallel`](crate::aead::unlock::aead_parallel).
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::lock::aead_parallel;
+///
+/// let plaintext = vec![0u8; 4 << 20];
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+///
+/// let (envelope, mac) = aead_parallel(&plaintext, key, nonce, "data".as_bytes());
+/// ```
+#[cfg(feature = "rayon")]
+pub fn aead_parallel(
+    plain_text: &[u8],
+    key: [u8; 32],
+    nonce: [u8; 24],
+    ad: &[u8],
+) -> (Vec<u8>, [u8; 16]) {
+    let chunks = parallel::chunk_count(plain_text.len());
+    let mut envelope = vec![0u8; plain_text.len() + chunks * 16];
+    parallel::lock_chunks(&mut envelope, plain_text, &key, &nonce);
+    let mac = parallel::summary_mac(&key, &nonce, ad, &envelope);
+    (envelope, mac)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -54,4 +164,29 @@ mod test {
             [170, 84, 72, 240, 51, 131, 115, 191, 122, 222, 170, 200, 158, 83, 202, 191]
         );
     }
+
+    #[test]
+    fn easy_no_ad() {
+        let key: [u8; 32] = [1; 32];
+        let nonce: [u8; 24] = [2; 24];
+
+        assert_eq!(
+            easy("secret".as_bytes(), key, nonce),
+            aead("secret".as_bytes(), key, nonce, &[])
+        );
+    }
+
+    #[test]
+    fn vectored_aead() {
+        let key: [u8; 32] = [1; 32];
+        let nonce: [u8; 24] = [2; 24];
+        let plain_text: [&[u8]; 2] = ["sec".as_bytes(), "ret".as_bytes()];
+        let ad: [&[u8]; 2] = ["da".as_bytes(), "ta".as_bytes()];
+
+        let (a, b) = aead_vectored(&plain_text, key, nonce, &ad);
+        assert_eq!(
+            (a, b),
+            aead("secret".as_bytes(), key, nonce, "data".as_bytes())
+        );
+    }
 }
@@ -2,5 +2,350 @@
 //!
 //! [Official documentation](https://monocypher.org/manual/aead)
 
+use crate::{poly1305, utils};
+use derive_more::From;
+use monocypher_sys as ffi;
+use std::ops::Deref;
+use std::ptr;
+use thiserror::Error;
+
 pub mod lock;
+pub mod nonce;
+pub mod padding;
+pub mod session;
 pub mod unlock;
+
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
+#[cfg(feature = "rayon")]
+pub mod parallel;
+
+pub use padding::PaddingScheme;
+pub use session::SecureSession;
+pub use unlock::verify_only;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Message is corrupt.")]
+    InvalidMac,
+    #[error("Buffer has an invalid length.")]
+    BadLength,
+    #[error("Padding is invalid.")]
+    InvalidPadding,
+}
+
+/// A 32 byte symmetric key that is wiped when dropped.
+#[derive(From)]
+pub struct Key([u8; 32]);
+
+impl_ct_eq!(Key);
+impl_zeroize!(Key);
+
+impl Deref for Key {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// A 16 byte message authentication code that compares in constant time.
+pub type Mac = crate::mac::Tag<16>;
+
+/// Authenticates additional data without any message.
+///
+/// This is the same as encrypting an empty plaintext, the MAC equals the one returned by
+/// [`lock::aead`] for an empty plaintext.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{authenticate, verify_authenticated};
+///
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+///
+/// let mac = authenticate("header".as_bytes(), &key, &nonce);
+/// verify_authenticated("header".as_bytes(), &key, &nonce, &mac).unwrap();
+/// ```
+pub fn authenticate(ad: &[u8], key: &[u8; 32], nonce: &[u8; 24]) -> Mac {
+    Mac::from(MessageKeys::new(key, nonce).mac(&[ad], &[]))
+}
+
+/// Verifies a MAC produced by [`authenticate`].
+pub fn verify_authenticated(
+    ad: &[u8],
+    key: &[u8; 32],
+    nonce: &[u8; 24],
+    mac: &Mac,
+) -> Result<(), Error> {
+    unlock::verify_only(&[], key, nonce, mac, ad)
+}
+
+/// Re-encrypts a message under a new key and nonce.
+///
+/// The intermediate plaintext is wiped before returning. If the message fails to authenticate
+/// under the old key, nothing is encrypted and [`Error::InvalidMac`] is returned.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{lock, rotate, unlock};
+///
+/// let old_key = [1u8; 32];
+/// let new_key = [2u8; 32];
+/// let old_nonce = [3u8; 24];
+/// let new_nonce = [4u8; 24];
+///
+/// let (cipher_text, mac) = lock::aead("plaintext".as_bytes(), old_key, old_nonce, &[]);
+/// let (cipher_text, mac) =
+///     rotate(&cipher_text, mac, old_key, new_key, old_nonce, new_nonce, &[]).unwrap();
+/// unlock::aead(&cipher_text, new_key, new_nonce, mac, &[]).unwrap();
+/// ```
+pub fn rotate(
+    cipher_text: &[u8],
+    mac: [u8; 16],
+    old_key: [u8; 32],
+    new_key: [u8; 32],
+    nonce_old: [u8; 24],
+    nonce_new: [u8; 24],
+    ad: &[u8],
+) -> Result<(Vec<u8>, [u8; 16]), Error> {
+    let mut plain_text = unlock::aead(cipher_text, old_key, nonce_old, mac, ad)?;
+    let ret = lock::aead(&plain_text, new_key, nonce_new, ad);
+    utils::wipe(&mut plain_text);
+    Ok(ret)
+}
+
+/// Authenticated encryption with a long-lived key.
+///
+/// The key is wiped when the `Aead` is dropped.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::Aead;
+///
+/// let cipher = Aead::new([137u8; 32]);
+/// let nonce = [120u8; 24];
+///
+/// let cymac = cipher.encrypt(nonce, "data".as_bytes(), "plaintext".as_bytes());
+/// cipher.decrypt(nonce, "data".as_bytes(), &cymac.0, cymac.1).unwrap();
+/// ```
+pub struct Aead {
+    key: [u8; 32],
+}
+
+impl_zeroize!(Aead, key);
+
+impl Aead {
+    /// Creates a new cipher with the given key.
+    pub fn new(key: [u8; 32]) -> Aead {
+        Aead { key }
+    }
+
+    /// Encrypts and authenticates plaintext with additional data.
+    pub fn encrypt(&self, nonce: [u8; 24], ad: &[u8], plain_text: &[u8]) -> (Vec<u8>, [u8; 16]) {
+        lock::aead(plain_text, self.key, nonce, ad)
+    }
+
+    /// Encrypts and authenticates plaintext with a single use nonce.
+    ///
+    /// Returns the nonce bytes along with the cipher text and MAC.
+    pub fn encrypt_once(
+        &self,
+        nonce: nonce::Nonce,
+        ad: &[u8],
+        plain_text: &[u8],
+    ) -> ([u8; 24], Vec<u8>, [u8; 16]) {
+        let nonce = nonce.into_bytes();
+        let (cipher_text, mac) = self.encrypt(nonce, ad, plain_text);
+        (nonce, cipher_text, mac)
+    }
+
+    /// Decrypts ciphertext with additional data.
+    pub fn decrypt(
+        &self,
+        nonce: [u8; 24],
+        ad: &[u8],
+        cipher_text: &[u8],
+        mac: [u8; 16],
+    ) -> Result<Vec<u8>, Error> {
+        unlock::aead(cipher_text, self.key, nonce, mac, ad)
+    }
+}
+
+impl Drop for Aead {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.key);
+    }
+}
+
+// Per message keys of the XChaCha20-Poly1305 construction used by crypto_aead_lock.
+struct MessageKeys {
+    stream_key: [u8; 32],
+    stream_nonce: [u8; 8],
+    auth_key: [u8; 64],
+}
+
+impl MessageKeys {
+    fn new(key: &[u8; 32], nonce: &[u8; 24]) -> MessageKeys {
+        let mut keys = MessageKeys {
+            stream_key: [0u8; 32],
+            stream_nonce: [0u8; 8],
+            auth_key: [0u8; 64],
+        };
+        keys.stream_nonce.copy_from_slice(&nonce[16..]);
+        unsafe {
+            ffi::crypto_chacha20_h(keys.stream_key.as_mut_ptr(), key.as_ptr(), nonce.as_ptr());
+            ffi::crypto_chacha20_djb(
+                keys.auth_key.as_mut_ptr(),
+                ptr::null(),
+                keys.auth_key.len(),
+                keys.stream_key.as_ptr(),
+                keys.stream_nonce.as_ptr(),
+                0,
+            );
+        }
+        keys
+    }
+
+    // Encrypts or decrypts `text` in place.
+    fn apply_keystream(&self, text: &mut [u8]) {
+        unsafe {
+            let text_ptr = text.as_mut_ptr();
+            ffi::crypto_chacha20_djb(
+                text_ptr,
+                text_ptr,
+                text.len(),
+                self.stream_key.as_ptr(),
+                self.stream_nonce.as_ptr(),
+                1,
+            );
+        }
+    }
+
+    // Computes the Poly1305 tag over the padded additional data, cipher text and their lengths.
+    fn mac(&self, ad: &[&[u8]], cipher_text: &[u8]) -> [u8; 16] {
+        let ad_len: usize = ad.iter().map(|part| part.len()).sum();
+
+        let mut poly_key = [0u8; 32];
+        poly_key.copy_from_slice(&self.auth_key[..32]);
+        let mut ctx = poly1305::Context::new(poly_key);
+        utils::wipe(&mut poly_key);
+        for part in ad {
+            ctx.update(part);
+        }
+        ctx.update(&[0u8; 16][..gap(ad_len)]);
+        ctx.update_padded16(cipher_text);
+        ctx.update(&poly1305::lengths_block(
+            ad_len as u64,
+            cipher_text.len() as u64,
+        ));
+        *ctx.finalize()
+    }
+}
+
+impl Drop for MessageKeys {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.stream_key);
+        utils::wipe(&mut self.auth_key);
+    }
+}
+
+// Number of padding bytes needed to align `len` to a 16 byte block.
+fn gap(len: usize) -> usize {
+    (16 - len % 16) % 16
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn authenticate_ad() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+
+        let mac = authenticate("data".as_bytes(), &key, &nonce);
+        assert_eq!(*mac, lock::aead(&[], key, nonce, "data".as_bytes()).1);
+        assert!(verify_authenticated("data".as_bytes(), &key, &nonce, &mac).is_ok());
+    }
+
+    #[test]
+    fn authenticate_forged() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+
+        let mac = authenticate("data".as_bytes(), &key, &nonce);
+        let ret = verify_authenticated("atad".as_bytes(), &key, &nonce, &mac);
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn rotate_key() {
+        let (cipher_text, mac) = lock::aead("secret".as_bytes(), [1u8; 32], [2u8; 24], &[]);
+
+        let rotated = rotate(
+            &cipher_text,
+            mac,
+            [1u8; 32],
+            [3u8; 32],
+            [2u8; 24],
+            [4u8; 24],
+            &[],
+        );
+        assert_eq!(
+            rotated.unwrap(),
+            lock::aead("secret".as_bytes(), [3u8; 32], [4u8; 24], &[])
+        );
+    }
+
+    #[test]
+    fn rotate_forged() {
+        let (cipher_text, mac) = lock::aead("secret".as_bytes(), [1u8; 32], [2u8; 24], &[]);
+
+        let rotated = rotate(
+            &cipher_text,
+            mac,
+            [5u8; 32],
+            [3u8; 32],
+            [2u8; 24],
+            [4u8; 24],
+            &[],
+        );
+        assert_eq!(rotated, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn aead_object() {
+        let cipher = Aead::new([1u8; 32]);
+        let nonce = [2u8; 24];
+
+        let (cipher_text, mac) = cipher.encrypt(nonce, "data".as_bytes(), "secret".as_bytes());
+        assert_eq!(
+            (cipher_text.clone(), mac),
+            lock::aead("secret".as_bytes(), [1u8; 32], nonce, "data".as_bytes())
+        );
+
+        let plain_text = cipher.decrypt(nonce, "data".as_bytes(), &cipher_text, mac);
+        assert_eq!(plain_text.unwrap(), "secret".as_bytes());
+    }
+
+    #[test]
+    fn aead_object_forged() {
+        let cipher = Aead::new([1u8; 32]);
+        let nonce = [2u8; 24];
+
+        let (cipher_text, mac) = cipher.encrypt(nonce, "data".as_bytes(), "secret".as_bytes());
+        let ret = cipher.decrypt(nonce, "atad".as_bytes(), &cipher_text, mac);
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+}
@@ -0,0 +1,120 @@
+//! Single use nonces
+//!
+//! A [`Nonce`] can neither be copied nor cloned and is consumed by
+//! [`Aead::encrypt_once`](crate::aead::Aead::encrypt_once), so the compiler rejects code that
+//! encrypts two messages with the same nonce token.
+
+/// A nonce that can only be used for a single encryption.
+#[derive(Debug)]
+pub struct Nonce([u8; 24]);
+
+impl Nonce {
+    /// Returns the nonce bytes, e.g. to send them along with the cipher text.
+    pub fn as_bytes(&self) -> &[u8; 24] {
+        &self.0
+    }
+
+    /// Generates a nonce with the operating system's random number generator.
+    ///
+    /// Requires the `getrandom` feature.
+    #[cfg(feature = "getrandom")]
+    pub fn random() -> Result<Nonce, crate::random::Error> {
+        crate::random::bytes().map(Nonce)
+    }
+
+    pub(crate) fn into_bytes(self) -> [u8; 24] {
+        self.0
+    }
+}
+
+/// Counter based generator of unique nonces.
+///
+/// Nonces consist of a 16 byte prefix followed by a 64 bit little endian counter. Nonces are only
+/// unique as long as the same prefix is never reused with the same key, so create a fresh
+/// generator with a new random prefix whenever the counter state is lost.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{nonce::NonceGenerator, Aead};
+///
+/// let cipher = Aead::new([137u8; 32]);
+/// let mut nonces = NonceGenerator::new([1u8; 16]);
+///
+/// let nonce = nonces.next().unwrap();
+/// let (nonce, cipher_text, mac) = cipher.encrypt_once(nonce, "data".as_bytes(), "plaintext".as_bytes());
+/// cipher.decrypt(nonce, "data".as_bytes(), &cipher_text, mac).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct NonceGenerator {
+    prefix: [u8; 16],
+    counter: Option<u64>,
+}
+
+impl NonceGenerator {
+    /// Creates a generator starting at counter zero.
+    pub fn new(prefix: [u8; 16]) -> NonceGenerator {
+        NonceGenerator {
+            prefix,
+            counter: Some(0),
+        }
+    }
+}
+
+impl Iterator for NonceGenerator {
+    type Item = Nonce;
+
+    /// Returns the next nonce, or `None` once the counter is exhausted.
+    fn next(&mut self) -> Option<Nonce> {
+        let counter = self.counter?;
+        self.counter = counter.checked_add(1);
+
+        let mut nonce = [0u8; 24];
+        nonce[..16].copy_from_slice(&self.prefix);
+        nonce[16..].copy_from_slice(&counter.to_le_bytes());
+        Some(Nonce(nonce))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aead::{lock, Aead};
+
+    #[test]
+    fn unique() {
+        let mut nonces = NonceGenerator::new([1u8; 16]);
+        let a = nonces.next().unwrap();
+        let b = nonces.next().unwrap();
+
+        assert_ne!(a.as_bytes(), b.as_bytes());
+        assert_eq!(a.as_bytes()[..16], [1u8; 16]);
+    }
+
+    #[test]
+    fn exhausted() {
+        let mut nonces = NonceGenerator {
+            prefix: [0u8; 16],
+            counter: Some(u64::MAX),
+        };
+
+        assert!(nonces.next().is_some());
+        assert!(nonces.next().is_none());
+    }
+
+    #[test]
+    fn encrypt_once() {
+        let key = [1u8; 32];
+        let cipher = Aead::new(key);
+        let nonce = NonceGenerator::new([2u8; 16]).next().unwrap();
+        let nonce_bytes = *nonce.as_bytes();
+
+        let (used, cipher_text, mac) =
+            cipher.encrypt_once(nonce, "data".as_bytes(), "secret".as_bytes());
+        assert_eq!(used, nonce_bytes);
+        assert_eq!(
+            (cipher_text, mac),
+            lock::aead("secret".as_bytes(), key, nonce_bytes, "data".as_bytes())
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Length hiding padding
+//!
+//! Both schemes append the ISO/IEC 7816-4 marker byte `0x80` followed by zero bytes. They only
+//! differ in the padded length: a multiple of a block size, or the Padmé length which leaks at
+//! most `O(log log L)` bits of the message length. The building blocks are in
+//! [`utils::pad`](crate::utils::pad).
+
+use crate::utils;
+
+/// Padding applied to the plaintext before encryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingScheme {
+    /// Pads to a multiple of the given block size, a block size of 0 is treated as 1.
+    Iso7816(usize),
+    /// Pads to the next Padmé length.
+    Padme,
+}
+
+impl PaddingScheme {
+    // Length of a padded message of `len` bytes, including the marker byte.
+    pub(crate) fn padded_len(&self, len: usize) -> usize {
+        match *self {
+            PaddingScheme::Iso7816(block_size) => {
+                let block_size = block_size.max(1);
+                (len / block_size + 1) * block_size
+            }
+            PaddingScheme::Padme => utils::pad::padme_len(len + 1),
+        }
+    }
+}
+
+// Copies the plaintext into a new buffer and pads it. The padded length exceeds the plaintext
+// length, so padding to a single block of that size gives exactly the padded length.
+pub(crate) fn pad(plain_text: &[u8], scheme: PaddingScheme) -> Vec<u8> {
+    utils::pad::iso7816(plain_text, scheme.padded_len(plain_text.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aead::{lock, unlock, Error};
+
+    #[test]
+    fn padded_len() {
+        let padded = pad("secret".as_bytes(), PaddingScheme::Iso7816(16));
+        assert_eq!(padded.len(), 16);
+
+        let padded = pad(&[1u8; 16], PaddingScheme::Iso7816(16));
+        assert_eq!(padded.len(), 32);
+
+        let padded = pad(&[1u8; 100], PaddingScheme::Padme);
+        assert_eq!(padded.len(), 104);
+        assert_eq!(utils::pad::unpad_iso7816(&padded), Some(&[1u8; 100][..]));
+    }
+
+    #[test]
+    fn lock_unlock_padded() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+        let scheme = PaddingScheme::Iso7816(64);
+
+        let (cipher_text, mac) =
+            lock::aead_padded("secret".as_bytes(), key, nonce, "data".as_bytes(), scheme);
+        assert_eq!(cipher_text.len(), 64);
+
+        let plain_text =
+            unlock::aead_padded(&cipher_text, key, nonce, mac, "data".as_bytes(), scheme);
+        assert_eq!(plain_text.unwrap(), "secret".as_bytes());
+    }
+
+    #[test]
+    fn unlock_unpadded() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+
+        let (cipher_text, mac) = lock::aead(&[0u8; 8], key, nonce, &[]);
+        let ret = unlock::aead_padded(&cipher_text, key, nonce, mac, &[], PaddingScheme::Padme);
+        assert_eq!(ret, Err(Error::InvalidPadding));
+    }
+}
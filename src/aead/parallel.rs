@@ -0,0 +1,197 @@
+//! Chunked authenticated encryption for large buffers
+//!
+//! The plaintext is split into chunks of [`CHUNK_SIZE`] bytes. Every chunk is encrypted with its
+//! own key, derived with keyed BLAKE2b from the key, the nonce and the chunk index. The envelope
+//! holds the cipher text of each chunk followed by its MAC. A summary MAC over the additional
+//! data, the number of chunks and all chunk MACs protects against reordering and truncation.
+
+use monocypher_sys as ffi;
+use rayon::prelude::*;
+use std::mem;
+
+/// Size of a plaintext chunk.
+pub const CHUNK_SIZE: usize = 1 << 20;
+
+// Size of a chunk in the envelope.
+pub(crate) const SEALED_CHUNK_SIZE: usize = CHUNK_SIZE + 16;
+
+// Number of chunks needed for a plaintext of `len` bytes, an empty plaintext still gets a MAC.
+pub(crate) fn chunk_count(len: usize) -> usize {
+    len.div_ceil(CHUNK_SIZE).max(1)
+}
+
+// Derives the key of the chunk at `index`.
+pub(crate) fn chunk_key(key: &[u8; 32], nonce: &[u8; 24], index: u64) -> [u8; 32] {
+    unsafe {
+        let mut ctx = mem::MaybeUninit::<ffi::crypto_blake2b_ctx>::uninit();
+        ffi::crypto_blake2b_keyed_init(ctx.as_mut_ptr(), 32, key.as_ptr(), key.len());
+        let mut ctx = ctx.assume_init();
+        update(&mut ctx, b"chunk");
+        update(&mut ctx, nonce);
+        update(&mut ctx, &index.to_le_bytes());
+
+        let mut chunk_key = mem::MaybeUninit::<[u8; 32]>::uninit();
+        ffi::crypto_blake2b_final(&mut ctx, chunk_key.as_mut_ptr() as *mut u8);
+        chunk_key.assume_init()
+    }
+}
+
+// Computes the summary MAC over the additional data and the chunk MACs of `envelope`.
+pub(crate) fn summary_mac(
+    key: &[u8; 32],
+    nonce: &[u8; 24],
+    ad: &[u8],
+    envelope: &[u8],
+) -> [u8; 16] {
+    unsafe {
+        let mut ctx = mem::MaybeUninit::<ffi::crypto_blake2b_ctx>::uninit();
+        ffi::crypto_blake2b_keyed_init(ctx.as_mut_ptr(), 16, key.as_ptr(), key.len());
+        let mut ctx = ctx.assume_init();
+        update(&mut ctx, b"summary");
+        update(&mut ctx, nonce);
+        update(&mut ctx, &(ad.len() as u64).to_le_bytes());
+        update(&mut ctx, ad);
+        update(
+            &mut ctx,
+            &(envelope.len().div_ceil(SEALED_CHUNK_SIZE) as u64).to_le_bytes(),
+        );
+        for chunk in envelope.chunks(SEALED_CHUNK_SIZE) {
+            update(&mut ctx, &chunk[chunk.len().saturating_sub(16)..]);
+        }
+
+        let mut mac = mem::MaybeUninit::<[u8; 16]>::uninit();
+        ffi::crypto_blake2b_final(&mut ctx, mac.as_mut_ptr() as *mut u8);
+        mac.assume_init()
+    }
+}
+
+#[inline]
+fn update(ctx: &mut ffi::crypto_blake2b_ctx, data: &[u8]) {
+    unsafe { ffi::crypto_blake2b_update(ctx, data.as_ptr(), data.len()) }
+}
+
+// Encrypts all chunks of `plain_text` into `envelope` on the rayon thread pool.
+pub(crate) fn lock_chunks(
+    envelope: &mut [u8],
+    plain_text: &[u8],
+    key: &[u8; 32],
+    nonce: &[u8; 24],
+) {
+    envelope
+        .par_chunks_mut(SEALED_CHUNK_SIZE)
+        .enumerate()
+        .for_each(|(index, sealed)| {
+            let start = index * CHUNK_SIZE;
+            let chunk = &plain_text[start..plain_text.len().min(start + CHUNK_SIZE)];
+            let (cipher_text, mac) = sealed.split_at_mut(chunk.len());
+            let mut chunk_key = chunk_key(key, nonce, index as u64);
+            unsafe {
+                ffi::crypto_aead_lock(
+                    cipher_text.as_mut_ptr(),
+                    mac.as_mut_ptr(),
+                    chunk_key.as_ptr(),
+                    nonce.as_ptr(),
+                    std::ptr::null(),
+                    0,
+                    chunk.as_ptr(),
+                    chunk.len(),
+                );
+            }
+            crate::utils::wipe(&mut chunk_key);
+        });
+}
+
+// Decrypts all chunks of `envelope` into `plain_text` on the rayon thread pool.
+//
+// Returns false if any chunk fails to authenticate.
+pub(crate) fn unlock_chunks(
+    plain_text: &mut [u8],
+    envelope: &[u8],
+    key: &[u8; 32],
+    nonce: &[u8; 24],
+) -> bool {
+    plain_text
+        .par_chunks_mut(CHUNK_SIZE)
+        .zip(envelope.par_chunks(SEALED_CHUNK_SIZE))
+        .enumerate()
+        .map(|(index, (chunk, sealed))| {
+            let (cipher_text, mac) = sealed.split_at(chunk.len());
+            let mut chunk_key = chunk_key(key, nonce, index as u64);
+            let ret = unsafe {
+                ffi::crypto_aead_unlock(
+                    chunk.as_mut_ptr(),
+                    mac.as_ptr(),
+                    chunk_key.as_ptr(),
+                    nonce.as_ptr(),
+                    std::ptr::null(),
+                    0,
+                    cipher_text.as_ptr(),
+                    cipher_text.len(),
+                )
+            };
+            crate::utils::wipe(&mut chunk_key);
+            ret == 0
+        })
+        .all(|ok| ok)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::aead::{lock, unlock, Error};
+
+    use super::*;
+
+    #[test]
+    fn parallel_roundtrip() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+        let plain_text: Vec<u8> = (0..CHUNK_SIZE * 2 + 100).map(|i| i as u8).collect();
+
+        let (envelope, mac) = lock::aead_parallel(&plain_text, key, nonce, "data".as_bytes());
+        assert_eq!(envelope.len(), plain_text.len() + 3 * 16);
+
+        let clear = unlock::aead_parallel(&envelope, key, nonce, mac, "data".as_bytes()).unwrap();
+        assert_eq!(clear, plain_text);
+    }
+
+    #[test]
+    fn parallel_empty() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+
+        let (envelope, mac) = lock::aead_parallel(&[], key, nonce, &[]);
+        assert_eq!(envelope.len(), 16);
+
+        let clear = unlock::aead_parallel(&envelope, key, nonce, mac, &[]).unwrap();
+        assert!(clear.is_empty());
+    }
+
+    #[test]
+    fn parallel_truncated() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+        let plain_text = vec![3u8; CHUNK_SIZE + 1];
+
+        let (envelope, mac) = lock::aead_parallel(&plain_text, key, nonce, &[]);
+        let ret = unlock::aead_parallel(&envelope[..SEALED_CHUNK_SIZE], key, nonce, mac, &[]);
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn parallel_corrupt_chunk() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+        let plain_text = vec![3u8; CHUNK_SIZE + 1];
+
+        let (mut envelope, mac) = lock::aead_parallel(&plain_text, key, nonce, &[]);
+        envelope[0] ^= 1;
+        let ret = unlock::aead_parallel(&envelope, key, nonce, mac, &[]);
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn parallel_bad_length() {
+        let ret = unlock::aead_parallel(&[0u8; 15], [1u8; 32], [2u8; 24], [0u8; 16], &[]);
+        assert_eq!(ret, Err(Error::BadLength));
+    }
+}
@@ -0,0 +1,230 @@
+//! Authenticated encryption of message sequences with automatic rekeying
+//!
+//! [Official documentation](https://monocypher.org/manual/aead#INCREMENTAL_INTERFACE)
+
+use crate::aead::Error;
+use crate::utils;
+use monocypher_sys as ffi;
+use std::mem;
+use std::os::raw::c_void;
+
+/// Thresholds after which the session key is ratcheted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RekeyLimits {
+    /// Number of messages.
+    pub messages: u64,
+    /// Number of plaintext bytes.
+    pub bytes: u64,
+}
+
+impl Default for RekeyLimits {
+    fn default() -> Self {
+        Self {
+            messages: 1 << 20,
+            bytes: 1 << 36,
+        }
+    }
+}
+
+/// A sequence of messages encrypted with the incremental AEAD interface.
+///
+/// Messages must be decrypted in the order in which they were encrypted. Every message already
+/// changes the internal key, so a compromised session cannot decrypt previous messages. In
+/// addition the key is ratcheted with BLAKE2b once one of the [`RekeyLimits`] is reached, which
+/// bounds the amount of data processed under a single session key.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::SecureSession;
+///
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+/// let mut sender = SecureSession::new(key, nonce, Default::default());
+/// let mut receiver = SecureSession::new(key, nonce, Default::default());
+///
+/// let (cipher_text, mac) = sender.encrypt(&[], "plaintext".as_bytes());
+/// receiver.decrypt(&[], &cipher_text, mac).unwrap();
+/// ```
+pub struct SecureSession {
+    ctx: ffi::crypto_aead_ctx,
+    nonce: [u8; 24],
+    limits: RekeyLimits,
+    messages: u64,
+    bytes: u64,
+}
+
+impl SecureSession {
+    /// Creates a session with the given key, nonce and rekeying thresholds.
+    pub fn new(key: [u8; 32], nonce: [u8; 24], limits: RekeyLimits) -> SecureSession {
+        unsafe {
+            let mut ctx = mem::MaybeUninit::<ffi::crypto_aead_ctx>::uninit();
+            ffi::crypto_aead_init_x(ctx.as_mut_ptr(), key.as_ptr(), nonce.as_ptr());
+            SecureSession {
+                ctx: ctx.assume_init(),
+                nonce,
+                limits,
+                messages: 0,
+                bytes: 0,
+            }
+        }
+    }
+
+    /// Encrypts and authenticates the next message.
+    pub fn encrypt(&mut self, ad: &[u8], plain_text: &[u8]) -> (Vec<u8>, [u8; 16]) {
+        let mut cipher_text = vec![0u8; plain_text.len()];
+        let mut mac = [0u8; 16];
+        unsafe {
+            ffi::crypto_aead_write(
+                &mut self.ctx,
+                cipher_text.as_mut_ptr(),
+                mac.as_mut_ptr(),
+                ad.as_ptr(),
+                ad.len(),
+                plain_text.as_ptr(),
+                plain_text.len(),
+            );
+        }
+        self.count(plain_text.len());
+        (cipher_text, mac)
+    }
+
+    /// Decrypts the next message.
+    ///
+    /// A corrupt message leaves the session unchanged.
+    pub fn decrypt(
+        &mut self,
+        ad: &[u8],
+        cipher_text: &[u8],
+        mac: [u8; 16],
+    ) -> Result<Vec<u8>, Error> {
+        let mut plain_text = vec![0u8; cipher_text.len()];
+        unsafe {
+            if ffi::crypto_aead_read(
+                &mut self.ctx,
+                plain_text.as_mut_ptr(),
+                mac.as_ptr(),
+                ad.as_ptr(),
+                ad.len(),
+                cipher_text.as_ptr(),
+                cipher_text.len(),
+            ) != 0
+            {
+                return Err(Error::InvalidMac);
+            }
+        }
+        self.count(cipher_text.len());
+        Ok(plain_text)
+    }
+
+    // Accounts for a processed message and rekeys once a limit is reached.
+    fn count(&mut self, len: usize) {
+        self.messages += 1;
+        self.bytes = self.bytes.saturating_add(len as u64);
+        if self.messages >= self.limits.messages || self.bytes >= self.limits.bytes {
+            self.rekey();
+        }
+    }
+
+    // Replaces the session key with its BLAKE2b hash and restarts the message counter.
+    fn rekey(&mut self) {
+        let mut key = [0u8; 32];
+        unsafe {
+            ffi::crypto_blake2b(key.as_mut_ptr(), 32, self.ctx.key.as_ptr(), 32);
+            ffi::crypto_aead_init_x(&mut self.ctx, key.as_ptr(), self.nonce.as_ptr());
+        }
+        utils::wipe(&mut key);
+        self.messages = 0;
+        self.bytes = 0;
+    }
+}
+
+impl Drop for SecureSession {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::crypto_wipe(
+                &mut self.ctx as *mut ffi::crypto_aead_ctx as *mut c_void,
+                mem::size_of::<ffi::crypto_aead_ctx>(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aead::lock;
+
+    fn limits(messages: u64) -> RekeyLimits {
+        RekeyLimits {
+            messages,
+            bytes: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn first_message() {
+        let mut session = SecureSession::new([1u8; 32], [2u8; 24], Default::default());
+        assert_eq!(
+            session.encrypt("data".as_bytes(), "secret".as_bytes()),
+            lock::aead("secret".as_bytes(), [1u8; 32], [2u8; 24], "data".as_bytes())
+        );
+    }
+
+    #[test]
+    fn sequence() {
+        let mut sender = SecureSession::new([1u8; 32], [2u8; 24], limits(2));
+        let mut receiver = SecureSession::new([1u8; 32], [2u8; 24], limits(2));
+
+        for i in 0..5u8 {
+            let (cipher_text, mac) = sender.encrypt(&[i], &[i; 10]);
+            assert_eq!(receiver.decrypt(&[i], &cipher_text, mac).unwrap(), [i; 10]);
+        }
+    }
+
+    #[test]
+    fn rekeyed() {
+        let mut rekeyed = SecureSession::new([1u8; 32], [2u8; 24], limits(1));
+        let mut plain = SecureSession::new([1u8; 32], [2u8; 24], limits(u64::MAX));
+
+        assert_eq!(
+            rekeyed.encrypt(&[], &[0u8; 8]),
+            plain.encrypt(&[], &[0u8; 8])
+        );
+        assert_ne!(
+            rekeyed.encrypt(&[], &[0u8; 8]),
+            plain.encrypt(&[], &[0u8; 8])
+        );
+    }
+
+    #[test]
+    fn rekeyed_bytes() {
+        let byte_limits = RekeyLimits {
+            messages: u64::MAX,
+            bytes: 8,
+        };
+        let mut sender = SecureSession::new([1u8; 32], [2u8; 24], byte_limits);
+        let mut receiver = SecureSession::new([1u8; 32], [2u8; 24], byte_limits);
+
+        for _ in 0..3 {
+            let (cipher_text, mac) = sender.encrypt(&[], &[1u8; 5]);
+            assert_eq!(receiver.decrypt(&[], &cipher_text, mac).unwrap(), [1u8; 5]);
+        }
+    }
+
+    #[test]
+    fn corrupt_message() {
+        let mut sender = SecureSession::new([1u8; 32], [2u8; 24], Default::default());
+        let mut receiver = SecureSession::new([1u8; 32], [2u8; 24], Default::default());
+
+        let (cipher_text, mac) = sender.encrypt(&[], "secret".as_bytes());
+        assert_eq!(
+            receiver.decrypt(&[], &cipher_text, [0u8; 16]),
+            Err(Error::InvalidMac)
+        );
+        assert_eq!(
+            receiver.decrypt(&[], &cipher_text, mac).unwrap(),
+            "secret".as_bytes()
+        );
+    }
+}
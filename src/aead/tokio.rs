@@ -0,0 +1,331 @@
+//! Encrypted async streams
+//!
+//! [`EncryptedWriter`] splits the written data into frames of at most [`FRAME_SIZE`] bytes and
+//! encrypts them with a [`SecureSession`], [`EncryptedReader`] verifies and decrypts them. A frame
+//! consists of the plaintext length (4 bytes, little endian, authenticated as additional data),
+//! the MAC (16 bytes) and the cipher text. An empty frame marks the end of the stream, so a
+//! truncated stream is reported as [`io::ErrorKind::UnexpectedEof`].
+//!
+//! Requires the `tokio` feature.
+
+use crate::aead::session::{RekeyLimits, SecureSession};
+use crate::utils;
+use ::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+/// Maximum number of plaintext bytes in a frame.
+pub const FRAME_SIZE: usize = 16 * 1024;
+
+const HEADER_SIZE: usize = 4 + 16;
+
+/// Encrypts everything written to it into the inner writer.
+///
+/// The stream must be shut down to write the end of stream marker.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::tokio::{EncryptedReader, EncryptedWriter};
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> std::io::Result<()> {
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+///
+/// let mut writer = EncryptedWriter::new(Vec::new(), key, nonce);
+/// writer.write_all("plaintext".as_bytes()).await?;
+/// writer.shutdown().await?;
+/// let cipher_text = writer.into_inner();
+///
+/// let mut reader = EncryptedReader::new(cipher_text.as_slice(), key, nonce);
+/// let mut plaintext = Vec::new();
+/// reader.read_to_end(&mut plaintext).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct EncryptedWriter<W> {
+    inner: W,
+    session: SecureSession,
+    plain_text: PlainText,
+    frame: Vec<u8>,
+    written: usize,
+    finished: bool,
+}
+
+impl<W: AsyncWrite + Unpin> EncryptedWriter<W> {
+    /// Creates a writer that encrypts into `inner`.
+    pub fn new(inner: W, key: [u8; 32], nonce: [u8; 24]) -> EncryptedWriter<W> {
+        EncryptedWriter {
+            inner,
+            session: SecureSession::new(key, nonce, RekeyLimits::default()),
+            plain_text: PlainText(Vec::with_capacity(FRAME_SIZE)),
+            frame: Vec::with_capacity(HEADER_SIZE + FRAME_SIZE),
+            written: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    // Encrypts the buffered plaintext into the next frame.
+    fn seal_frame(&mut self) {
+        let len = (self.plain_text.len() as u32).to_le_bytes();
+        let (cipher_text, mac) = self.session.encrypt(&len, &self.plain_text);
+        utils::wipe(&mut self.plain_text);
+        self.plain_text.clear();
+
+        self.frame.clear();
+        self.frame.extend_from_slice(&len);
+        self.frame.extend_from_slice(&mac);
+        self.frame.extend_from_slice(&cipher_text);
+        self.written = 0;
+    }
+
+    // Writes the pending frame to the inner writer.
+    fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.written < self.frame.len() {
+            let n = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.frame[self.written..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.written += n;
+        }
+        self.frame.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for EncryptedWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        ready!(this.poll_frame(cx))?;
+
+        let n = buf.len().min(FRAME_SIZE - this.plain_text.len());
+        this.plain_text.extend_from_slice(&buf[..n]);
+        if this.plain_text.len() == FRAME_SIZE {
+            this.seal_frame();
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_frame(cx))?;
+        if !this.plain_text.is_empty() {
+            this.seal_frame();
+            ready!(this.poll_frame(cx))?;
+        }
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        let this = self.get_mut();
+        if !this.finished {
+            this.seal_frame();
+            this.finished = true;
+        }
+        ready!(this.poll_frame(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+/// Decrypts and verifies the frames read from the inner reader.
+///
+/// A corrupt frame is reported as [`io::ErrorKind::InvalidData`].
+pub struct EncryptedReader<R> {
+    inner: R,
+    session: SecureSession,
+    frame: Vec<u8>,
+    filled: usize,
+    plain_text: PlainText,
+    read: usize,
+    finished: bool,
+}
+
+impl<R: AsyncRead + Unpin> EncryptedReader<R> {
+    /// Creates a reader that decrypts from `inner`.
+    pub fn new(inner: R, key: [u8; 32], nonce: [u8; 24]) -> EncryptedReader<R> {
+        EncryptedReader {
+            inner,
+            session: SecureSession::new(key, nonce, RekeyLimits::default()),
+            frame: vec![0u8; HEADER_SIZE],
+            filled: 0,
+            plain_text: PlainText(Vec::new()),
+            read: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    // Reads from the inner reader until the frame buffer is full.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.filled < self.frame.len() {
+            let mut buf = ReadBuf::new(&mut self.frame[self.filled..]);
+            ready!(Pin::new(&mut self.inner).poll_read(cx, &mut buf))?;
+            let n = buf.filled().len();
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            self.filled += n;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    // Reads and decrypts the next frame.
+    fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_fill(cx))?;
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&self.frame[..4]);
+        let body_len = u32::from_le_bytes(len) as usize;
+        if body_len > FRAME_SIZE {
+            return Poll::Ready(Err(io::ErrorKind::InvalidData.into()));
+        }
+        self.frame.resize(HEADER_SIZE + body_len, 0);
+        ready!(self.poll_fill(cx))?;
+
+        let mut mac = [0u8; 16];
+        mac.copy_from_slice(&self.frame[4..HEADER_SIZE]);
+        let plain_text = self
+            .session
+            .decrypt(&len, &self.frame[HEADER_SIZE..], mac)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        self.plain_text = PlainText(plain_text);
+        self.read = 0;
+        self.finished = body_len == 0;
+        self.frame.truncate(HEADER_SIZE);
+        self.filled = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for EncryptedReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.read == this.plain_text.len() && !this.finished {
+            ready!(this.poll_frame(cx))?;
+        }
+
+        let n = buf.remaining().min(this.plain_text.len() - this.read);
+        buf.put_slice(&this.plain_text[this.read..this.read + n]);
+        this.read += n;
+        Poll::Ready(Ok(()))
+    }
+}
+
+// Plaintext buffer that is wiped when dropped.
+struct PlainText(Vec<u8>);
+
+impl Deref for PlainText {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for PlainText {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Drop for PlainText {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn encrypt(plain_text: &[u8]) -> Vec<u8> {
+        let mut writer = EncryptedWriter::new(Vec::new(), [1u8; 32], [2u8; 24]);
+        writer.write_all(plain_text).await.unwrap();
+        writer.shutdown().await.unwrap();
+        writer.into_inner()
+    }
+
+    async fn decrypt(cipher_text: &[u8]) -> io::Result<Vec<u8>> {
+        let mut reader = EncryptedReader::new(cipher_text, [1u8; 32], [2u8; 24]);
+        let mut plain_text = Vec::new();
+        reader.read_to_end(&mut plain_text).await?;
+        Ok(plain_text)
+    }
+
+    #[::tokio::test]
+    async fn roundtrip() {
+        let plain_text: Vec<u8> = (0..FRAME_SIZE * 2 + 7).map(|i| i as u8).collect();
+        let cipher_text = encrypt(&plain_text).await;
+        assert_eq!(cipher_text.len(), plain_text.len() + 4 * HEADER_SIZE);
+
+        assert_eq!(decrypt(&cipher_text).await.unwrap(), plain_text);
+    }
+
+    #[::tokio::test]
+    async fn empty() {
+        let cipher_text = encrypt(&[]).await;
+        assert_eq!(cipher_text.len(), HEADER_SIZE);
+
+        assert!(decrypt(&cipher_text).await.unwrap().is_empty());
+    }
+
+    #[::tokio::test]
+    async fn duplex() {
+        let (client, server) = ::tokio::io::duplex(64);
+        let mut writer = EncryptedWriter::new(client, [1u8; 32], [2u8; 24]);
+        let mut reader = EncryptedReader::new(server, [1u8; 32], [2u8; 24]);
+
+        let send = async {
+            writer.write_all(&[3u8; 1000]).await.unwrap();
+            writer.shutdown().await.unwrap();
+        };
+        let mut plain_text = Vec::new();
+        let recv = reader.read_to_end(&mut plain_text);
+        let (_, received) = ::tokio::join!(send, recv);
+
+        assert_eq!(received.unwrap(), 1000);
+        assert_eq!(plain_text, [3u8; 1000]);
+    }
+
+    #[::tokio::test]
+    async fn truncated() {
+        let cipher_text = encrypt("secret".as_bytes()).await;
+        let ret = decrypt(&cipher_text[..cipher_text.len() - HEADER_SIZE]).await;
+        assert_eq!(ret.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[::tokio::test]
+    async fn corrupt() {
+        let mut cipher_text = encrypt("secret".as_bytes()).await;
+        cipher_text[HEADER_SIZE] ^= 1;
+        let ret = decrypt(&cipher_text).await;
+        assert_eq!(ret.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
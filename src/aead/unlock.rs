@@ -1,7 +1,34 @@
 //! Authenticated decryption w/o additional data
 
+#[cfg(feature = "rayon")]
+use crate::aead::parallel;
+use crate::aead::{Error, MessageKeys, PaddingScheme};
+use crate::utils::{self, pad};
 use monocypher_sys as ffi;
 
+/// Decrypt ciphertext.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{lock, unlock};
+///
+/// let plaintext = "plaintext";
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+///
+/// let cymac = lock::easy(plaintext.as_bytes(), key, nonce);
+/// unlock::easy(&cymac.0, key, nonce, cymac.1).unwrap();
+/// ```
+pub fn easy(
+    cipher_text: &[u8],
+    key: [u8; 32],
+    nonce: [u8; 24],
+    mac: [u8; 16],
+) -> Result<Vec<u8>, Error> {
+    aead(cipher_text, key, nonce, mac, &[])
+}
+
 /// Decrypt ciphertext with additional data.
 ///
 /// # Example
@@ -23,7 +50,7 @@ pub fn aead(
     nonce: [u8; 24],
     mac: [u8; 16],
     ad: &[u8],
-) -> Result<Vec<u8>, String> {
+) -> Result<Vec<u8>, Error> {
     unsafe {
         let mut plain_text: Vec<u8> = vec![0u8; cipher_text.len()];
         if ffi::crypto_aead_unlock(
@@ -39,6 +66,212 @@ pub fn aead(
         {
             return Ok(plain_text);
         }
-        Err("Message is corrupt.".to_owned())
+        Err(Error::InvalidMac)
+    }
+}
+
+/// Decrypt ciphertext with additional data and strip its padding.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{lock, unlock, PaddingScheme};
+///
+/// let plaintext = "plaintext";
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+/// let ad = "data";
+/// let scheme = PaddingScheme::Iso7816(64);
+///
+/// let cymac = lock::aead_padded(plaintext.as_bytes(), key, nonce, ad.as_bytes(), scheme);
+/// unlock::aead_padded(&cymac.0, key, nonce, cymac.1, ad.as_bytes(), scheme).unwrap();
+/// ```
+pub fn aead_padded(
+    cipher_text: &[u8],
+    key: [u8; 32],
+    nonce: [u8; 24],
+    mac: [u8; 16],
+    ad: &[u8],
+    scheme: PaddingScheme,
+) -> Result<Vec<u8>, Error> {
+    let mut plain_text = aead(cipher_text, key, nonce, mac, ad)?;
+    match pad::unpad_iso7816(&plain_text).map(<[u8]>::len) {
+        Some(len) if scheme.padded_len(len) == plain_text.len() => {
+            utils::wipe(&mut plain_text[len..]);
+            plain_text.truncate(len);
+            Ok(plain_text)
+        }
+        _ => {
+            utils::wipe(&mut plain_text);
+            Err(Error::InvalidPadding)
+        }
+    }
+}
+
+/// Decrypts cipher text and authenticates additional data that are split into several parts.
+///
+/// The parts are processed as if they were concatenated.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{lock, unlock};
+///
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+/// let header: [&[u8]; 2] = ["version".as_bytes(), "type".as_bytes()];
+///
+/// let cymac = lock::aead_vectored(&["plain".as_bytes(), "text".as_bytes()], key, nonce, &header);
+/// unlock::aead_vectored(&[&cymac.0], key, nonce, cymac.1, &header).unwrap();
+/// ```
+pub fn aead_vectored(
+    cipher_text: &[&[u8]],
+    key: [u8; 32],
+    nonce: [u8; 24],
+    mac: [u8; 16],
+    ad: &[&[u8]],
+) -> Result<Vec<u8>, Error> {
+    let mut plain_text = cipher_text.concat();
+    let keys = MessageKeys::new(&key, &nonce);
+    let real_mac = keys.mac(ad, &plain_text);
+    unsafe {
+        if ffi::crypto_verify16(mac.as_ptr(), real_mac.as_ptr()) != 0 {
+            return Err(Error::InvalidMac);
+        }
+    }
+    keys.apply_keystream(&mut plain_text);
+    Ok(plain_text)
+}
+
+/// Decrypt an envelope produced by [`lock::aead_parallel`](crate::aead::lock::aead_parallel).
+///
+/// The summary MAC is checked before any chunk is decrypted.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{lock, unlock};
+///
+/// let plaintext = vec![0u8; 4 << 20];
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+///
+/// let (envelope, mac) = lock::aead_parallel(&plaintext, key, nonce, "data".as_bytes());
+/// unlock::aead_parallel(&envelope, key, nonce, mac, "data".as_bytes()).unwrap();
+/// ```
+#[cfg(feature = "rayon")]
+pub fn aead_parallel(
+    envelope: &[u8],
+    key: [u8; 32],
+    nonce: [u8; 24],
+    mac: [u8; 16],
+    ad: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let last_chunk = envelope.len() % parallel::SEALED_CHUNK_SIZE;
+    if envelope.is_empty() || (1..16).contains(&last_chunk) {
+        return Err(Error::BadLength);
+    }
+    let chunks = envelope.len().div_ceil(parallel::SEALED_CHUNK_SIZE);
+
+    let real_mac = parallel::summary_mac(&key, &nonce, ad, envelope);
+    unsafe {
+        if ffi::crypto_verify16(mac.as_ptr(), real_mac.as_ptr()) != 0 {
+            return Err(Error::InvalidMac);
+        }
+    }
+
+    let mut plain_text = vec![0u8; envelope.len() - chunks * 16];
+    if !parallel::unlock_chunks(&mut plain_text, envelope, &key, &nonce) {
+        utils::wipe(&mut plain_text);
+        return Err(Error::InvalidMac);
+    }
+    Ok(plain_text)
+}
+
+/// Authenticates cipher text and additional data without decrypting it.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::{lock, verify_only};
+///
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+/// let ad = "data";
+///
+/// let cymac = lock::aead("plaintext".as_bytes(), key, nonce, ad.as_bytes());
+/// verify_only(&cymac.0, &key, &nonce, &cymac.1, ad.as_bytes()).unwrap();
+/// ```
+pub fn verify_only(
+    cipher_text: &[u8],
+    key: &[u8; 32],
+    nonce: &[u8; 24],
+    mac: &[u8; 16],
+    ad: &[u8],
+) -> Result<(), Error> {
+    let real_mac = MessageKeys::new(key, nonce).mac(&[ad], cipher_text);
+    unsafe {
+        if ffi::crypto_verify16(mac.as_ptr(), real_mac.as_ptr()) == 0 {
+            return Ok(());
+        }
+    }
+    Err(Error::InvalidMac)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aead::lock;
+
+    #[test]
+    fn verify_only_valid() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+        let (cipher_text, mac) = lock::aead("secret".as_bytes(), key, nonce, "data".as_bytes());
+
+        assert!(verify_only(&cipher_text, &key, &nonce, &mac, "data".as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn verify_only_forged() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+        let (mut cipher_text, mac) = lock::aead("secret".as_bytes(), key, nonce, "data".as_bytes());
+
+        let ret = verify_only(&cipher_text, &key, &nonce, &mac, "atad".as_bytes());
+        assert_eq!(ret, Err(Error::InvalidMac));
+
+        cipher_text[0] ^= 1;
+        let ret = verify_only(&cipher_text, &key, &nonce, &mac, "data".as_bytes());
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn vectored() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+        let (cipher_text, mac) = lock::aead("secret".as_bytes(), key, nonce, "data".as_bytes());
+
+        let ad: [&[u8]; 3] = ["d".as_bytes(), "".as_bytes(), "ata".as_bytes()];
+        let plain_text = aead_vectored(
+            &[&cipher_text[..2], &cipher_text[2..]],
+            key,
+            nonce,
+            mac,
+            &ad,
+        )
+        .unwrap();
+        assert_eq!(plain_text, "secret".as_bytes());
+    }
+
+    #[test]
+    fn vectored_forged() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 24];
+        let (cipher_text, mac) = lock::aead("secret".as_bytes(), key, nonce, "data".as_bytes());
+
+        let ad: [&[u8]; 2] = ["da".as_bytes(), "at".as_bytes()];
+        let ret = aead_vectored(&[&cipher_text], key, nonce, mac, &ad);
+        assert_eq!(ret, Err(Error::InvalidMac));
     }
 }
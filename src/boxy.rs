@@ -0,0 +1,145 @@
+//! Authenticated public key encryption
+//!
+//! Analogous to NaCl's `crypto_box`: the sender encrypts with its secret key and the recipient's
+//! public key, the recipient decrypts with its secret key and the sender's public key. The key is
+//! the BLAKE2b hash of the X25519 shared secret, the sender's and the recipient's public key, the
+//! message is encrypted with XChaCha20-Poly1305. A valid box proves that it was sealed by the
+//! sender, or by the recipient itself.
+//!
+//! The nonce must never repeat for the same pair of keys, random nonces are fine.
+
+use crate::aead::{lock as aead_lock, unlock as aead_unlock};
+use crate::hashing::blake2b;
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::utils;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Public key has a low order.")]
+    LowOrder,
+    #[error("Message is corrupt.")]
+    InvalidMac,
+}
+
+/// Encrypts the plaintext from `sender_sk` to `recipient_pk`.
+///
+/// Returns the ciphertext and the MAC.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::boxy::{lock, unlock};
+/// use monocypher::key_exchange::SecretKey;
+///
+/// let alice = SecretKey::from([1u8; 32]);
+/// let bob = SecretKey::from([2u8; 32]);
+/// let nonce = [3u8; 24];
+///
+/// let (cipher_text, mac) = lock("plaintext".as_bytes(), nonce, &bob.public_key(), &alice).unwrap();
+/// let plain_text = unlock(&cipher_text, nonce, mac, &alice.public_key(), &bob).unwrap();
+/// assert_eq!(plain_text, "plaintext".as_bytes());
+/// ```
+pub fn lock(
+    plain_text: &[u8],
+    nonce: [u8; 24],
+    recipient_pk: &PublicKey,
+    sender_sk: &SecretKey,
+) -> Result<(Vec<u8>, [u8; 16]), Error> {
+    let mut key = box_key(
+        sender_sk,
+        recipient_pk,
+        &sender_sk.public_key(),
+        recipient_pk,
+    )?;
+    let ret = aead_lock::easy(plain_text, key, nonce);
+    utils::wipe(&mut key);
+    Ok(ret)
+}
+
+/// Decrypts a box from `sender_pk` to `recipient_sk`.
+pub fn unlock(
+    cipher_text: &[u8],
+    nonce: [u8; 24],
+    mac: [u8; 16],
+    sender_pk: &PublicKey,
+    recipient_sk: &SecretKey,
+) -> Result<Vec<u8>, Error> {
+    let mut key = box_key(
+        recipient_sk,
+        sender_pk,
+        sender_pk,
+        &recipient_sk.public_key(),
+    )?;
+    let ret = aead_unlock::easy(cipher_text, key, nonce, mac);
+    utils::wipe(&mut key);
+    ret.map_err(|_| Error::InvalidMac)
+}
+
+// Hashes the shared secret with both public keys, so the key differs for each direction.
+fn box_key(
+    secret_key: &SecretKey,
+    their_public_key: &PublicKey,
+    sender_pk: &PublicKey,
+    recipient_pk: &PublicKey,
+) -> Result<[u8; 32], Error> {
+    let shared_secret = secret_key
+        .diffie_hellman(their_public_key)
+        .map_err(|_| Error::LowOrder)?;
+    let mut ctx = blake2b::Context::<32>::new_n();
+    ctx.update(&*shared_secret);
+    ctx.update(&**sender_pk);
+    ctx.update(&**recipient_pk);
+    let mut key = [0u8; 32];
+    ctx.finalize_into(&mut key);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lock_unlock() {
+        let alice = SecretKey::from([1u8; 32]);
+        let bob = SecretKey::from([2u8; 32]);
+        let (cipher_text, mac) =
+            lock("secret".as_bytes(), [3u8; 24], &bob.public_key(), &alice).unwrap();
+        let plain_text = unlock(&cipher_text, [3u8; 24], mac, &alice.public_key(), &bob).unwrap();
+        assert_eq!(plain_text, "secret".as_bytes());
+    }
+
+    #[test]
+    fn wrong_sender() {
+        let alice = SecretKey::from([1u8; 32]);
+        let bob = SecretKey::from([2u8; 32]);
+        let eve = SecretKey::from([4u8; 32]);
+        let (cipher_text, mac) =
+            lock("secret".as_bytes(), [3u8; 24], &bob.public_key(), &alice).unwrap();
+        let ret = unlock(&cipher_text, [3u8; 24], mac, &eve.public_key(), &bob);
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn directional() {
+        let alice = SecretKey::from([1u8; 32]);
+        let bob = SecretKey::from([2u8; 32]);
+        let (cipher_text, mac) =
+            lock("secret".as_bytes(), [3u8; 24], &bob.public_key(), &alice).unwrap();
+        // The same box presented as coming from bob to alice.
+        let ret = unlock(&cipher_text, [3u8; 24], mac, &bob.public_key(), &alice);
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn low_order() {
+        let alice = SecretKey::from([1u8; 32]);
+        let ret = lock(
+            "secret".as_bytes(),
+            [3u8; 24],
+            &PublicKey::from([0u8; 32]),
+            &alice,
+        );
+        assert_eq!(ret, Err(Error::LowOrder));
+    }
+}
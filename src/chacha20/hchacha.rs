@@ -0,0 +1,124 @@
+//! HChaCha20 subkey derivation for XChaCha20 based constructions.
+
+use crate::aead::Key;
+use crate::utils;
+use derive_more::From;
+use monocypher_sys as ffi;
+use std::ops::Deref;
+
+/// A 32 byte subkey derived with HChaCha20 that is wiped when dropped.
+pub struct SubKey([u8; 32]);
+
+impl_zeroize!(SubKey);
+
+impl Deref for SubKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for SubKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// A 24 byte XChaCha20 nonce.
+#[derive(Clone, Copy, Debug, From, PartialEq, Eq)]
+pub struct XNonce([u8; 24]);
+
+impl XNonce {
+    /// Splits the nonce into the HChaCha20 input and the 8 byte ChaCha20 nonce.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::aead::Key;
+    /// use monocypher::chacha20::{hchacha20, Context, XNonce};
+    ///
+    /// let key = Key::from([137u8; 32]);
+    /// let nonce = XNonce::from([120u8; 24]);
+    ///
+    /// let (input, djb_nonce) = nonce.split();
+    /// let sub_key = hchacha20(&key, input);
+    /// let mut ctx = Context::new(*sub_key, *djb_nonce);
+    /// ```
+    pub fn split(&self) -> (&[u8; 16], &[u8; 8]) {
+        let (input, nonce) = self.0.split_at(16);
+        (input.try_into().unwrap(), nonce.try_into().unwrap())
+    }
+}
+
+impl Deref for XNonce {
+    type Target = [u8; 24];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Derives a subkey from a key and the first 16 bytes of an XChaCha20 nonce.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::Key;
+/// use monocypher::chacha20::hchacha20;
+///
+/// let key = Key::from([137u8; 32]);
+/// let sub_key = hchacha20(&key, &[120u8; 16]);
+/// ```
+pub fn hchacha20(key: &Key, input: &[u8; 16]) -> SubKey {
+    let mut sub_key = [0u8; 32];
+    unsafe {
+        ffi::crypto_chacha20_h(sub_key.as_mut_ptr(), key.as_ptr(), input.as_ptr());
+    }
+    SubKey(sub_key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chacha20::Context;
+
+    // draft-irtf-cfrg-xchacha section 2.2.1
+    #[test]
+    fn hchacha20_vector() {
+        let key: Vec<u8> = (0..32).collect();
+        let key = Key::from(<[u8; 32]>::try_from(key).unwrap());
+        let input = [
+            0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00, 0x31, 0x41,
+            0x59, 0x27,
+        ];
+        assert_eq!(
+            hex::encode(*hchacha20(&key, &input)),
+            "82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc"
+        );
+    }
+
+    #[test]
+    fn split() {
+        let mut bytes = [0u8; 24];
+        bytes[15] = 1;
+        bytes[16] = 2;
+        let nonce = XNonce::from(bytes);
+
+        let (input, djb_nonce) = nonce.split();
+        assert_eq!(input[15], 1);
+        assert_eq!(djb_nonce[0], 2);
+    }
+
+    #[test]
+    fn matches_x() {
+        let nonce = XNonce::from([2u8; 24]);
+        let (input, djb_nonce) = nonce.split();
+        let sub_key = hchacha20(&Key::from([1u8; 32]), input);
+
+        assert_eq!(
+            Context::new(*sub_key, *djb_nonce).encrypt(&[3u8; 100]).0,
+            Context::new_x([1u8; 32], [2u8; 24]).encrypt(&[3u8; 100]).0
+        );
+    }
+}
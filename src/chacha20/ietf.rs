@@ -0,0 +1,220 @@
+//! IETF ChaCha20 with a 96 bit nonce and a 32 bit block counter (RFC 8439)
+//!
+//! The 32 bit counter limits a single nonce to 256 GiB of key stream.
+
+// Key stream available to a single nonce, 2^32 blocks of 64 bytes.
+pub(super) const STREAM_LEN: u64 = 1 << 38;
+
+use crate::chacha20::{Blocks, KeyStream};
+use monocypher_sys as ffi;
+use std::mem::MaybeUninit;
+
+/// IETF ChaCha20 context.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::chacha20::ietf::Context;
+///
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 12];
+///
+/// let mut ctx = Context::new(key, nonce);
+/// ctx.set_ctr(1);
+/// let (cipher_text, ctr) = ctx.encrypt("plaintext".as_bytes());
+/// assert_eq!(ctr, 1);
+/// ```
+///
+/// # Panics
+///
+/// The methods producing key stream panic instead of wrapping the counter around if the
+/// request goes past the end of the key stream, where it would repeat from block 0. The
+/// `cipher-traits` implementation returns an error instead.
+pub struct Context(pub(super) KeyStream<Ietf>);
+
+impl Context {
+    /// Initializes a context with a 96 bit nonce.
+    pub fn new(key: [u8; 32], nonce: [u8; 12]) -> Context {
+        Context(KeyStream::new(key, Ietf { nonce, ctr: 0 }))
+    }
+
+    /// Encrypts the plaintext.
+    ///
+    /// Returns the ciphertext and the counter after the operation, see
+    /// [`current_ctr`](Self::current_ctr).
+    pub fn encrypt(&mut self, plain_text: &[u8]) -> (Vec<u8>, u64) {
+        self.check_len(plain_text.len());
+        let cipher_text = self.0.encrypt(plain_text);
+        (cipher_text, self.current_ctr())
+    }
+
+    /// Decrypts the ciphertext.
+    ///
+    /// Returns the plaintext and the counter after the operation, see
+    /// [`current_ctr`](Self::current_ctr).
+    pub fn decrypt(&mut self, cipher_text: &[u8]) -> (Vec<u8>, u64) {
+        self.check_len(cipher_text.len());
+        let plain_text = self.0.encrypt(cipher_text);
+        (plain_text, self.current_ctr())
+    }
+
+    /// Encrypts or decrypts the text in place.
+    ///
+    /// Returns the counter after the operation, see [`current_ctr`](Self::current_ctr).
+    pub fn apply_keystream(&mut self, text: &mut [u8]) -> u64 {
+        self.check_len(text.len());
+        self.0.apply_keystream(text);
+        self.current_ctr()
+    }
+
+    /// Fills the buffer with raw key stream.
+    ///
+    /// Returns the counter after the operation, see [`current_ctr`](Self::current_ctr).
+    pub fn stream(&mut self, stream: &mut [u8]) -> u64 {
+        self.check_len(stream.len());
+        self.0.stream(stream);
+        self.current_ctr()
+    }
+
+    /// Fills an uninitialized buffer with raw key stream and returns it as initialized bytes.
+    ///
+    /// This saves zeroing a freshly allocated buffer that is overwritten anyway.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::chacha20::ietf::Context;
+    ///
+    /// let mut ctx = Context::new([137u8; 32], [120u8; 12]);
+    /// let mut buffer = Vec::with_capacity(4096);
+    /// let stream = ctx.stream_uninit(buffer.spare_capacity_mut());
+    /// assert_eq!(stream.len(), 4096);
+    /// ```
+    pub fn stream_uninit<'a>(&mut self, out: &'a mut [MaybeUninit<u8>]) -> &'a [u8] {
+        self.check_len(out.len());
+        self.0.stream_uninit(out)
+    }
+
+    /// Returns the counter of the block that holds the next key stream byte.
+    ///
+    /// After processing a multiple of 64 bytes this is the counter Monocypher returns, a
+    /// context created with the same key and nonce and set to this counter continues the key
+    /// stream at the start of that block. It is 2^32 once the whole key stream is used.
+    pub fn current_ctr(&self) -> u64 {
+        if self.0.mid_block() {
+            self.0.blocks.ctr - 1
+        } else {
+            self.0.blocks.ctr
+        }
+    }
+
+    /// Sets the block counter, the next output starts at the beginning of that block.
+    pub fn set_ctr(&mut self, ctr: u32) {
+        self.0.blocks.ctr = u64::from(ctr);
+        self.0.discard_block();
+    }
+
+    // Byte position of the next key stream byte, at most the stream length.
+    pub(super) fn position(&self) -> u64 {
+        self.current_ctr() * 64 + u64::from(self.0.block_offset())
+    }
+
+    // Panics if less than `len` bytes of key stream are left.
+    fn check_len(&self, len: usize) {
+        assert!(
+            len as u64 <= STREAM_LEN - self.position(),
+            "IETF ChaCha20 key stream exhausted"
+        );
+    }
+}
+
+pub(crate) struct Ietf {
+    nonce: [u8; 12],
+    // Index of the next block. Monocypher's counter is 32 bits, keeping 64 tells the end of the
+    // key stream after 2^32 blocks apart from its start.
+    pub(super) ctr: u64,
+}
+
+impl Blocks for Ietf {
+    unsafe fn blocks(&mut self, key: &[u8; 32], output: *mut u8, input: *const u8, len: usize) {
+        ffi::crypto_chacha20_ietf(
+            output,
+            input,
+            len,
+            key.as_ptr(),
+            self.nonce.as_ptr(),
+            self.ctr as u32,
+        );
+        self.ctr += (len / 64) as u64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // RFC 8439 section 2.4.2
+    #[test]
+    fn rfc8439() {
+        let key: Vec<u8> = (0..32).collect();
+        let nonce = [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let plain_text =
+            "Ladies and Gentlemen of the class of '99: If I could offer you only one tip \
+                          for the future, sunscreen would be it.";
+
+        let mut ctx = Context::new(key.try_into().unwrap(), nonce);
+        ctx.set_ctr(1);
+        assert_eq!(
+            hex::encode(ctx.encrypt(plain_text.as_bytes()).0),
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0bf91b65c5524733ab8f593d\
+             abcd62b3571639d624e65152ab8f530c359f0861d807ca0dbf500d6a6156a38e088a22b65e52bc514d16cc\
+             f806818ce91ab77937365af90bbf74a35be6b40b8eedf2785e42874d"
+        );
+    }
+
+    #[test]
+    fn split() {
+        let (whole, _) = Context::new([1u8; 32], [2u8; 12]).encrypt(&[3u8; 150]);
+
+        let mut ctx = Context::new([1u8; 32], [2u8; 12]);
+        let (mut parts, _) = ctx.encrypt(&[3u8; 70]);
+        parts.extend(ctx.encrypt(&[3u8; 80]).0);
+        assert_eq!(parts, whole);
+    }
+
+    #[test]
+    fn current_ctr() {
+        let mut ctx = Context::new([1u8; 32], [2u8; 12]);
+        ctx.set_ctr(7);
+        assert_eq!(ctx.stream(&mut [0u8; 64]), 8);
+        assert_eq!(ctx.stream(&mut [0u8; 1]), 8);
+        assert_eq!(ctx.encrypt(&[0u8; 63]).1, 9);
+        assert_eq!(ctx.current_ctr(), 9);
+    }
+
+    #[test]
+    fn end_of_stream() {
+        let mut ctx = Context::new([1u8; 32], [2u8; 12]);
+        ctx.set_ctr(u32::MAX);
+        assert_eq!(ctx.stream(&mut [0u8; 10]), u64::from(u32::MAX));
+        assert_eq!(ctx.apply_keystream(&mut [0u8; 54]), 1 << 32);
+        assert_eq!(ctx.encrypt(&[]), (vec![], 1 << 32));
+    }
+
+    #[test]
+    #[should_panic(expected = "key stream exhausted")]
+    fn past_end_of_stream() {
+        let mut ctx = Context::new([1u8; 32], [2u8; 12]);
+        ctx.set_ctr(u32::MAX);
+        ctx.encrypt(&[0u8; 65]);
+    }
+
+    #[test]
+    #[should_panic(expected = "key stream exhausted")]
+    fn wrap_around() {
+        let mut ctx = Context::new([1u8; 32], [2u8; 12]);
+        ctx.set_ctr(u32::MAX);
+        ctx.stream(&mut [0u8; 64]);
+        ctx.stream(&mut [0u8; 1]);
+    }
+}
@@ -0,0 +1,418 @@
+//! ChaCha20 stream cipher
+//!
+//! Raw stream cipher access, for authenticated encryption use the [`aead`](crate::aead) module.
+//!
+//! [Official documentation](https://monocypher.org/manual/advanced/chacha20)
+
+use crate::aead::Key;
+use crate::utils;
+use monocypher_sys as ffi;
+use std::mem::MaybeUninit;
+use std::{ptr, slice};
+
+mod hchacha;
+pub mod ietf;
+#[cfg(feature = "rand_core")]
+mod rng;
+#[cfg(feature = "cipher-traits")]
+mod traits;
+
+pub use hchacha::{hchacha20, SubKey, XNonce};
+#[cfg(feature = "rand_core")]
+pub use rng::ChaChaRng;
+
+/// ChaCha20 context with a 64 bit nonce and a 64 bit block counter.
+///
+/// The context keeps track of its position in the key stream, so encrypting a message in several
+/// parts gives the same result as encrypting it at once.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::chacha20::Context;
+///
+/// let key = [137u8; 32];
+/// let nonce = [120u8; 24];
+///
+/// let mut ctx = Context::new_x(key, nonce);
+/// let (cipher_text, _) = ctx.encrypt("plaintext".as_bytes());
+///
+/// let mut ctx = Context::new_x(key, nonce);
+/// let (plain_text, _) = ctx.decrypt(&cipher_text);
+/// ```
+pub struct Context(KeyStream<Djb>);
+
+impl Context {
+    /// Initializes a ChaCha20 context with a 64 bit nonce.
+    pub fn new(key: [u8; 32], nonce: [u8; 8]) -> Context {
+        Context(KeyStream::new(key, Djb { nonce, ctr: 0 }))
+    }
+
+    /// Initializes an XChaCha20 context with a 192 bit nonce.
+    ///
+    /// XChaCha20 derives a subkey from the key and the first 16 bytes of the nonce with HChaCha20,
+    /// so random nonces can be used safely.
+    pub fn new_x(key: [u8; 32], nonce: [u8; 24]) -> Context {
+        let nonce = XNonce::from(nonce);
+        let (input, djb_nonce) = nonce.split();
+        let sub_key = hchacha20(&Key::from(key), input);
+        Context::new(*sub_key, *djb_nonce)
+    }
+
+    /// Encrypts the plaintext.
+    ///
+    /// Returns the ciphertext and the counter after the operation, see
+    /// [`current_ctr`](Self::current_ctr).
+    pub fn encrypt(&mut self, plain_text: &[u8]) -> (Vec<u8>, u64) {
+        let cipher_text = self.0.encrypt(plain_text);
+        (cipher_text, self.current_ctr())
+    }
+
+    /// Decrypts the ciphertext.
+    ///
+    /// Returns the plaintext and the counter after the operation, see
+    /// [`current_ctr`](Self::current_ctr).
+    pub fn decrypt(&mut self, cipher_text: &[u8]) -> (Vec<u8>, u64) {
+        let plain_text = self.0.encrypt(cipher_text);
+        (plain_text, self.current_ctr())
+    }
+
+    /// Encrypts or decrypts the text in place.
+    ///
+    /// Returns the counter after the operation, see [`current_ctr`](Self::current_ctr).
+    pub fn apply_keystream(&mut self, text: &mut [u8]) -> u64 {
+        self.0.apply_keystream(text);
+        self.current_ctr()
+    }
+
+    /// Fills the buffer with raw key stream.
+    ///
+    /// Returns the counter after the operation, see [`current_ctr`](Self::current_ctr).
+    pub fn stream(&mut self, stream: &mut [u8]) -> u64 {
+        self.0.stream(stream);
+        self.current_ctr()
+    }
+
+    /// Fills an uninitialized buffer with raw key stream and returns it as initialized bytes.
+    ///
+    /// This saves zeroing a freshly allocated buffer that is overwritten anyway.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::chacha20::Context;
+    ///
+    /// let mut ctx = Context::new_x([137u8; 32], [120u8; 24]);
+    /// let mut buffer = Vec::with_capacity(4096);
+    /// let stream = ctx.stream_uninit(buffer.spare_capacity_mut());
+    /// assert_eq!(stream.len(), 4096);
+    /// ```
+    pub fn stream_uninit<'a>(&mut self, out: &'a mut [MaybeUninit<u8>]) -> &'a [u8] {
+        self.0.stream_uninit(out)
+    }
+
+    /// Returns the counter of the block that holds the next key stream byte.
+    ///
+    /// After processing a multiple of 64 bytes this is the counter Monocypher returns, a
+    /// context created with the same key and nonce and set to this counter continues the key
+    /// stream at the start of that block.
+    pub fn current_ctr(&self) -> u64 {
+        if self.0.mid_block() {
+            self.0.blocks.ctr.wrapping_sub(1)
+        } else {
+            self.0.blocks.ctr
+        }
+    }
+
+    /// Sets the block counter, the next output starts at the beginning of that block.
+    pub fn set_ctr(&mut self, ctr: u64) {
+        self.0.blocks.ctr = ctr;
+        self.0.discard_block();
+    }
+
+    /// Moves to a byte position in the key stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::chacha20::Context;
+    ///
+    /// let key = [137u8; 32];
+    /// let nonce = [120u8; 24];
+    ///
+    /// let (cipher_text, _) = Context::new_x(key, nonce).encrypt(&[0u8; 1000]);
+    ///
+    /// let mut ctx = Context::new_x(key, nonce);
+    /// ctx.seek(100);
+    /// assert_eq!(ctx.decrypt(&cipher_text[100..]).0, [0u8; 900]);
+    /// ```
+    pub fn seek(&mut self, byte_offset: u64) {
+        self.set_ctr(byte_offset / 64);
+        self.0.skip_in_block((byte_offset % 64) as usize);
+    }
+}
+
+// Block function of a ChaCha20 variant along with its nonce and block counter.
+trait Blocks {
+    // Processes `len` bytes, a multiple of the block size, and advances the counter.
+    //
+    // `output` and `input` must be valid for `len` bytes and may be equal, `input` may be null.
+    unsafe fn blocks(&mut self, key: &[u8; 32], output: *mut u8, input: *const u8, len: usize);
+}
+
+struct Djb {
+    nonce: [u8; 8],
+    ctr: u64,
+}
+
+impl Blocks for Djb {
+    unsafe fn blocks(&mut self, key: &[u8; 32], output: *mut u8, input: *const u8, len: usize) {
+        self.ctr = ffi::crypto_chacha20_djb(
+            output,
+            input,
+            len,
+            key.as_ptr(),
+            self.nonce.as_ptr(),
+            self.ctr,
+        );
+    }
+}
+
+// Key stream that can be consumed at byte granularity.
+struct KeyStream<B> {
+    key: [u8; 32],
+    blocks: B,
+    block: [u8; 64],
+    block_idx: usize,
+}
+
+impl<B: Blocks> KeyStream<B> {
+    fn new(key: [u8; 32], blocks: B) -> KeyStream<B> {
+        KeyStream {
+            key,
+            blocks,
+            block: [0u8; 64],
+            block_idx: 64,
+        }
+    }
+
+    fn encrypt(&mut self, plain_text: &[u8]) -> Vec<u8> {
+        let mut cipher_text = plain_text.to_vec();
+        self.apply_keystream(&mut cipher_text);
+        cipher_text
+    }
+
+    fn apply_keystream(&mut self, text: &mut [u8]) {
+        let text_ptr = text.as_mut_ptr();
+        unsafe { self.process(text_ptr, text_ptr, text.len()) }
+    }
+
+    fn stream(&mut self, stream: &mut [u8]) {
+        unsafe { self.process(stream.as_mut_ptr(), ptr::null(), stream.len()) }
+    }
+
+    fn stream_uninit<'a>(&mut self, out: &'a mut [MaybeUninit<u8>]) -> &'a [u8] {
+        let len = out.len();
+        let out = out.as_mut_ptr() as *mut u8;
+        unsafe {
+            self.process(out, ptr::null(), len);
+            slice::from_raw_parts(out, len)
+        }
+    }
+
+    // Whether part of the current block has already been used.
+    fn mid_block(&self) -> bool {
+        self.block_idx < 64
+    }
+
+    // Position of the next key stream byte inside its block.
+    fn block_offset(&self) -> u8 {
+        if self.mid_block() {
+            self.block_idx as u8
+        } else {
+            0
+        }
+    }
+
+    // Drops the rest of the buffered block, so the next output starts at the block counter.
+    fn discard_block(&mut self) {
+        utils::wipe(&mut self.block);
+        self.block_idx = 64;
+    }
+
+    // Skips the first `offset` bytes of the block at the counter, `offset` is less than a block.
+    fn skip_in_block(&mut self, offset: usize) {
+        if offset > 0 {
+            unsafe {
+                self.blocks
+                    .blocks(&self.key, self.block.as_mut_ptr(), ptr::null(), 64);
+            }
+            self.block_idx = offset;
+        }
+    }
+
+    // Writes `input` xor key stream to `output`, or the key stream alone if `input` is null.
+    //
+    // `output` and `input` must be valid for `len` bytes, and may be equal.
+    unsafe fn process(&mut self, output: *mut u8, input: *const u8, len: usize) {
+        let mut offset = 0;
+        while offset < len && self.block_idx < 64 {
+            let key_byte = self.block[self.block_idx];
+            *output.add(offset) = if input.is_null() {
+                key_byte
+            } else {
+                *input.add(offset) ^ key_byte
+            };
+            self.block_idx += 1;
+            offset += 1;
+        }
+
+        let full_blocks = (len - offset) / 64 * 64;
+        if full_blocks > 0 {
+            self.blocks.blocks(
+                &self.key,
+                output.add(offset),
+                input_at(input, offset),
+                full_blocks,
+            );
+            offset += full_blocks;
+        }
+
+        if offset < len {
+            self.blocks
+                .blocks(&self.key, self.block.as_mut_ptr(), ptr::null(), 64);
+            self.block_idx = 0;
+            self.process(output.add(offset), input_at(input, offset), len - offset);
+        }
+    }
+}
+
+impl<B> Drop for KeyStream<B> {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.key);
+        utils::wipe(&mut self.block);
+    }
+}
+
+// Offsets a possibly null input pointer.
+#[inline]
+unsafe fn input_at(input: *const u8, offset: usize) -> *const u8 {
+    if input.is_null() {
+        input
+    } else {
+        input.add(offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn djb() {
+        let mut ctx = Context::new([1u8; 32], [2u8; 8]);
+        assert_eq!(
+            ctx.encrypt("secret".as_bytes()).0,
+            [161, 46, 182, 12, 78, 118]
+        );
+    }
+
+    #[test]
+    fn x() {
+        let mut ctx = Context::new_x([1u8; 32], [2u8; 24]);
+        assert_eq!(
+            ctx.encrypt("secret".as_bytes()).0,
+            [197, 222, 109, 147, 186, 152]
+        );
+    }
+
+    #[test]
+    fn split() {
+        let plain_text = [3u8; 200];
+        let (whole, _) = Context::new([1u8; 32], [2u8; 8]).encrypt(&plain_text);
+
+        let mut ctx = Context::new([1u8; 32], [2u8; 8]);
+        let (mut parts, _) = ctx.encrypt(&plain_text[..10]);
+        parts.extend(ctx.encrypt(&plain_text[10..150]).0);
+        parts.extend(ctx.encrypt(&plain_text[150..]).0);
+        assert_eq!(parts, whole);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let (cipher_text, _) = Context::new_x([1u8; 32], [2u8; 24]).encrypt(&[3u8; 100]);
+        let (plain_text, _) = Context::new_x([1u8; 32], [2u8; 24]).decrypt(&cipher_text);
+        assert_eq!(plain_text, [3u8; 100]);
+    }
+
+    #[test]
+    fn stream() {
+        let mut stream = [0u8; 70];
+        Context::new([1u8; 32], [2u8; 8]).stream(&mut stream);
+        assert_eq!(
+            stream.to_vec(),
+            Context::new([1u8; 32], [2u8; 8]).encrypt(&[0u8; 70]).0
+        );
+    }
+
+    #[test]
+    fn stream_uninit() {
+        let mut stream = [0u8; 100];
+        Context::new([1u8; 32], [2u8; 8]).stream(&mut stream);
+
+        let mut ctx = Context::new([1u8; 32], [2u8; 8]);
+        let mut buffer = [MaybeUninit::uninit(); 100];
+        assert_eq!(ctx.stream_uninit(&mut buffer[..30]), &stream[..30]);
+        assert_eq!(ctx.stream_uninit(&mut buffer[30..]), &stream[30..]);
+    }
+
+    #[test]
+    fn set_ctr() {
+        let mut stream = [0u8; 128];
+        Context::new([1u8; 32], [2u8; 8]).stream(&mut stream);
+
+        let mut ctx = Context::new([1u8; 32], [2u8; 8]);
+        ctx.encrypt(&[0u8; 10]);
+        ctx.set_ctr(1);
+        assert_eq!(ctx.encrypt(&[0u8; 64]).0, stream[64..]);
+    }
+
+    #[test]
+    fn seek() {
+        let mut stream = [0u8; 200];
+        Context::new([1u8; 32], [2u8; 8]).stream(&mut stream);
+
+        let mut ctx = Context::new([1u8; 32], [2u8; 8]);
+        ctx.stream(&mut [0u8; 10]);
+        ctx.seek(70);
+        assert_eq!(ctx.current_ctr(), 1);
+        assert_eq!(ctx.encrypt(&[0u8; 130]).0, stream[70..]);
+
+        ctx.seek(128);
+        assert_eq!(ctx.encrypt(&[0u8; 72]).0, stream[128..]);
+    }
+
+    #[test]
+    fn current_ctr() {
+        let mut ctx = Context::new([1u8; 32], [2u8; 8]);
+        assert_eq!(ctx.current_ctr(), 0);
+        assert_eq!(ctx.stream(&mut [0u8; 128]), 2);
+        assert_eq!(ctx.stream(&mut [0u8; 10]), 2);
+        assert_eq!(ctx.apply_keystream(&mut [0u8; 54]), 3);
+        assert_eq!(ctx.encrypt(&[0u8; 100]).1, 4);
+        assert_eq!(ctx.decrypt(&[0u8; 28]).1, 5);
+        assert_eq!(ctx.current_ctr(), 5);
+    }
+
+    #[test]
+    fn resume() {
+        let mut stream = [0u8; 192];
+        Context::new([1u8; 32], [2u8; 8]).stream(&mut stream);
+
+        let mut ctx = Context::new([1u8; 32], [2u8; 8]);
+        let ctr = ctx.stream(&mut [0u8; 128]);
+        let mut resumed = Context::new([1u8; 32], [2u8; 8]);
+        resumed.set_ctr(ctr);
+        assert_eq!(resumed.encrypt(&[0u8; 64]).0, stream[128..]);
+    }
+}
@@ -0,0 +1,76 @@
+//! Deterministic random number generator on top of the ChaCha20 key stream.
+
+use crate::chacha20::Context;
+use rand_core::{impls, CryptoRng, Error, RngCore, SeedableRng};
+
+/// Cryptographically secure, seedable random number generator.
+///
+/// The output is the ChaCha20 key stream of the seed with an all zero nonce, so the same seed
+/// always produces the same sequence.
+///
+/// Requires the `rand_core` feature.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::chacha20::ChaChaRng;
+/// use rand_core::{RngCore, SeedableRng};
+///
+/// let mut rng = ChaChaRng::from_seed([1u8; 32]);
+/// let value = rng.next_u64();
+/// ```
+pub struct ChaChaRng(Context);
+
+impl SeedableRng for ChaChaRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        ChaChaRng(Context::new(seed, [0u8; 8]))
+    }
+}
+
+impl RngCore for ChaChaRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.stream(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for ChaChaRng {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let mut a = ChaChaRng::from_seed([1u8; 32]);
+        let mut b = ChaChaRng::from_seed([1u8; 32]);
+        assert_eq!(a.next_u64(), b.next_u64());
+
+        let mut c = ChaChaRng::from_seed([2u8; 32]);
+        assert_ne!(a.next_u64(), c.next_u64());
+    }
+
+    #[test]
+    fn key_stream() {
+        let mut stream = [0u8; 12];
+        Context::new([1u8; 32], [0u8; 8]).stream(&mut stream);
+
+        let mut rng = ChaChaRng::from_seed([1u8; 32]);
+        assert_eq!(rng.next_u32().to_le_bytes(), stream[..4]);
+        assert_eq!(rng.next_u64().to_le_bytes(), stream[4..]);
+    }
+}
@@ -0,0 +1,132 @@
+//! [`cipher`] stream cipher traits for the ChaCha20 contexts.
+
+use crate::chacha20::ietf::{self, STREAM_LEN as IETF_STREAM_LEN};
+use crate::chacha20::Context;
+use ::cipher::inout::InOutBuf;
+use ::cipher::{OverflowError, SeekNum, StreamCipher, StreamCipherError, StreamCipherSeek};
+
+impl StreamCipher for Context {
+    fn try_apply_keystream_inout(
+        &mut self,
+        buf: InOutBuf<'_, '_, u8>,
+    ) -> Result<(), StreamCipherError> {
+        let len = buf.len();
+        let (input, output) = buf.into_raw();
+        unsafe { self.0.process(output, input, len) }
+        Ok(())
+    }
+}
+
+impl StreamCipherSeek for Context {
+    fn try_current_pos<T: SeekNum>(&self) -> Result<T, OverflowError> {
+        T::from_block_byte(self.0.blocks.ctr, self.0.block_offset(), 64)
+    }
+
+    fn try_seek<T: SeekNum>(&mut self, pos: T) -> Result<(), StreamCipherError> {
+        let (ctr, offset) = pos
+            .into_block_byte::<u64>(64)
+            .map_err(|_| StreamCipherError)?;
+        self.set_ctr(ctr);
+        self.0.skip_in_block(offset as usize);
+        Ok(())
+    }
+}
+
+impl StreamCipher for ietf::Context {
+    fn try_apply_keystream_inout(
+        &mut self,
+        buf: InOutBuf<'_, '_, u8>,
+    ) -> Result<(), StreamCipherError> {
+        let remaining = IETF_STREAM_LEN.saturating_sub(self.position());
+        if buf.len() as u64 > remaining {
+            return Err(StreamCipherError);
+        }
+        let len = buf.len();
+        let (input, output) = buf.into_raw();
+        unsafe { self.0.process(output, input, len) }
+        Ok(())
+    }
+}
+
+impl StreamCipherSeek for ietf::Context {
+    fn try_current_pos<T: SeekNum>(&self) -> Result<T, OverflowError> {
+        T::from_block_byte(self.0.blocks.ctr, self.0.block_offset(), 64)
+    }
+
+    fn try_seek<T: SeekNum>(&mut self, pos: T) -> Result<(), StreamCipherError> {
+        let (ctr, offset) = pos
+            .into_block_byte::<u64>(64)
+            .map_err(|_| StreamCipherError)?;
+        if ctr * 64 + u64::from(offset) > IETF_STREAM_LEN {
+            return Err(StreamCipherError);
+        }
+        self.0.blocks.ctr = ctr;
+        self.0.discard_block();
+        self.0.skip_in_block(offset as usize);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apply_keystream() {
+        let (cipher_text, _) = Context::new_x([1u8; 32], [2u8; 24]).encrypt(&[3u8; 100]);
+
+        let mut text = [3u8; 100];
+        StreamCipher::apply_keystream(&mut Context::new_x([1u8; 32], [2u8; 24]), &mut text);
+        assert_eq!(text.to_vec(), cipher_text);
+    }
+
+    #[test]
+    fn seek() {
+        let mut stream = [0u8; 200];
+        Context::new_x([1u8; 32], [2u8; 24]).stream(&mut stream);
+
+        let mut ctx = Context::new_x([1u8; 32], [2u8; 24]);
+        ctx.seek(130u64);
+        assert_eq!(ctx.current_pos::<u64>(), 130);
+        assert_eq!(ctx.encrypt(&[0u8; 70]).0, stream[130..]);
+        assert_eq!(ctx.current_pos::<u64>(), 200);
+    }
+
+    #[test]
+    fn ietf_seek() {
+        let mut stream = [0u8; 100];
+        ietf::Context::new([1u8; 32], [2u8; 12]).stream(&mut stream);
+
+        let mut ctx = ietf::Context::new([1u8; 32], [2u8; 12]);
+        ctx.seek(64u32);
+        assert_eq!(ctx.current_pos::<u32>(), 64);
+        ctx.seek(5u32);
+        assert_eq!(ctx.encrypt(&[0u8; 95]).0, stream[5..]);
+    }
+
+    #[test]
+    fn ietf_end_of_stream() {
+        let mut ctx = ietf::Context::new([1u8; 32], [2u8; 12]);
+        ctx.seek(IETF_STREAM_LEN - 10);
+        assert_eq!(ctx.current_pos::<u64>(), IETF_STREAM_LEN - 10);
+        assert!(ctx.try_apply_keystream(&mut [0u8; 11]).is_err());
+        assert!(ctx.try_apply_keystream(&mut [0u8; 10]).is_ok());
+        assert_eq!(ctx.current_pos::<u64>(), IETF_STREAM_LEN);
+        assert!(ctx.try_apply_keystream(&mut [0u8; 1]).is_err());
+        assert!(ctx.try_seek(IETF_STREAM_LEN + 1).is_err());
+    }
+
+    #[test]
+    fn ietf_last_block() {
+        let mut last = [0u8; 64];
+        let mut ctx = ietf::Context::new([1u8; 32], [2u8; 12]);
+        ctx.set_ctr(u32::MAX);
+        ctx.stream(&mut last);
+
+        let mut ctx = ietf::Context::new([1u8; 32], [2u8; 12]);
+        ctx.seek(IETF_STREAM_LEN - 20);
+        let mut text = [0u8; 20];
+        ctx.apply_keystream(&mut text);
+        assert_eq!(text, last[44..]);
+    }
+}
@@ -2,48 +2,152 @@
 //!
 //! [Official documentation](https://monocypher.org/manual/optional/ed25519)
 
-use crate::{Error, KeyPair, PrivKey, PubKey, PubPrivKey, Seed, Signature};
+use crate::fingerprint::Fingerprint;
+use crate::hashing::sha512;
+use crate::signing::{Ed25519SigningKey, Ed25519VerifyingKey};
+use crate::{utils, Error, KeyPair, PrivKey, PubKey, PubPrivKey, Seed, Signature};
 use derive_more::From;
 use monocypher_sys as ffi;
-use std::mem;
+use std::io::{self, Read};
 
-#[derive(Debug, From)]
+mod stream;
+
+pub use stream::{SigningStream, VerifyingStream};
+
+/// The seed followed by the public key.
+///
+/// A private key is generated with [`KeyPair::generate_key_pair`] or decoded with a check that
+/// the public half belongs to the seed. Signing with a wrong public half would leak the secret
+/// scalar, so there is no unchecked conversion from bytes:
+///
+/// ```compile_fail
+/// let private_key = monocypher::ed25519::PrivateKey::from([0u8; 64]);
+/// ```
+#[derive(Debug)]
 pub struct PrivateKey([u8; 64]);
 
+impl_bytes!(
+    PrivateKey,
+    64,
+    crate::signing::check_key_pair::<sha512::Context>
+);
+impl_ct_eq!(PrivateKey);
+impl_zeroize!(PrivateKey);
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
 #[derive(Debug, From)]
 pub struct PublicKey([u8; 32]);
 
+impl_bytes!(PublicKey, 32);
+impl_ct_eq!(PublicKey);
+
+// Prefix of the hashes of Ed25519ctx, RFC 8032 section 5.1.
+const DOM2_PREFIX: &[u8] = b"SigEd25519 no Ed25519 collisions";
+
+impl PrivateKey {
+    /// Signs the concatenation of the parts without copying them into one buffer.
+    pub fn sign_parts(&self, parts: &[&[u8]]) -> Signature {
+        Ed25519SigningKey::from(self).sign_parts(parts)
+    }
+
+    /// Signs a message with Ed25519ctx, the context separates the signatures of different uses
+    /// of the same key.
+    ///
+    /// The context is 1 to 255 bytes long, RFC 8032 does not allow an empty context for
+    /// Ed25519ctx. The signature only verifies with [`PublicKey::check_with_context`] and the
+    /// same context.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::ed25519;
+    /// use monocypher::{KeyPair, PubPrivKey, Seed};
+    ///
+    /// let keypair: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+    ///     KeyPair::generate_key_pair(Seed::from([2u8; 32]));
+    ///
+    /// let signature = keypair
+    ///     .private_key
+    ///     .sign_with_context("message".as_bytes(), "backups".as_bytes())
+    ///     .unwrap();
+    /// keypair
+    ///     .public_key
+    ///     .check_with_context(signature, "message".as_bytes(), "backups".as_bytes())
+    ///     .unwrap();
+    /// ```
+    pub fn sign_with_context(&self, message: &[u8], context: &[u8]) -> Result<Signature, Error> {
+        let dom2 = dom2(context)?;
+        let signing_key = Ed25519SigningKey::from(self);
+        Ok(signing_key.sign_domain(&[&dom2], &[message]))
+    }
+}
+
+impl PublicKey {
+    /// Checks a signature of the concatenation of the parts.
+    pub fn check_parts(&self, signature: Signature, parts: &[&[u8]]) -> Result<(), Error> {
+        Ed25519VerifyingKey::from(self.0).check_parts(signature, parts)
+    }
+
+    /// Checks a signature of a message read to the end of `reader`, in a single pass.
+    ///
+    /// Read errors are returned as [`Error::Io`].
+    pub fn check_reader<R: Read>(&self, signature: Signature, mut reader: R) -> Result<(), Error> {
+        let mut verifier = VerifyingStream::new(self, &signature);
+        io::copy(&mut reader, &mut verifier)?;
+        verifier.finish()
+    }
+
+    /// Returns the fingerprint of the key, to compare it manually.
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new("ed25519", &self.0)
+    }
+
+    /// Checks a signature and rejects malleable encodings, see
+    /// [`pubkey::PublicKey::check_strict`](crate::pubkey::PublicKey::check_strict).
+    pub fn check_strict(&self, signature: Signature, message: &[u8]) -> Result<(), Error> {
+        Ed25519VerifyingKey::from(self.0).check_strict(signature, message)
+    }
+
+    /// Checks an Ed25519ctx signature made with [`PrivateKey::sign_with_context`].
+    ///
+    /// Fails with [`Error::Context`] if the context is empty or longer than 255 bytes.
+    pub fn check_with_context(
+        &self,
+        signature: Signature,
+        message: &[u8],
+        context: &[u8],
+    ) -> Result<(), Error> {
+        let dom2 = dom2(context)?;
+        Ed25519VerifyingKey::from(self.0).check_domain(signature, &[&dom2], &[message])
+    }
+}
+
+// dom2(0, context) of RFC 8032, the context is 1 to 255 bytes long.
+fn dom2(context: &[u8]) -> Result<Vec<u8>, Error> {
+    if !(1..=255).contains(&context.len()) {
+        return Err(Error::Context);
+    }
+    let mut dom2 = DOM2_PREFIX.to_vec();
+    dom2.extend_from_slice(&[0, context.len() as u8]);
+    dom2.extend_from_slice(context);
+    Ok(dom2)
+}
+
 impl PrivKey for PrivateKey {
     /// Signs a message with the secret_key.
     fn sign(&self, message: &[u8]) -> Signature {
-        unsafe {
-            let mut signature = mem::MaybeUninit::<[u8; 64]>::uninit();
-            ffi::crypto_ed25519_sign(
-                signature.as_mut_ptr() as *mut u8,
-                self.0.as_ptr(),
-                message.as_ptr(),
-                message.len(),
-            );
-
-            Signature::from(signature.assume_init())
-        }
+        Ed25519SigningKey::from(self).sign(message)
     }
 }
 
 impl PubKey for PublicKey {
     fn check(&self, signature: crate::Signature, message: &[u8]) -> Result<(), crate::Error> {
-        unsafe {
-            if ffi::crypto_ed25519_check(
-                signature.as_ptr(),
-                self.0.as_ptr(),
-                message.as_ptr(),
-                message.len(),
-            ) == 0
-            {
-                return Ok(());
-            }
-            Err(Error::Signature)
-        }
+        Ed25519VerifyingKey::from(self.0).check(signature, message)
     }
 }
 
@@ -61,7 +165,7 @@ impl PubPrivKey for KeyPair<PrivateKey, PublicKey> {
         }
 
         Self {
-            private_key: PrivateKey::from(private_key),
+            private_key: PrivateKey(private_key),
             public_key: PublicKey::from(public_key),
         }
     }
@@ -71,7 +175,7 @@ impl PubPrivKey for KeyPair<PrivateKey, PublicKey> {
 mod test {
     use crate::{
         ed25519::{self, KeyPair},
-        PubPrivKey, Seed,
+        Error, PubPrivKey, Seed, Signature,
     };
 
     #[test]
@@ -89,6 +193,27 @@ mod test {
         );
     }
 
+    #[test]
+    fn private_key_bytes_test() {
+        let keypair: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([2u8; 32]));
+        let private_key = ed25519::PrivateKey::from_hex(&keypair.private_key.to_hex()).unwrap();
+        assert_eq!(private_key.as_bytes(), keypair.private_key.as_bytes());
+
+        let mut mismatch = keypair.private_key.to_bytes();
+        mismatch[32] ^= 1;
+        let ret = ed25519::PrivateKey::try_from(&mismatch[..]);
+        assert!(matches!(ret, Err(Error::KeyMismatch)));
+
+        // The seed with the public key of another key pair.
+        let other: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([3u8; 32]));
+        let mut foreign = keypair.private_key.to_bytes();
+        foreign[32..].copy_from_slice(other.public_key.as_bytes());
+        let ret = ed25519::PrivateKey::try_from(&foreign[..]);
+        assert!(matches!(ret, Err(Error::KeyMismatch)));
+    }
+
     #[test]
     fn sign_test() {
         let seed = Seed::from([2u8; 32]);
@@ -121,6 +246,77 @@ mod test {
         assert_eq!(ret.is_ok(), true)
     }
 
+    #[test]
+    fn context_vector() {
+        // RFC 8032 section 7.2, the context "foo".
+        let seed: [u8; 32] =
+            hex::decode("0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let keypair: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from(seed));
+        let message = hex::decode("f726936d19c800494e3fdaff20b276a8").unwrap();
+
+        let sig = keypair
+            .private_key
+            .sign_with_context(&message, "foo".as_bytes())
+            .unwrap();
+        assert_eq!(
+            hex::encode(*sig),
+            "55a4cc2f70a54e04288c5f4cd1e45a7bb520b36292911876cada7323198dd87a\
+             8b36950b95130022907a7fb7c4e9b2d5f6cca685a587b4b21f4b888e4e7edb0d"
+        );
+        let ret = keypair
+            .public_key
+            .check_with_context(sig, &message, "foo".as_bytes());
+        assert!(ret.is_ok());
+    }
+
+    #[test]
+    fn context_separates() {
+        let keypair: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([2u8; 32]));
+        let sig = keypair
+            .private_key
+            .sign_with_context("test".as_bytes(), "app".as_bytes())
+            .unwrap();
+        assert_eq!(
+            hex::encode(*sig),
+            "984902b3499d213e90b0ed792669df92beb45513d22eee82d8a1f32dd84e867c\
+             0511477da4cc8f8685cd4d3122f14c670ca914df1889765db068decbafa7cb06"
+        );
+
+        let forged = Signature::from(*sig);
+        let ret =
+            keypair
+                .public_key
+                .check_with_context(forged, "test".as_bytes(), "other".as_bytes());
+        assert!(matches!(ret, Err(Error::Signature)));
+        assert!(keypair.check(sig, "test".as_bytes()).is_err());
+
+        let ret = keypair
+            .private_key
+            .sign_with_context("test".as_bytes(), &[0u8; 256]);
+        assert!(matches!(ret, Err(Error::Context)));
+    }
+
+    #[test]
+    fn empty_context() {
+        let keypair: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([2u8; 32]));
+        let ret = keypair
+            .private_key
+            .sign_with_context("test".as_bytes(), &[]);
+        assert!(matches!(ret, Err(Error::Context)));
+
+        let sig = keypair.sign("test".as_bytes());
+        let ret = keypair
+            .public_key
+            .check_with_context(sig, "test".as_bytes(), &[]);
+        assert!(matches!(ret, Err(Error::Context)));
+    }
+
     #[test]
     fn check_forged() {
         let seed = Seed::from([2u8; 32]);
@@ -133,4 +329,30 @@ mod test {
 
         assert_eq!(ret.is_err(), true)
     }
+
+    #[test]
+    fn check_strict() {
+        let keypair: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([2u8; 32]));
+        let sig = keypair.sign("test".as_bytes());
+        assert!(keypair
+            .public_key
+            .check_strict(sig, "test".as_bytes())
+            .is_ok());
+
+        // Adding L to S keeps the equation true, but Monocypher rejects it already.
+        let mut sig = keypair.sign("test".as_bytes()).to_bytes();
+        let mut carry = 0u16;
+        let l = hex::decode("edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010")
+            .unwrap();
+        for (s, l) in sig[32..].iter_mut().zip(l) {
+            let sum = *s as u16 + l as u16 + carry;
+            *s = sum as u8;
+            carry = sum >> 8;
+        }
+        let ret = keypair
+            .public_key
+            .check_strict(Signature::from(sig), "test".as_bytes());
+        assert!(ret.is_err());
+    }
 }
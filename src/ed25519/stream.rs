@@ -0,0 +1,75 @@
+//! Signatures of streams too large for memory
+//!
+//! The streams are the ones of [`signing`](crate::signing) with SHA-512, with the same interface as
+//! the ones of [`pubkey`](crate::pubkey).
+
+use crate::ed25519::{PrivateKey, PublicKey};
+use crate::hashing::sha512::Context;
+use crate::signing;
+use crate::Signature;
+
+/// Signs messages read from a seekable reader.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::ed25519::{self, SigningStream, VerifyingStream};
+/// use monocypher::{KeyPair, PubPrivKey, Seed};
+/// use std::io::{self, Cursor};
+///
+/// let keypair: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+///     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+/// let artifact = vec![7u8; 1 << 20];
+///
+/// let signature = SigningStream::new(&keypair.private_key)
+///     .sign(Cursor::new(&artifact))
+///     .unwrap();
+///
+/// let mut verifier = VerifyingStream::new(&keypair.public_key, &signature);
+/// io::copy(&mut &artifact[..], &mut verifier).unwrap();
+/// verifier.finish().unwrap();
+/// ```
+pub type SigningStream<'a> = signing::SigningStream<'a, Context>;
+
+/// Checks a signature of a message fed in chunks.
+pub type VerifyingStream = signing::VerifyingStream<Context>;
+
+impl<'a> SigningStream<'a> {
+    pub fn new(private_key: &'a PrivateKey) -> SigningStream<'a> {
+        signing::SigningStream::with_key(private_key.as_bytes())
+    }
+}
+
+impl VerifyingStream {
+    pub fn new(public_key: &PublicKey, signature: &Signature) -> VerifyingStream {
+        signing::VerifyingStream::with_key(public_key.as_bytes(), signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyPair, PrivKey, PubPrivKey, Seed};
+    use std::io::Cursor;
+
+    #[test]
+    fn same_as_one_shot() {
+        let keypair: KeyPair<PrivateKey, PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([2u8; 32]));
+        let message = vec![7u8; 100_000];
+        let signature = SigningStream::new(&keypair.private_key)
+            .sign(Cursor::new(&message))
+            .unwrap();
+        assert_eq!(signature, keypair.private_key.sign(&message));
+        let parts = [&message[..10], &message[10..]];
+        assert_eq!(signature, keypair.private_key.sign_parts(&parts));
+
+        let mut verifier = VerifyingStream::new(&keypair.public_key, &signature);
+        verifier.update(&message);
+        assert!(verifier.finish().is_ok());
+        assert!(keypair
+            .public_key
+            .check_reader(signature, &message[..])
+            .is_ok());
+    }
+}
@@ -0,0 +1,131 @@
+//! Signed and encrypted messages
+//!
+//! The sender signs the message with its EdDSA key, together with the recipient's X25519 public
+//! key, and seals the signature and the message to the recipient with [`sealed::seal`]. Because
+//! the recipient key is part of the signed data, a recipient cannot decrypt a message and pass
+//! it on to someone else as if the sender had written it for them.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::{pubkey, sealed, PrivKey, PubKey, Signature};
+use thiserror::Error;
+
+const SIGNATURE_DST: &[u8] = b"monocypher-rs signcryption";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error(transparent)]
+    Sealed(#[from] sealed::Error),
+    #[error("Signature check failed.")]
+    Signature,
+}
+
+/// Signs the message for `recipient_pk` and seals it to that key.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::envelope::{open, sign_and_seal};
+/// use monocypher::key_exchange::SecretKey;
+/// use monocypher::pubkey;
+/// use monocypher::{KeyPair, PubPrivKey, Seed};
+///
+/// let alice: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+///     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+/// let bob = SecretKey::generate().unwrap();
+///
+/// let envelope = sign_and_seal("message".as_bytes(), &alice.private_key, &bob.public_key()).unwrap();
+/// let message = open(&envelope, &bob, &alice.public_key).unwrap();
+/// assert_eq!(message, "message".as_bytes());
+/// ```
+pub fn sign_and_seal(
+    message: &[u8],
+    sender_signing_key: &pubkey::PrivateKey,
+    recipient_pk: &PublicKey,
+) -> Result<Vec<u8>, Error> {
+    let signature = sender_signing_key.sign(&signed_data(message, recipient_pk));
+    let mut inner = Vec::with_capacity(64 + message.len());
+    inner.extend_from_slice(&*signature);
+    inner.extend_from_slice(message);
+    Ok(sealed::seal(&inner, recipient_pk)?)
+}
+
+/// Opens an envelope and checks that `sender_pk` signed it for this recipient.
+pub fn open(
+    envelope: &[u8],
+    recipient_sk: &SecretKey,
+    sender_pk: &pubkey::PublicKey,
+) -> Result<Vec<u8>, Error> {
+    let mut inner = sealed::open(envelope, recipient_sk)?;
+    if inner.len() < 64 {
+        return Err(sealed::Error::Malformed.into());
+    }
+    let message = inner.split_off(64);
+    let mut signature = [0u8; 64];
+    signature.copy_from_slice(&inner);
+
+    let signed = signed_data(&message, &recipient_sk.public_key());
+    sender_pk
+        .check(Signature::from(signature), &signed)
+        .map_err(|_| Error::Signature)?;
+    Ok(message)
+}
+
+// Prefixes the message with a domain separator and the recipient key.
+fn signed_data(message: &[u8], recipient_pk: &PublicKey) -> Vec<u8> {
+    [SIGNATURE_DST, &**recipient_pk, message].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyPair, PubPrivKey, Seed};
+
+    fn signing_key(seed: u8) -> KeyPair<pubkey::PrivateKey, pubkey::PublicKey> {
+        KeyPair::generate_key_pair(Seed::from([seed; 32]))
+    }
+
+    #[test]
+    fn sign_and_open() {
+        let alice = signing_key(1);
+        let bob = SecretKey::from([2u8; 32]);
+        let envelope = sign_and_seal(b"message", &alice.private_key, &bob.public_key()).unwrap();
+        assert_eq!(envelope.len(), sealed::OVERHEAD + 64 + 7);
+        assert_eq!(
+            open(&envelope, &bob, &alice.public_key).unwrap(),
+            b"message"
+        );
+    }
+
+    #[test]
+    fn wrong_sender() {
+        let alice = signing_key(1);
+        let bob = SecretKey::from([2u8; 32]);
+        let envelope = sign_and_seal(b"message", &alice.private_key, &bob.public_key()).unwrap();
+        let ret = open(&envelope, &bob, &signing_key(3).public_key);
+        assert_eq!(ret, Err(Error::Signature));
+    }
+
+    #[test]
+    fn forwarded() {
+        let alice = signing_key(1);
+        let bob = SecretKey::from([2u8; 32]);
+        let carol = SecretKey::from([3u8; 32]);
+        let envelope = sign_and_seal(b"message", &alice.private_key, &bob.public_key()).unwrap();
+
+        // Bob re-seals the signed message to carol.
+        let inner = sealed::open(&envelope, &bob).unwrap();
+        let forwarded = sealed::seal(&inner, &carol.public_key()).unwrap();
+        let ret = open(&forwarded, &carol, &alice.public_key);
+        assert_eq!(ret, Err(Error::Signature));
+    }
+
+    #[test]
+    fn malformed() {
+        let bob = SecretKey::from([2u8; 32]);
+        let envelope = sealed::seal(&[0u8; 63], &bob.public_key()).unwrap();
+        let ret = open(&envelope, &bob, &signing_key(1).public_key);
+        assert_eq!(ret, Err(Error::Sealed(sealed::Error::Malformed)));
+    }
+}
@@ -0,0 +1,100 @@
+//! Short fingerprints of public keys for manual comparison
+//!
+//! A fingerprint is the 16 byte BLAKE2b hash of a domain separation string, the key algorithm
+//! and the public key. The algorithm is part of the hash, so the same 32 bytes used as an X25519
+//! and as an EdDSA key have different fingerprints. [`Fingerprint`] displays as eight groups of
+//! four hex digits, such as `d473 511a e7b8 e2ca 5959 d220 4e97 f47b`.
+//!
+//! # Example
+//!
+//! ```
+//! use monocypher::key_exchange::SecretKey;
+//!
+//! let public_key = SecretKey::from([2u8; 32]).public_key();
+//! assert_eq!(
+//!     public_key.fingerprint().to_string(),
+//!     "d473 511a e7b8 e2ca 5959 d220 4e97 f47b"
+//! );
+//! ```
+
+use crate::hashing::blake2b;
+use derive_more::From;
+use std::fmt;
+use std::ops::Deref;
+
+const DST: &[u8] = b"monocypher-rs fingerprint";
+
+/// Size of a fingerprint in bytes.
+pub const FINGERPRINT_LEN: usize = 16;
+
+/// The fingerprint of a public key.
+#[derive(Clone, Copy, Debug, From, PartialEq, Eq, Hash)]
+pub struct Fingerprint([u8; FINGERPRINT_LEN]);
+
+impl Fingerprint {
+    // Hashes a public key of the given algorithm.
+    pub(crate) fn new(algorithm: &str, public_key: &[u8; 32]) -> Fingerprint {
+        let mut ctx = blake2b::Context::<FINGERPRINT_LEN>::new_n();
+        ctx.update(DST);
+        ctx.update(&[algorithm.len() as u8]);
+        ctx.update(algorithm.as_bytes());
+        ctx.update(public_key);
+        let mut fingerprint = [0u8; FINGERPRINT_LEN];
+        ctx.finalize_into(&mut fingerprint);
+        Fingerprint(fingerprint)
+    }
+}
+
+impl Deref for Fingerprint {
+    type Target = [u8; FINGERPRINT_LEN];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, group) in self.0.chunks(2).enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(&hex::encode(group))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::key_exchange::SecretKey;
+    use crate::pubkey;
+    use crate::{KeyPair, PubPrivKey, Seed};
+
+    #[test]
+    fn x25519() {
+        let public_key = SecretKey::from([2u8; 32]).public_key();
+        let fingerprint = public_key.fingerprint();
+        assert_eq!(
+            hex::encode(*fingerprint),
+            "d473511ae7b8e2ca5959d2204e97f47b"
+        );
+        assert_eq!(
+            fingerprint.to_string(),
+            "d473 511a e7b8 e2ca 5959 d220 4e97 f47b"
+        );
+    }
+
+    #[test]
+    fn algorithm_separates() {
+        let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([2u8; 32]));
+        let x25519 = Fingerprint::new("x25519", &keypair.public_key);
+        assert_ne!(keypair.public_key.fingerprint(), x25519);
+        assert_eq!(
+            keypair.public_key.fingerprint(),
+            keypair.private_key.public_key().fingerprint()
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Authenticated key agreement with signed transcripts
+//!
+//! Both parties generate an ephemeral X25519 key and send its public key. Once both public keys
+//! are known, each party signs the transcript with its EdDSA key from [`pubkey`](crate::pubkey)
+//! and sends the signature:
+//!
+//! ```text
+//! transcript = initiator ephemeral || responder ephemeral || initiator identity || responder identity
+//! signature  = EdDSA(role || transcript)
+//! ```
+//!
+//! The session keys are only released when the other party's signature is valid. Signing the
+//! role keeps a party from reflecting the other's signature, signing both identities keeps an
+//! attacker from claiming the session as its own.
+//!
+//! # Example
+//!
+//! ```
+//! use monocypher::handshake::ake::{Ake, Role};
+//! use monocypher::pubkey;
+//! use monocypher::{KeyPair, PubPrivKey, Seed};
+//!
+//! let alice: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+//!     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+//! let bob: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+//!     KeyPair::generate_key_pair(Seed::from([2u8; 32]));
+//!
+//! let (alice_ake, alice_ephemeral) = Ake::start(Role::Initiator, &alice, &bob.public_key).unwrap();
+//! let (bob_ake, bob_ephemeral) = Ake::start(Role::Responder, &bob, &alice.public_key).unwrap();
+//!
+//! let (alice_pending, alice_signature) = alice_ake.exchange(&bob_ephemeral).unwrap();
+//! let (bob_pending, bob_signature) = bob_ake.exchange(&alice_ephemeral).unwrap();
+//!
+//! let alice_keys = alice_pending.finish(&bob_signature).unwrap();
+//! let bob_keys = bob_pending.finish(&alice_signature).unwrap();
+//! assert_eq!(*alice_keys.tx, *bob_keys.rx);
+//! assert_eq!(*alice_keys.rx, *bob_keys.tx);
+//! ```
+
+use crate::aead::Key;
+use crate::hashing::blake2b;
+use crate::key_exchange::kx::SessionKeys;
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::{pubkey, utils, KeyPair, PrivKey, PubKey, Signature};
+use thiserror::Error;
+
+const TRANSCRIPT_DST: &[u8] = b"monocypher-rs signed ake";
+const KEYS_DST: &[u8] = b"monocypher-rs signed ake keys";
+
+type IdentityKey = KeyPair<pubkey::PrivateKey, pubkey::PublicKey>;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Public key has a low order.")]
+    LowOrder,
+    #[error("Signature check failed.")]
+    Signature,
+}
+
+/// The role of a party, the two parties must pick different ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Initiator,
+    Responder,
+}
+
+impl Role {
+    fn tag(self) -> u8 {
+        match self {
+            Role::Initiator => 0,
+            Role::Responder => 1,
+        }
+    }
+
+    fn other(self) -> Role {
+        match self {
+            Role::Initiator => Role::Responder,
+            Role::Responder => Role::Initiator,
+        }
+    }
+}
+
+/// A party that sent its ephemeral key.
+pub struct Ake<'a> {
+    role: Role,
+    identity: &'a IdentityKey,
+    their_identity: &'a pubkey::PublicKey,
+    ephemeral: SecretKey,
+}
+
+impl<'a> Ake<'a> {
+    /// Generates the ephemeral key, whose public key is sent to the other party.
+    pub fn start(
+        role: Role,
+        identity: &'a IdentityKey,
+        their_identity: &'a pubkey::PublicKey,
+    ) -> Result<(Ake<'a>, PublicKey), Error> {
+        let ephemeral = SecretKey::generate().map_err(|_| Error::Random)?;
+        let public_key = ephemeral.public_key();
+        let ake = Ake {
+            role,
+            identity,
+            their_identity,
+            ephemeral,
+        };
+        Ok((ake, public_key))
+    }
+
+    /// Computes the shared secret and signs the transcript.
+    ///
+    /// The signature is sent to the other party.
+    pub fn exchange(self, their_ephemeral: &PublicKey) -> Result<(Pending<'a>, Signature), Error> {
+        let shared_secret = self
+            .ephemeral
+            .diffie_hellman(their_ephemeral)
+            .map_err(|_| Error::LowOrder)?;
+
+        let our_ephemeral = self.ephemeral.public_key();
+        let (ephemerals, identities) = match self.role {
+            Role::Initiator => (
+                [*our_ephemeral, **their_ephemeral],
+                [*self.identity.public_key, **self.their_identity],
+            ),
+            Role::Responder => (
+                [**their_ephemeral, *our_ephemeral],
+                [**self.their_identity, *self.identity.public_key],
+            ),
+        };
+        let transcript = [ephemerals, identities].concat().concat();
+
+        let mut ctx = blake2b::Context::new();
+        ctx.update(KEYS_DST);
+        ctx.update(&*shared_secret);
+        ctx.update(&transcript);
+        let keys = ctx.finalize();
+
+        let signature = self
+            .identity
+            .private_key
+            .sign(&signed_data(self.role, &transcript));
+        let pending = Pending {
+            role: self.role,
+            their_identity: self.their_identity,
+            transcript,
+            keys,
+        };
+        Ok((pending, signature))
+    }
+}
+
+/// A party that sent its signature and waits for the other one.
+pub struct Pending<'a> {
+    role: Role,
+    their_identity: &'a pubkey::PublicKey,
+    transcript: Vec<u8>,
+    keys: [u8; 64],
+}
+
+impl Pending<'_> {
+    /// Checks the other party's signature and returns the session keys.
+    pub fn finish(self, their_signature: &Signature) -> Result<SessionKeys, Error> {
+        let signed = signed_data(self.role.other(), &self.transcript);
+        self.their_identity
+            .check(Signature::from(**their_signature), &signed)
+            .map_err(|_| Error::Signature)?;
+
+        let to_key = |half: &[u8]| {
+            let mut key = [0u8; 32];
+            key.copy_from_slice(half);
+            let ret = Key::from(key);
+            utils::wipe(&mut key);
+            ret
+        };
+        let initiator_to_responder = to_key(&self.keys[..32]);
+        let responder_to_initiator = to_key(&self.keys[32..]);
+        Ok(match self.role {
+            Role::Initiator => SessionKeys {
+                rx: responder_to_initiator,
+                tx: initiator_to_responder,
+            },
+            Role::Responder => SessionKeys {
+                rx: initiator_to_responder,
+                tx: responder_to_initiator,
+            },
+        })
+    }
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.keys);
+    }
+}
+
+fn signed_data(role: Role, transcript: &[u8]) -> Vec<u8> {
+    [TRANSCRIPT_DST, &[role.tag()], transcript].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{PubPrivKey, Seed};
+
+    fn identity(seed: u8) -> IdentityKey {
+        KeyPair::generate_key_pair(Seed::from([seed; 32]))
+    }
+
+    #[test]
+    fn agreement() {
+        let (alice, bob) = (identity(1), identity(2));
+        let (alice_ake, alice_ephemeral) =
+            Ake::start(Role::Initiator, &alice, &bob.public_key).unwrap();
+        let (bob_ake, bob_ephemeral) =
+            Ake::start(Role::Responder, &bob, &alice.public_key).unwrap();
+        let (alice_pending, alice_signature) = alice_ake.exchange(&bob_ephemeral).unwrap();
+        let (bob_pending, bob_signature) = bob_ake.exchange(&alice_ephemeral).unwrap();
+
+        let alice_keys = alice_pending.finish(&bob_signature).unwrap();
+        let bob_keys = bob_pending.finish(&alice_signature).unwrap();
+        assert_eq!(*alice_keys.tx, *bob_keys.rx);
+        assert_eq!(*alice_keys.rx, *bob_keys.tx);
+        assert_ne!(*alice_keys.tx, *alice_keys.rx);
+    }
+
+    #[test]
+    fn wrong_identity() {
+        let (alice, bob, mallory) = (identity(1), identity(2), identity(3));
+        let (alice_ake, _) = Ake::start(Role::Initiator, &alice, &bob.public_key).unwrap();
+        let (mallory_ake, mallory_ephemeral) =
+            Ake::start(Role::Responder, &mallory, &alice.public_key).unwrap();
+        let (alice_pending, _) = alice_ake.exchange(&mallory_ephemeral).unwrap();
+        let (_, mallory_signature) = mallory_ake
+            .exchange(&SecretKey::from([4u8; 32]).public_key())
+            .unwrap();
+        assert_eq!(
+            alice_pending.finish(&mallory_signature).err(),
+            Some(Error::Signature)
+        );
+    }
+
+    #[test]
+    fn reflected_signature() {
+        // Alice talks to herself, so only the role tells the signatures apart.
+        let alice = identity(1);
+        let (alice_ake, _) = Ake::start(Role::Initiator, &alice, &alice.public_key).unwrap();
+        let bob_ephemeral = SecretKey::from([4u8; 32]).public_key();
+        let (alice_pending, alice_signature) = alice_ake.exchange(&bob_ephemeral).unwrap();
+        assert_eq!(
+            alice_pending.finish(&alice_signature).err(),
+            Some(Error::Signature)
+        );
+    }
+
+    #[test]
+    fn low_order() {
+        let (alice, bob) = (identity(1), identity(2));
+        let (alice_ake, _) = Ake::start(Role::Initiator, &alice, &bob.public_key).unwrap();
+        let ret = alice_ake.exchange(&PublicKey::from([0u8; 32]));
+        assert_eq!(ret.err(), Some(Error::LowOrder));
+    }
+}
@@ -0,0 +1,7 @@
+//! Authenticated key exchange handshakes
+//!
+//! Requires the `getrandom` feature.
+
+pub mod ake;
+pub mod noise_xx;
+pub mod x3dh;
@@ -0,0 +1,596 @@
+//! Noise XX handshake
+//!
+//! Implements `Noise_XX_25519_ChaChaPoly_BLAKE2b` of the
+//! [Noise protocol framework](https://noiseprotocol.org/noise.html). Both parties authenticate
+//! with static X25519 keys that are exchanged encrypted during the handshake, so they do not need
+//! to know each other's keys in advance:
+//!
+//! ```text
+//! -> e
+//! <- e, ee, s, es
+//! -> s, se
+//! ```
+//!
+//! Every handshake message can carry a payload. The payload of the first message is neither
+//! encrypted nor authenticated, the one of the second message is encrypted but the initiator is
+//! not authenticated yet. A handshake that failed must be abandoned.
+//!
+//! # Example
+//!
+//! ```
+//! use monocypher::handshake::noise_xx::Handshake;
+//! use monocypher::key_exchange::SecretKey;
+//!
+//! let mut initiator = Handshake::initiator(SecretKey::generate().unwrap(), &[]);
+//! let mut responder = Handshake::responder(SecretKey::generate().unwrap(), &[]);
+//!
+//! let message = initiator.write_message(&[]).unwrap();
+//! responder.read_message(&message).unwrap();
+//! let message = responder.write_message(&[]).unwrap();
+//! initiator.read_message(&message).unwrap();
+//! let message = initiator.write_message(&[]).unwrap();
+//! responder.read_message(&message).unwrap();
+//!
+//! let mut initiator = initiator.into_transport().unwrap();
+//! let mut responder = responder.into_transport().unwrap();
+//! assert_eq!(initiator.handshake_hash(), responder.handshake_hash());
+//!
+//! let message = initiator.encrypt("ping".as_bytes()).unwrap();
+//! assert_eq!(responder.decrypt(&message).unwrap(), "ping".as_bytes());
+//! ```
+
+use crate::hashing::blake2b;
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::utils;
+use monocypher_sys as ffi;
+use std::mem;
+use std::os::raw::c_void;
+use thiserror::Error;
+
+const PROTOCOL_NAME: &[u8] = b"Noise_XX_25519_ChaChaPoly_BLAKE2b";
+
+/// Maximum size of a Noise message.
+pub const MAX_MESSAGE_LEN: usize = 65535;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Public key has a low order.")]
+    LowOrder,
+    #[error("Message is corrupt.")]
+    InvalidMac,
+    #[error("Message is too short.")]
+    Malformed,
+    #[error("Message is longer than 65535 bytes.")]
+    TooLong,
+    #[error("Message is out of order or the handshake is not finished.")]
+    OutOfOrder,
+    #[error("All nonces of the key are used.")]
+    NonceExhausted,
+}
+
+/// State of one party during the handshake.
+pub struct Handshake {
+    initiator: bool,
+    symmetric: SymmetricState,
+    s: SecretKey,
+    e: Option<SecretKey>,
+    re: Option<PublicKey>,
+    rs: Option<PublicKey>,
+    messages: usize,
+}
+
+impl Handshake {
+    /// Starts a handshake as the party sending the first message.
+    ///
+    /// Both parties must use the same prologue, it is authenticated by the handshake.
+    pub fn initiator(static_key: SecretKey, prologue: &[u8]) -> Handshake {
+        Handshake::new(true, static_key, prologue)
+    }
+
+    /// Starts a handshake as the party receiving the first message.
+    pub fn responder(static_key: SecretKey, prologue: &[u8]) -> Handshake {
+        Handshake::new(false, static_key, prologue)
+    }
+
+    fn new(initiator: bool, static_key: SecretKey, prologue: &[u8]) -> Handshake {
+        Handshake {
+            initiator,
+            symmetric: SymmetricState::new(prologue),
+            s: static_key,
+            e: None,
+            re: None,
+            rs: None,
+            messages: 0,
+        }
+    }
+
+    /// Writes the next handshake message with a payload.
+    pub fn write_message(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        if self.messages >= 3 || self.initiator_turn() != self.initiator {
+            return Err(Error::OutOfOrder);
+        }
+        let overhead = [32, 32 + 48 + 16, 48 + 16][self.messages];
+        if payload.len() > MAX_MESSAGE_LEN - overhead {
+            return Err(Error::TooLong);
+        }
+
+        let mut message = Vec::with_capacity(overhead + payload.len());
+        if self.messages < 2 {
+            let e = self.ephemeral()?;
+            message.extend_from_slice(&*e);
+            self.symmetric.mix_hash(&*e);
+        }
+        if self.messages == 1 {
+            self.mix_dh(Key::Ephemeral, Key::RemoteEphemeral)?;
+        }
+        if self.messages > 0 {
+            let s = self.s.public_key();
+            message.extend_from_slice(&self.symmetric.encrypt_and_hash(&*s)?);
+            self.mix_dh(Key::Static, Key::RemoteEphemeral)?;
+        }
+        message.extend_from_slice(&self.symmetric.encrypt_and_hash(payload)?);
+        self.messages += 1;
+        Ok(message)
+    }
+
+    /// Reads the next handshake message and returns its payload.
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        if self.messages >= 3 || self.initiator_turn() == self.initiator {
+            return Err(Error::OutOfOrder);
+        }
+        let overhead = [32, 32 + 48 + 16, 48 + 16][self.messages];
+        if message.len() < overhead {
+            return Err(Error::Malformed);
+        }
+
+        let mut message = message;
+        if self.messages < 2 {
+            let mut re = [0u8; 32];
+            re.copy_from_slice(&message[..32]);
+            self.re = Some(PublicKey::from(re));
+            self.symmetric.mix_hash(&re);
+            message = &message[32..];
+        }
+        if self.messages == 1 {
+            self.mix_dh(Key::Ephemeral, Key::RemoteEphemeral)?;
+        }
+        if self.messages > 0 {
+            let rs = self.symmetric.decrypt_and_hash(&message[..48])?;
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&rs);
+            self.rs = Some(PublicKey::from(key));
+            self.mix_dh(Key::Ephemeral, Key::RemoteStatic)?;
+            message = &message[48..];
+        }
+        let payload = self.symmetric.decrypt_and_hash(message)?;
+        self.messages += 1;
+        Ok(payload)
+    }
+
+    /// Returns whether all three messages were exchanged.
+    pub fn is_finished(&self) -> bool {
+        self.messages == 3
+    }
+
+    /// Returns the static key of the other party, once it was received.
+    pub fn remote_static(&self) -> Option<&PublicKey> {
+        self.rs.as_ref()
+    }
+
+    /// Finishes the handshake and returns the keys of the transport phase.
+    pub fn into_transport(self) -> Result<Transport, Error> {
+        let remote_static = match (self.is_finished(), self.rs) {
+            (true, Some(rs)) => rs,
+            _ => return Err(Error::OutOfOrder),
+        };
+        let (initiator_to_responder, responder_to_initiator) = self.symmetric.split();
+        let (send, receive) = if self.initiator {
+            (initiator_to_responder, responder_to_initiator)
+        } else {
+            (responder_to_initiator, initiator_to_responder)
+        };
+        Ok(Transport {
+            send,
+            receive,
+            handshake_hash: self.symmetric.h,
+            remote_static,
+        })
+    }
+
+    // The initiator writes the first and the last message.
+    fn initiator_turn(&self) -> bool {
+        self.messages != 1
+    }
+
+    // Returns the public ephemeral key, generating the key pair first.
+    fn ephemeral(&mut self) -> Result<PublicKey, Error> {
+        if self.e.is_none() {
+            self.e = Some(SecretKey::generate().map_err(|_| Error::Random)?);
+        }
+        Ok(self.e.as_ref().map(SecretKey::public_key).unwrap())
+    }
+
+    // Mixes the shared secret of one of our keys and one of theirs into the chaining key.
+    fn mix_dh(&mut self, ours: Key, theirs: Key) -> Result<(), Error> {
+        let secret_key = match ours {
+            Key::Static => Some(&self.s),
+            _ => self.e.as_ref(),
+        };
+        let public_key = match theirs {
+            Key::RemoteStatic => self.rs.as_ref(),
+            _ => self.re.as_ref(),
+        };
+        let (Some(secret_key), Some(public_key)) = (secret_key, public_key) else {
+            return Err(Error::OutOfOrder);
+        };
+        let shared_secret = secret_key
+            .diffie_hellman(public_key)
+            .map_err(|_| Error::LowOrder)?;
+        self.symmetric.mix_key(&*shared_secret);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Key {
+    Static,
+    Ephemeral,
+    RemoteStatic,
+    RemoteEphemeral,
+}
+
+/// Keys of an established session.
+pub struct Transport {
+    send: CipherState,
+    receive: CipherState,
+    handshake_hash: [u8; 64],
+    remote_static: PublicKey,
+}
+
+impl Transport {
+    /// Encrypts the next message to the other party.
+    pub fn encrypt(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        if payload.len() > MAX_MESSAGE_LEN - 16 {
+            return Err(Error::TooLong);
+        }
+        self.send.encrypt(&[], payload)
+    }
+
+    /// Decrypts the next message from the other party.
+    ///
+    /// A corrupt message leaves the session unchanged.
+    pub fn decrypt(&mut self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        self.receive.decrypt(&[], message)
+    }
+
+    /// Returns the hash of the handshake, identical for both parties.
+    ///
+    /// It can be signed to bind the session to other credentials.
+    pub fn handshake_hash(&self) -> &[u8; 64] {
+        &self.handshake_hash
+    }
+
+    /// Returns the authenticated static key of the other party.
+    pub fn remote_static(&self) -> &PublicKey {
+        &self.remote_static
+    }
+}
+
+// ChaCha20-Poly1305 key with a message counter.
+struct CipherState {
+    key: Option<[u8; 32]>,
+    nonce: u64,
+}
+
+impl CipherState {
+    fn new(key: Option<[u8; 32]>) -> CipherState {
+        CipherState { key, nonce: 0 }
+    }
+
+    fn encrypt(&mut self, ad: &[u8], plain_text: &[u8]) -> Result<Vec<u8>, Error> {
+        let Some(key) = &self.key else {
+            return Ok(plain_text.to_vec());
+        };
+        if self.nonce == u64::MAX {
+            return Err(Error::NonceExhausted);
+        }
+
+        let mut message = vec![0u8; plain_text.len() + 16];
+        let (cipher_text, mac) = message.split_at_mut(plain_text.len());
+        let mut ctx = init_ietf(key, self.nonce);
+        unsafe {
+            ffi::crypto_aead_write(
+                &mut ctx,
+                cipher_text.as_mut_ptr(),
+                mac.as_mut_ptr(),
+                ad.as_ptr(),
+                ad.len(),
+                plain_text.as_ptr(),
+                plain_text.len(),
+            );
+        }
+        wipe_ctx(&mut ctx);
+        self.nonce += 1;
+        Ok(message)
+    }
+
+    fn decrypt(&mut self, ad: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
+        let Some(key) = &self.key else {
+            return Ok(message.to_vec());
+        };
+        if self.nonce == u64::MAX {
+            return Err(Error::NonceExhausted);
+        }
+        if message.len() < 16 {
+            return Err(Error::Malformed);
+        }
+
+        let (cipher_text, mac) = message.split_at(message.len() - 16);
+        let mut plain_text = vec![0u8; cipher_text.len()];
+        let mut ctx = init_ietf(key, self.nonce);
+        let ret = unsafe {
+            ffi::crypto_aead_read(
+                &mut ctx,
+                plain_text.as_mut_ptr(),
+                mac.as_ptr(),
+                ad.as_ptr(),
+                ad.len(),
+                cipher_text.as_ptr(),
+                cipher_text.len(),
+            )
+        };
+        wipe_ctx(&mut ctx);
+        if ret != 0 {
+            return Err(Error::InvalidMac);
+        }
+        self.nonce += 1;
+        Ok(plain_text)
+    }
+}
+
+impl Drop for CipherState {
+    fn drop(&mut self) {
+        if let Some(key) = &mut self.key {
+            utils::wipe(key);
+        }
+    }
+}
+
+// Starts RFC 8439 ChaCha20-Poly1305 with the nonce encoding of Noise.
+fn init_ietf(key: &[u8; 32], nonce: u64) -> ffi::crypto_aead_ctx {
+    let mut ietf_nonce = [0u8; 12];
+    ietf_nonce[4..].copy_from_slice(&nonce.to_le_bytes());
+    unsafe {
+        let mut ctx = mem::MaybeUninit::<ffi::crypto_aead_ctx>::uninit();
+        ffi::crypto_aead_init_ietf(ctx.as_mut_ptr(), key.as_ptr(), ietf_nonce.as_ptr());
+        ctx.assume_init()
+    }
+}
+
+fn wipe_ctx(ctx: &mut ffi::crypto_aead_ctx) {
+    unsafe {
+        ffi::crypto_wipe(
+            ctx as *mut ffi::crypto_aead_ctx as *mut c_void,
+            mem::size_of::<ffi::crypto_aead_ctx>(),
+        );
+    }
+}
+
+// Chaining key, handshake hash and the current handshake key.
+struct SymmetricState {
+    ck: [u8; 64],
+    h: [u8; 64],
+    cipher: CipherState,
+}
+
+impl SymmetricState {
+    fn new(prologue: &[u8]) -> SymmetricState {
+        let mut h = [0u8; 64];
+        h[..PROTOCOL_NAME.len()].copy_from_slice(PROTOCOL_NAME);
+        let mut state = SymmetricState {
+            ck: h,
+            h,
+            cipher: CipherState::new(None),
+        };
+        state.mix_hash(prologue);
+        state
+    }
+
+    fn mix_hash(&mut self, data: &[u8]) {
+        let mut ctx = blake2b::Context::new();
+        ctx.update(&self.h);
+        ctx.update(data);
+        self.h = ctx.finalize();
+    }
+
+    fn mix_key(&mut self, input_key_material: &[u8]) {
+        let (ck, mut temp_key) = hkdf(&self.ck, input_key_material);
+        self.ck = ck;
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&temp_key[..32]);
+        utils::wipe(&mut temp_key);
+        self.cipher = CipherState::new(Some(key));
+        utils::wipe(&mut key);
+    }
+
+    fn encrypt_and_hash(&mut self, plain_text: &[u8]) -> Result<Vec<u8>, Error> {
+        let cipher_text = self.cipher.encrypt(&self.h, plain_text)?;
+        self.mix_hash(&cipher_text);
+        Ok(cipher_text)
+    }
+
+    fn decrypt_and_hash(&mut self, cipher_text: &[u8]) -> Result<Vec<u8>, Error> {
+        let plain_text = self.cipher.decrypt(&self.h, cipher_text)?;
+        self.mix_hash(cipher_text);
+        Ok(plain_text)
+    }
+
+    fn split(&self) -> (CipherState, CipherState) {
+        let (mut temp_key_1, mut temp_key_2) = hkdf(&self.ck, &[]);
+        let mut keys = [[0u8; 32]; 2];
+        keys[0].copy_from_slice(&temp_key_1[..32]);
+        keys[1].copy_from_slice(&temp_key_2[..32]);
+        utils::wipe(&mut temp_key_1);
+        utils::wipe(&mut temp_key_2);
+        let ret = (
+            CipherState::new(Some(keys[0])),
+            CipherState::new(Some(keys[1])),
+        );
+        utils::wipe(keys.as_flattened_mut());
+        ret
+    }
+}
+
+impl Drop for SymmetricState {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.ck);
+    }
+}
+
+// HMAC-BLAKE2b with a key of the hash size.
+fn hmac(key: &[u8; 64], data: &[&[u8]]) -> [u8; 64] {
+    let mut pad = [0u8; 128];
+    pad[..64].copy_from_slice(key);
+
+    pad.iter_mut().for_each(|b| *b ^= 0x36);
+    let mut inner = blake2b::Context::new();
+    inner.update(&pad);
+    data.iter().for_each(|data| inner.update(data));
+    let inner = inner.finalize();
+
+    pad.iter_mut().for_each(|b| *b ^= 0x36 ^ 0x5c);
+    let mut outer = blake2b::Context::new();
+    outer.update(&pad);
+    outer.update(&inner);
+    utils::wipe(&mut pad);
+    outer.finalize()
+}
+
+// HKDF of the Noise specification with two outputs.
+fn hkdf(chaining_key: &[u8; 64], input_key_material: &[u8]) -> ([u8; 64], [u8; 64]) {
+    let mut temp_key = hmac(chaining_key, &[input_key_material]);
+    let output_1 = hmac(&temp_key, &[&[1]]);
+    let output_2 = hmac(&temp_key, &[&output_1, &[2]]);
+    utils::wipe(&mut temp_key);
+    (output_1, output_2)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn handshake() -> (Handshake, Handshake, [Vec<u8>; 3]) {
+        let mut initiator = Handshake::initiator(SecretKey::from([1u8; 32]), b"prologue");
+        let mut responder = Handshake::responder(SecretKey::from([3u8; 32]), b"prologue");
+        initiator.e = Some(SecretKey::from([2u8; 32]));
+        responder.e = Some(SecretKey::from([4u8; 32]));
+
+        let m1 = initiator.write_message(b"hello").unwrap();
+        assert_eq!(responder.read_message(&m1).unwrap(), b"hello");
+        let m2 = responder.write_message(b"world").unwrap();
+        assert_eq!(initiator.read_message(&m2).unwrap(), b"world");
+        let m3 = initiator.write_message(b"!").unwrap();
+        assert_eq!(responder.read_message(&m3).unwrap(), b"!");
+        (initiator, responder, [m1, m2, m3])
+    }
+
+    // Computed with an independent implementation of the Noise specification.
+    #[test]
+    fn vector() {
+        let (initiator, responder, messages) = handshake();
+        assert_eq!(
+            hex::encode(&messages[0]),
+            "ce8d3ad1ccb633ec7b70c17814a5c76ecd029685050d344745ba05870e587d5968656c6c6f"
+        );
+        assert_eq!(
+            hex::encode(&messages[1]),
+            "ac01b2209e86354fb853237b5de0f4fab13c7fcbf433a61c019369617fecf10b8001b4ce4bb2746a\
+             6bd693c4d0a900d849389237f160d22856081e810110e359f3342227d4ea54bd707899b92ca73367\
+             f864a7cf3db7cbfb0665f9c9f482a6ad98d940ad04"
+        );
+        assert_eq!(
+            hex::encode(&messages[2]),
+            "ef85c6b0379016c6845ae99f75be21275605d8f34ef8dbd5a2653aa4ef7b87c578d7a5cb68d7911d\
+             372d24ff526b8c54b4af94049a09dfca39b6d0b8a269c907bc"
+        );
+
+        let mut initiator = initiator.into_transport().unwrap();
+        let mut responder = responder.into_transport().unwrap();
+        assert_eq!(
+            hex::encode(initiator.handshake_hash()),
+            "3bb3b6ba643818e9cb989770605cea2c53b27eebd249ab61eca28d84f4dca0c9b07f01a0c19a029c\
+             d7fd1a555557c91d179c8f522ee290686dfd1f20821bd5ae"
+        );
+        assert_eq!(
+            initiator.remote_static(),
+            &SecretKey::from([3u8; 32]).public_key()
+        );
+        assert_eq!(
+            responder.remote_static(),
+            &SecretKey::from([1u8; 32]).public_key()
+        );
+
+        let ping = initiator.encrypt(b"ping").unwrap();
+        assert_eq!(
+            hex::encode(&ping),
+            "1a273c84f4b5d5318e60dc1a66bf98e982c8594a"
+        );
+        assert_eq!(responder.decrypt(&ping).unwrap(), b"ping");
+        let pong = responder.encrypt(b"pong").unwrap();
+        assert_eq!(
+            hex::encode(&pong),
+            "6a04b98cec0f96e048755853c3e00c2eda9ad792"
+        );
+        assert_eq!(initiator.decrypt(&pong).unwrap(), b"pong");
+    }
+
+    #[test]
+    fn transport_corrupt() {
+        let (initiator, responder, _) = handshake();
+        let mut initiator = initiator.into_transport().unwrap();
+        let mut responder = responder.into_transport().unwrap();
+
+        let mut message = initiator.encrypt(b"ping").unwrap();
+        message[0] ^= 1;
+        assert_eq!(responder.decrypt(&message), Err(Error::InvalidMac));
+        message[0] ^= 1;
+        assert_eq!(responder.decrypt(&message).unwrap(), b"ping");
+        assert_eq!(responder.decrypt(&message), Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn prologue_mismatch() {
+        let mut initiator = Handshake::initiator(SecretKey::from([1u8; 32]), b"prologue");
+        let mut responder = Handshake::responder(SecretKey::from([3u8; 32]), b"other");
+
+        let message = initiator.write_message(&[]).unwrap();
+        responder.read_message(&message).unwrap();
+        let message = responder.write_message(&[]).unwrap();
+        assert_eq!(initiator.read_message(&message), Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn out_of_order() {
+        let mut initiator = Handshake::initiator(SecretKey::from([1u8; 32]), &[]);
+        let mut responder = Handshake::responder(SecretKey::from([3u8; 32]), &[]);
+        assert_eq!(responder.write_message(&[]), Err(Error::OutOfOrder));
+        assert_eq!(initiator.read_message(&[0u8; 32]), Err(Error::OutOfOrder));
+
+        let message = initiator.write_message(&[]).unwrap();
+        assert_eq!(initiator.write_message(&[]), Err(Error::OutOfOrder));
+        responder.read_message(&message).unwrap();
+        assert!(responder.into_transport().is_err());
+    }
+
+    #[test]
+    fn malformed() {
+        let mut responder = Handshake::responder(SecretKey::from([3u8; 32]), &[]);
+        assert_eq!(responder.read_message(&[0u8; 31]), Err(Error::Malformed));
+
+        let mut initiator = Handshake::initiator(SecretKey::from([1u8; 32]), &[]);
+        let long = vec![0u8; MAX_MESSAGE_LEN];
+        assert_eq!(initiator.write_message(&long), Err(Error::TooLong));
+    }
+}
@@ -0,0 +1,321 @@
+//! X3DH key agreement
+//!
+//! Follows the structure of Signal's [X3DH](https://signal.org/docs/specifications/x3dh/):
+//! the responder publishes a [`PreKeyBundle`] with its identity key, a prekey signed with its
+//! EdDSA key from [`pubkey`](crate::pubkey) and optionally a one-time prekey. The initiator
+//! checks the signature, combines its identity key and a fresh ephemeral key with the bundle and
+//! sends an [`InitialMessage`], from which the responder computes the same key offline:
+//!
+//! ```text
+//! DH1 = DH(IK_A, SPK_B)
+//! DH2 = DH(EK_A, IK_B)
+//! DH3 = DH(EK_A, SPK_B)
+//! DH4 = DH(EK_A, OPK_B)
+//! SK  = BLAKE2b-256(0xff * 32 || DH1 || DH2 || DH3 || DH4 || info)
+//! ```
+//!
+//! DH4 is left out without one-time prekey. Messages encrypted under the shared key should use
+//! [`associated_data`] as additional data, to bind them to both identities.
+
+use crate::hashing::blake2b;
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::{pubkey, utils, PrivKey, PubKey, Signature};
+use std::ops::Deref;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Public key has a low order.")]
+    LowOrder,
+    #[error("Signature of the signed prekey is invalid.")]
+    Signature,
+    #[error("One-time prekey is missing or does not match the message.")]
+    OneTimePrekey,
+}
+
+/// The keys a responder publishes so that others can start sessions with it.
+#[derive(Debug)]
+pub struct PreKeyBundle {
+    /// Long term X25519 identity key.
+    pub identity_key: PublicKey,
+    /// Long term EdDSA key that signs the prekeys.
+    pub signing_key: pubkey::PublicKey,
+    /// Medium term X25519 prekey.
+    pub signed_prekey: PublicKey,
+    /// Signature of the signed prekey, see [`sign_prekey`].
+    pub prekey_signature: Signature,
+    /// X25519 prekey that is used for a single session, if any is left.
+    pub one_time_prekey: Option<PublicKey>,
+}
+
+/// The keys the initiator sends along with its first message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InitialMessage {
+    /// X25519 identity key of the initiator.
+    pub identity_key: PublicKey,
+    /// Ephemeral X25519 key of the initiator.
+    pub ephemeral_key: PublicKey,
+    /// One-time prekey of the responder that was used, if any.
+    pub one_time_prekey: Option<PublicKey>,
+}
+
+/// The agreed key, wiped on drop.
+pub struct SharedKey([u8; 32]);
+
+impl_ct_eq!(SharedKey);
+impl_zeroize!(SharedKey);
+
+impl Deref for SharedKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for SharedKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// Signs a prekey with the responder's EdDSA key.
+pub fn sign_prekey(signing_key: &pubkey::PrivateKey, prekey: &PublicKey) -> Signature {
+    signing_key.sign(&**prekey)
+}
+
+/// Checks the bundle and computes the shared key of the initiator.
+///
+/// `info` identifies the application, both parties must use the same.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::handshake::x3dh::{initiate, respond, sign_prekey, PreKeyBundle};
+/// use monocypher::key_exchange::SecretKey;
+/// use monocypher::pubkey;
+/// use monocypher::{KeyPair, PubPrivKey, Seed};
+///
+/// let alice = SecretKey::generate().unwrap();
+/// let bob = SecretKey::generate().unwrap();
+/// let bob_signing: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+///     KeyPair::generate_key_pair(Seed::from([7u8; 32]));
+/// let bob_prekey = SecretKey::generate().unwrap();
+///
+/// let bundle = PreKeyBundle {
+///     identity_key: bob.public_key(),
+///     prekey_signature: sign_prekey(&bob_signing.private_key, &bob_prekey.public_key()),
+///     signing_key: bob_signing.public_key,
+///     signed_prekey: bob_prekey.public_key(),
+///     one_time_prekey: None,
+/// };
+///
+/// let (alice_key, message) = initiate(&alice, &bundle, "example".as_bytes()).unwrap();
+/// let bob_key = respond(&bob, &bob_prekey, None, &message, "example".as_bytes()).unwrap();
+/// assert_eq!(*alice_key, *bob_key);
+/// ```
+pub fn initiate(
+    identity_key: &SecretKey,
+    bundle: &PreKeyBundle,
+    info: &[u8],
+) -> Result<(SharedKey, InitialMessage), Error> {
+    bundle
+        .signing_key
+        .check(
+            Signature::from(*bundle.prekey_signature),
+            &*bundle.signed_prekey,
+        )
+        .map_err(|_| Error::Signature)?;
+
+    let ephemeral_key = SecretKey::generate().map_err(|_| Error::Random)?;
+    let mut shared_key = Kdf::new();
+    shared_key.mix(identity_key, &bundle.signed_prekey)?;
+    shared_key.mix(&ephemeral_key, &bundle.identity_key)?;
+    shared_key.mix(&ephemeral_key, &bundle.signed_prekey)?;
+    if let Some(one_time_prekey) = &bundle.one_time_prekey {
+        shared_key.mix(&ephemeral_key, one_time_prekey)?;
+    }
+
+    let message = InitialMessage {
+        identity_key: identity_key.public_key(),
+        ephemeral_key: ephemeral_key.public_key(),
+        one_time_prekey: bundle.one_time_prekey,
+    };
+    Ok((shared_key.finalize(info), message))
+}
+
+/// Computes the shared key of the responder from an initial message.
+///
+/// `one_time_prekey` must be the secret key of the one-time prekey named in the message, which
+/// should be deleted afterwards.
+pub fn respond(
+    identity_key: &SecretKey,
+    signed_prekey: &SecretKey,
+    one_time_prekey: Option<&SecretKey>,
+    message: &InitialMessage,
+    info: &[u8],
+) -> Result<SharedKey, Error> {
+    let one_time_prekey = match (one_time_prekey, &message.one_time_prekey) {
+        (Some(secret_key), Some(public_key)) if secret_key.public_key() == *public_key => {
+            Some(secret_key)
+        }
+        (None, None) => None,
+        _ => return Err(Error::OneTimePrekey),
+    };
+
+    let mut shared_key = Kdf::new();
+    shared_key.mix(signed_prekey, &message.identity_key)?;
+    shared_key.mix(identity_key, &message.ephemeral_key)?;
+    shared_key.mix(signed_prekey, &message.ephemeral_key)?;
+    if let Some(one_time_prekey) = one_time_prekey {
+        shared_key.mix(one_time_prekey, &message.ephemeral_key)?;
+    }
+    Ok(shared_key.finalize(info))
+}
+
+/// Returns the identity keys of the initiator and the responder, concatenated.
+pub fn associated_data(initiator: &PublicKey, responder: &PublicKey) -> [u8; 64] {
+    let mut ad = [0u8; 64];
+    ad[..32].copy_from_slice(&**initiator);
+    ad[32..].copy_from_slice(&**responder);
+    ad
+}
+
+// Hashes the shared secrets in order.
+struct Kdf(blake2b::Context<32>);
+
+impl Kdf {
+    fn new() -> Kdf {
+        let mut ctx = blake2b::Context::<32>::new_n();
+        ctx.update(&[0xff; 32]);
+        Kdf(ctx)
+    }
+
+    fn mix(&mut self, secret_key: &SecretKey, public_key: &PublicKey) -> Result<(), Error> {
+        let shared_secret = secret_key
+            .diffie_hellman(public_key)
+            .map_err(|_| Error::LowOrder)?;
+        self.0.update(&*shared_secret);
+        Ok(())
+    }
+
+    fn finalize(mut self, info: &[u8]) -> SharedKey {
+        self.0.update(info);
+        let mut key = SharedKey([0u8; 32]);
+        self.0.finalize_into(&mut key.0);
+        key
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyPair, PubPrivKey, Seed};
+
+    struct Responder {
+        identity_key: SecretKey,
+        signed_prekey: SecretKey,
+        one_time_prekey: SecretKey,
+    }
+
+    fn responder(one_time_prekey: bool) -> (PreKeyBundle, Responder) {
+        let responder = Responder {
+            identity_key: SecretKey::from([2u8; 32]),
+            signed_prekey: SecretKey::from([3u8; 32]),
+            one_time_prekey: SecretKey::from([4u8; 32]),
+        };
+        let signing_key: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([5u8; 32]));
+        let signed_prekey = responder.signed_prekey.public_key();
+        let bundle = PreKeyBundle {
+            identity_key: responder.identity_key.public_key(),
+            prekey_signature: sign_prekey(&signing_key.private_key, &signed_prekey),
+            signing_key: signing_key.public_key,
+            signed_prekey,
+            one_time_prekey: one_time_prekey.then(|| responder.one_time_prekey.public_key()),
+        };
+        (bundle, responder)
+    }
+
+    #[test]
+    fn agreement() {
+        let alice = SecretKey::from([1u8; 32]);
+        for one_time_prekey in [false, true] {
+            let (bundle, bob) = responder(one_time_prekey);
+            let (alice_key, message) = initiate(&alice, &bundle, b"test").unwrap();
+            assert_eq!(message.identity_key, alice.public_key());
+            assert_eq!(message.one_time_prekey, bundle.one_time_prekey);
+
+            let bob_key = respond(
+                &bob.identity_key,
+                &bob.signed_prekey,
+                one_time_prekey.then_some(&bob.one_time_prekey),
+                &message,
+                b"test",
+            )
+            .unwrap();
+            assert_eq!(*alice_key, *bob_key);
+
+            let other = respond(
+                &bob.identity_key,
+                &bob.signed_prekey,
+                one_time_prekey.then_some(&bob.one_time_prekey),
+                &message,
+                b"other",
+            )
+            .unwrap();
+            assert_ne!(*alice_key, *other);
+        }
+    }
+
+    #[test]
+    fn forged_prekey() {
+        let (mut bundle, _) = responder(false);
+        bundle.signed_prekey = SecretKey::from([6u8; 32]).public_key();
+        let ret = initiate(&SecretKey::from([1u8; 32]), &bundle, b"test");
+        assert_eq!(ret.err(), Some(Error::Signature));
+    }
+
+    #[test]
+    fn one_time_prekey_mismatch() {
+        let (bundle, bob) = responder(true);
+        let (_, message) = initiate(&SecretKey::from([1u8; 32]), &bundle, b"test").unwrap();
+
+        let ret = respond(
+            &bob.identity_key,
+            &bob.signed_prekey,
+            None,
+            &message,
+            b"test",
+        );
+        assert_eq!(ret.err(), Some(Error::OneTimePrekey));
+        let wrong = SecretKey::from([6u8; 32]);
+        let ret = respond(
+            &bob.identity_key,
+            &bob.signed_prekey,
+            Some(&wrong),
+            &message,
+            b"test",
+        );
+        assert_eq!(ret.err(), Some(Error::OneTimePrekey));
+    }
+
+    #[test]
+    fn low_order() {
+        let (mut bundle, _) = responder(false);
+        bundle.identity_key = PublicKey::from([0u8; 32]);
+        let ret = initiate(&SecretKey::from([1u8; 32]), &bundle, b"test");
+        assert_eq!(ret.err(), Some(Error::LowOrder));
+    }
+
+    #[test]
+    fn associated_data_order() {
+        let alice = SecretKey::from([1u8; 32]).public_key();
+        let bob = SecretKey::from([2u8; 32]).public_key();
+        let ad = associated_data(&alice, &bob);
+        assert_eq!(&ad[..32], &*alice);
+        assert_eq!(&ad[32..], &*bob);
+    }
+}
@@ -3,7 +3,32 @@
 //! [Official documentation](https://monocypher.org/manual/hash)
 
 use monocypher_sys as ffi;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::{self, Read, Write};
 use std::mem;
+use std::path::Path;
+use thiserror::Error;
+
+// Read buffer size of `hash_reader`.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+// Size of the windows `hash_file_mmap` feeds to the context.
+#[cfg(feature = "memmap2")]
+const MMAP_WINDOW_SIZE: usize = 16 * 1024 * 1024;
+
+// Version byte of the serialized context layout.
+const STATE_VERSION: u8 = 1;
+
+/// Length of a serialized [`Context`].
+pub const STATE_SIZE: usize = 1 + 8 * (8 + 2 + 16 + 2);
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Hashing state is invalid.")]
+    InvalidState,
+}
 
 /// Simple function to hash the input data with the given key.
 ///
@@ -72,16 +97,175 @@ pub fn general(data: &[u8]) -> [u8; 64] {
     }
 }
 
-pub struct Context(ffi::crypto_blake2b_ctx);
+/// Hashes the input data into a digest of `N` bytes.
+///
+/// Shorter digests are not truncated 64 byte digests, the length is part of the BLAKE2b
+/// parameters.
+///
+/// # Panics
+///
+/// Panics if `N` is not in `1..=64`.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::blake2b::easy_n;
+///
+/// let hash: [u8; 32] = easy_n("tohash".as_bytes());
+/// ```
+pub fn easy_n<const N: usize>(data: &[u8]) -> [u8; N] {
+    easy_keyed_n(data, &[])
+}
 
-impl Default for Context {
-    fn default() -> Self {
-        Self::new()
+/// Hashes the input data with the given key into a digest of `N` bytes.
+///
+/// # Panics
+///
+/// Panics if `N` is not in `1..=64`.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::blake2b::easy_keyed_n;
+///
+/// let hash = easy_keyed_n::<32>("tohash".as_bytes(), "key".as_bytes());
+/// ```
+pub fn easy_keyed_n<const N: usize>(data: &[u8], key: &[u8]) -> [u8; N] {
+    assert_hash_size(N);
+    let mut hash = [0u8; N];
+    unsafe {
+        ffi::crypto_blake2b_keyed(
+            hash.as_mut_ptr(),
+            N,
+            key.as_ptr(),
+            key.len(),
+            data.as_ptr(),
+            data.len(),
+        );
+    }
+    hash
+}
+
+/// Hashes every item on its own.
+///
+/// With the `rayon` feature the items are hashed on the rayon thread pool.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::blake2b::hash_many;
+///
+/// let hashes = hash_many(&["one".as_bytes(), "two".as_bytes()]);
+/// assert_eq!(hashes.len(), 2);
+/// ```
+pub fn hash_many(items: &[&[u8]]) -> Vec<[u8; 64]> {
+    #[cfg(feature = "rayon")]
+    let hashes = items.par_iter().map(|item| general(item)).collect();
+    #[cfg(not(feature = "rayon"))]
+    let hashes = items.iter().map(|item| general(item)).collect();
+    hashes
+}
+
+/// Hashes everything the reader returns until the end of input.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::blake2b::hash_reader;
+///
+/// let hash = hash_reader("tohash".as_bytes()).unwrap();
+/// ```
+pub fn hash_reader<R: Read>(mut reader: R) -> io::Result<[u8; 64]> {
+    let mut ctx = Context::new();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(ctx.finalize()),
+            Ok(len) => ctx.update(&buffer[..len]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hashes the content of a file.
+///
+/// # Example
+///
+/// ```no_run
+/// use monocypher::hashing::blake2b::hash_file;
+///
+/// let hash = hash_file("Cargo.toml").unwrap();
+/// ```
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<[u8; 64]> {
+    hash_reader(File::open(path)?)
+}
+
+/// Hashes the content of a file by mapping it into memory.
+///
+/// For large files on fast storage this is faster than [`hash_file`]. The file must not be
+/// truncated while it is hashed, on most platforms that terminates the process.
+///
+/// Requires the `memmap2` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use monocypher::hashing::blake2b::hash_file_mmap;
+///
+/// let hash = hash_file_mmap("Cargo.toml").unwrap();
+/// ```
+#[cfg(feature = "memmap2")]
+pub fn hash_file_mmap<P: AsRef<Path>>(path: P) -> io::Result<[u8; 64]> {
+    let file = File::open(path)?;
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    #[cfg(unix)]
+    map.advise(memmap2::Advice::Sequential)?;
+
+    let mut ctx = Context::new();
+    for window in map.chunks(MMAP_WINDOW_SIZE) {
+        ctx.update(window);
     }
+    Ok(ctx.finalize())
+}
+
+/// A 64 byte keyed BLAKE2b authentication tag that compares in constant time.
+pub type Tag = crate::mac::Tag<64>;
+
+/// Authenticates the message with keyed BLAKE2b.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::blake2b::{mac, verify_mac};
+///
+/// let key = [137u8; 32];
+/// let tag = mac(&key, "message".as_bytes());
+/// assert!(verify_mac(&key, "message".as_bytes(), &tag));
+/// ```
+pub fn mac(key: &[u8], message: &[u8]) -> Tag {
+    Tag::from(general_keyed(message, key))
+}
+
+/// Checks a tag produced by [`mac`] in constant time.
+pub fn verify_mac(key: &[u8], message: &[u8], tag: &Tag) -> bool {
+    mac(key, message) == *tag
+}
+
+#[inline]
+fn assert_hash_size(len: usize) {
+    assert!(
+        (1..=64).contains(&len),
+        "BLAKE2b digests are 1 to 64 bytes long"
+    );
 }
 
 /// Context based hashing for e.g. large inputs.
 ///
+/// The digest length `N` is a parameter of BLAKE2b, not a truncation, and defaults to 64 bytes.
+/// Contexts for shorter digests are created with [`new_n`](Self::new_n) or
+/// [`with_key_n`](Self::with_key_n), lengths outside `1..=64` do not compile.
+///
 /// # Example
 ///
 /// ```
@@ -90,26 +274,151 @@ impl Default for Context {
 /// let mut ctx = Context::with_key("tohash".as_bytes());
 /// ctx.update("moretohash".as_bytes());
 /// let hash = ctx.finalize();
+///
+/// let mut ctx = Context::<32>::new_n();
+/// ctx.update("tohash".as_bytes());
+/// let hash: [u8; 32] = ctx.finalize();
 /// ```
-impl Context {
-    #[inline]
+///
+/// ```compile_fail
+/// use monocypher::hashing::blake2b::Context;
+///
+/// let ctx = Context::<65>::new_n();
+/// ```
+#[derive(Clone)]
+pub struct Context<const N: usize = 64>(ffi::crypto_blake2b_ctx);
 
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Context {
     /// Initializes a new context.
     pub fn new() -> Context {
-        unsafe {
-            let mut ctx = mem::MaybeUninit::<ffi::crypto_blake2b_ctx>::uninit();
-            ffi::crypto_blake2b_init(ctx.as_mut_ptr(), 64);
-            Context(ctx.assume_init())
-        }
+        Context::new_n()
     }
 
     /// Initializes a new context with the given key.
     pub fn with_key(key: &[u8]) -> Context {
+        Context::with_key_n(key)
+    }
+}
+
+impl<const N: usize> Context<N> {
+    // Evaluated by the constructors, so unsupported lengths fail to compile.
+    const VALID_LEN: () = assert!(N >= 1 && N <= 64, "BLAKE2b digests are 1 to 64 bytes long");
+
+    /// Initializes a new context for a digest of `N` bytes.
+    pub fn new_n() -> Context<N> {
+        Context::with_key_n(&[])
+    }
+
+    /// Initializes a new context with the given key for a digest of `N` bytes.
+    pub fn with_key_n(key: &[u8]) -> Context<N> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_LEN;
+        Context(init(key, N))
+    }
+
+    /// Re-initializes the context without a key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::hashing::blake2b::Context;
+    ///
+    /// let mut ctx = Context::new();
+    /// for item in ["one", "two"] {
+    ///     ctx.reset();
+    ///     ctx.update(item.as_bytes());
+    ///     let hash = ctx.finalize();
+    /// }
+    /// ```
+    pub fn reset(&mut self) {
+        unsafe {
+            ffi::crypto_blake2b_init(&mut self.0, N);
+        }
+    }
+
+    /// Re-initializes the context with the given key.
+    pub fn reset_keyed(&mut self, key: &[u8]) {
         unsafe {
-            let mut ctx = mem::MaybeUninit::<ffi::crypto_blake2b_ctx>::uninit();
-            ffi::crypto_blake2b_keyed_init(ctx.as_mut_ptr(), 64, key.as_ptr(), key.len());
-            Context(ctx.assume_init())
+            ffi::crypto_blake2b_keyed_init(&mut self.0, N, key.as_ptr(), key.len());
+        }
+    }
+
+    /// Serializes the hashing state, so it can be resumed with [`restore`](Self::restore).
+    ///
+    /// The layout is a version byte followed by the state words, the byte counter, the buffered
+    /// block, the buffer index and the digest length, each as little endian 64 bit integers.
+    /// The state of a keyed context allows forging hashes under that key, keep it secret.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::hashing::blake2b::Context;
+    ///
+    /// let mut ctx = Context::new();
+    /// ctx.update("to".as_bytes());
+    /// let state = ctx.serialize();
+    ///
+    /// let mut ctx: Context = Context::restore(&state).unwrap();
+    /// ctx.update("hash".as_bytes());
+    /// let hash = ctx.finalize();
+    /// ```
+    pub fn serialize(&self) -> [u8; STATE_SIZE] {
+        let words = self
+            .0
+            .hash
+            .iter()
+            .chain(&self.0.input_offset)
+            .chain(&self.0.input)
+            .copied()
+            .chain([self.0.input_idx as u64, self.0.hash_size as u64]);
+
+        let mut state = [0u8; STATE_SIZE];
+        state[0] = STATE_VERSION;
+        for (chunk, word) in state[1..].chunks_exact_mut(8).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
         }
+        state
+    }
+
+    /// Restores a context serialized with [`serialize`](Self::serialize).
+    ///
+    /// Fails if the state belongs to a context with another digest length.
+    pub fn restore(state: &[u8]) -> Result<Context<N>, Error> {
+        if state.len() != STATE_SIZE || state[0] != STATE_VERSION {
+            return Err(Error::InvalidState);
+        }
+        let mut words = state[1..]
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()));
+
+        let mut ctx = Context::<N>::new_n();
+        for word in ctx
+            .0
+            .hash
+            .iter_mut()
+            .chain(&mut ctx.0.input_offset)
+            .chain(&mut ctx.0.input)
+        {
+            *word = words.next().unwrap();
+        }
+        let input_idx = words.next().unwrap();
+        let hash_size = words.next().unwrap();
+        if input_idx > 128 || hash_size != N as u64 {
+            return Err(Error::InvalidState);
+        }
+        ctx.0.input_idx = input_idx as usize;
+        Ok(ctx)
+    }
+
+    /// Returns the length of the digest in bytes.
+    pub fn hash_size(&self) -> usize {
+        N
     }
 
     /// Updates the context with the given data.
@@ -122,12 +431,202 @@ impl Context {
 
     /// Finalizes the hash and returns it.
     #[inline]
-    pub fn finalize(&mut self) -> [u8; 64] {
+    pub fn finalize(&mut self) -> [u8; N] {
+        let mut hash = [0u8; N];
+        self.finalize_into(&mut hash);
+        hash
+    }
+
+    /// Finalizes the hash and writes it to `hash`, without a copy of the digest on the stack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::hashing::blake2b::Context;
+    ///
+    /// let mut ctx = Context::<32>::new_n();
+    /// ctx.update("tohash".as_bytes());
+    /// let mut hash = [0u8; 32];
+    /// ctx.finalize_into(&mut hash);
+    /// ```
+    pub fn finalize_into(&mut self, hash: &mut [u8; N]) {
         unsafe {
-            let mut hash = mem::MaybeUninit::<[u8; 64]>::uninit();
-            ffi::crypto_blake2b_final(&mut self.0, hash.as_mut_ptr() as *mut u8);
-            hash.assume_init()
+            ffi::crypto_blake2b_final(&mut self.0, hash.as_mut_ptr());
+        }
+    }
+}
+
+// Initializes a keyed state for a digest of `len` bytes, the callers check the length.
+fn init(key: &[u8], len: usize) -> ffi::crypto_blake2b_ctx {
+    unsafe {
+        let mut ctx = mem::MaybeUninit::<ffi::crypto_blake2b_ctx>::uninit();
+        ffi::crypto_blake2b_keyed_init(ctx.as_mut_ptr(), len, key.as_ptr(), key.len());
+        ctx.assume_init()
+    }
+}
+
+// Writes the keyed digest of the concatenated parts to `hash`, whose length between 1 and 64 is
+// the digest length. For the key derivation functions with an output length chosen at runtime.
+pub(crate) fn keyed_into(key: &[u8], parts: &[&[u8]], hash: &mut [u8]) {
+    assert_hash_size(hash.len());
+    let mut ctx = init(key, hash.len());
+    unsafe {
+        for part in parts {
+            ffi::crypto_blake2b_update(&mut ctx, part.as_ptr(), part.len());
         }
+        ffi::crypto_blake2b_final(&mut ctx, hash.as_mut_ptr());
+    }
+}
+
+// Like `keyed_into` of no input, with the BLAKE2b salt and personalization parameters.
+//
+// Monocypher does not take them, but they only change the initial state words 4 to 7, which
+// are not compressed before the first update.
+pub(crate) fn salt_personal_into(
+    key: &[u8],
+    salt: &[u8; 16],
+    personal: &[u8; 16],
+    hash: &mut [u8],
+) {
+    assert_hash_size(hash.len());
+    let mut ctx = init(key, hash.len());
+    let params = salt.chunks_exact(8).chain(personal.chunks_exact(8));
+    for (word, param) in ctx.hash[4..].iter_mut().zip(params) {
+        *word ^= u64::from_le_bytes(param.try_into().unwrap());
+    }
+    unsafe {
+        ffi::crypto_blake2b_final(&mut ctx, hash.as_mut_ptr());
+    }
+}
+
+/// BLAKE2b with a 32 byte digest.
+///
+/// This is BLAKE2b parameterized for 32 byte outputs, not a truncated 64 byte digest.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::blake2b::Blake2b256;
+///
+/// let hash = Blake2b256::hash("tohash".as_bytes());
+///
+/// let mut ctx = Blake2b256::new();
+/// ctx.update("to".as_bytes());
+/// ctx.update("hash".as_bytes());
+/// assert_eq!(ctx.finalize(), hash);
+/// ```
+#[derive(Clone)]
+pub struct Blake2b256(Context<32>);
+
+impl Default for Blake2b256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Blake2b256 {
+    /// Hashes the input data.
+    pub fn hash(data: &[u8]) -> [u8; 32] {
+        easy_n(data)
+    }
+
+    /// Hashes the input data with the given key.
+    pub fn hash_keyed(data: &[u8], key: &[u8]) -> [u8; 32] {
+        easy_keyed_n(data, key)
+    }
+
+    /// Initializes a new context.
+    pub fn new() -> Blake2b256 {
+        Blake2b256(Context::new_n())
+    }
+
+    /// Initializes a new context with the given key.
+    pub fn with_key(key: &[u8]) -> Blake2b256 {
+        Blake2b256(Context::with_key_n(key))
+    }
+
+    /// Updates the context with the given data.
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Finalizes the hash and returns it.
+    #[inline]
+    pub fn finalize(&mut self) -> [u8; 32] {
+        self.0.finalize()
+    }
+}
+
+/// Feeds written data into the hash, so readers can be hashed with [`io::copy`].
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::blake2b::Context;
+/// use std::io;
+///
+/// let mut ctx = Context::new();
+/// io::copy(&mut "tohash".as_bytes(), &mut ctx).unwrap();
+/// let hash = ctx.finalize();
+/// ```
+impl<const N: usize> Write for Context<N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// [`digest`] traits for the 64 byte digest, so the context can be used with generic code that
+/// expects a [`digest::Digest`].
+///
+/// Requires the `digest` feature.
+///
+/// # Example
+///
+/// ```
+/// use digest::Digest;
+/// use monocypher::hashing::blake2b::Context;
+///
+/// let hash = Context::digest("tohash".as_bytes());
+/// ```
+///
+/// Contexts for shorter digests do not implement them, their output size would not match:
+///
+/// ```compile_fail
+/// use digest::Digest;
+/// use monocypher::hashing::blake2b::Context;
+///
+/// let hash = Context::<32>::digest("tohash".as_bytes());
+/// ```
+#[cfg(feature = "digest")]
+impl digest::HashMarker for Context<64> {}
+
+#[cfg(feature = "digest")]
+impl digest::OutputSizeUser for Context<64> {
+    type OutputSize = digest::consts::U64;
+}
+
+#[cfg(feature = "digest")]
+impl digest::core_api::BlockSizeUser for Context<64> {
+    type BlockSize = digest::consts::U128;
+}
+
+#[cfg(feature = "digest")]
+impl digest::Update for Context<64> {
+    fn update(&mut self, data: &[u8]) {
+        Context::<64>::update(self, data);
+    }
+}
+
+#[cfg(feature = "digest")]
+impl digest::FixedOutput for Context<64> {
+    fn finalize_into(mut self, out: &mut digest::Output<Self>) {
+        out.copy_from_slice(&Context::<64>::finalize(&mut self));
     }
 }
 
@@ -144,6 +643,76 @@ mod test {
         assert_eq!(hex::encode(hash.to_vec()), "e33ee689585ebe3fc169a845482a47432c21a4134134d2f6c57d06dda4622500e73c79f3ab9d8a3728a7575ebb0f5a78bc6608db427e18cbba1ff6847e3fb6bb");
     }
 
+    #[test]
+    fn blake2b_clone() {
+        let mut prefix = Context::new();
+        prefix.update("TE".as_bytes());
+
+        let mut branch = prefix.clone();
+        branch.update("XT".as_bytes());
+        prefix.update("ST".as_bytes());
+
+        assert_eq!(prefix.finalize(), easy("TEST".as_bytes()));
+        assert_eq!(branch.finalize(), easy("TEXT".as_bytes()));
+    }
+
+    #[test]
+    fn blake2b_reset() {
+        let mut ctx = Context::<32>::new_n();
+        ctx.update("garbage".as_bytes());
+        ctx.reset();
+        ctx.update("TEST".as_bytes());
+        assert_eq!(ctx.finalize(), easy_n::<32>("TEST".as_bytes()));
+
+        let mut ctx = Context::new();
+        ctx.update("garbage".as_bytes());
+        ctx.reset_keyed("test".as_bytes());
+        ctx.update("TEST".as_bytes());
+        assert_eq!(
+            ctx.finalize(),
+            general_keyed("TEST".as_bytes(), "test".as_bytes())
+        );
+    }
+
+    #[test]
+    fn blake2b_serialize() {
+        let mut ctx = Context::<32>::with_key_n("test".as_bytes());
+        ctx.update(&[7u8; 200]);
+        let state = ctx.serialize();
+        assert_eq!(state.len(), STATE_SIZE);
+
+        let mut restored = Context::<32>::restore(&state).unwrap();
+        ctx.update("TEST".as_bytes());
+        restored.update("TEST".as_bytes());
+        assert_eq!(ctx.finalize(), restored.finalize());
+
+        // A 64 byte context does not take the state of a 32 byte one.
+        assert_eq!(
+            Context::<64>::restore(&state).err(),
+            Some(Error::InvalidState)
+        );
+    }
+
+    #[test]
+    fn blake2b_restore_invalid() {
+        let state = Context::new().serialize();
+        assert!(Context::<64>::restore(&state[1..]).is_err());
+
+        let mut bad_version = state;
+        bad_version[0] = 0;
+        assert_eq!(
+            Context::<64>::restore(&bad_version).err(),
+            Some(Error::InvalidState)
+        );
+
+        let mut bad_size = state;
+        bad_size[STATE_SIZE - 8] = 65;
+        assert_eq!(
+            Context::<64>::restore(&bad_size).err(),
+            Some(Error::InvalidState)
+        );
+    }
+
     #[test]
     fn blake2b_len() {
         let vec = easy("TEST".as_bytes());
@@ -167,4 +736,136 @@ mod test {
         let ret = general_keyed("TEST".as_bytes(), "test".as_bytes()).to_vec();
         assert_eq!(hex::encode(ret), "e33ee689585ebe3fc169a845482a47432c21a4134134d2f6c57d06dda4622500e73c79f3ab9d8a3728a7575ebb0f5a78bc6608db427e18cbba1ff6847e3fb6bb");
     }
+
+    #[test]
+    fn blake2b_n() {
+        assert_eq!(
+            hex::encode(easy_n::<32>("TEST".as_bytes())),
+            "88525ae864705608a27fa9a5028fc3af0bdb91b8624563c60fc08a61602fcb8e"
+        );
+        assert_eq!(
+            hex::encode(easy_keyed_n::<32>("TEST".as_bytes(), "test".as_bytes())),
+            "187eb1431a73cf6331b64d60672737471785064a0d4448077b4ab1291ef42e41"
+        );
+        assert_eq!(easy_n::<64>("TEST".as_bytes()), easy("TEST".as_bytes()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn blake2b_n_too_long() {
+        easy_n::<65>("TEST".as_bytes());
+    }
+
+    #[test]
+    fn blake2b_incremental_len() {
+        let mut ctx = Context::<20>::with_key_n("test".as_bytes());
+        assert_eq!(ctx.hash_size(), 20);
+        ctx.update("TEST".as_bytes());
+        ctx.update("MORE".as_bytes());
+        let hash = ctx.finalize();
+        assert_eq!(
+            hex::encode(hash),
+            "7bfb3970f6f53a033fe133bc158366b1cbae14ad"
+        );
+
+        let mut runtime = [0u8; 20];
+        keyed_into(
+            "test".as_bytes(),
+            &["TE".as_bytes(), "STMORE".as_bytes()],
+            &mut runtime,
+        );
+        assert_eq!(runtime, hash);
+    }
+
+    #[test]
+    fn blake2b_write() {
+        let mut ctx = Context::new();
+        io::copy(&mut "TEST".as_bytes(), &mut ctx).unwrap();
+        write!(ctx, "{}", 42).unwrap();
+
+        let mut expected = Context::new();
+        expected.update("TEST42".as_bytes());
+        assert_eq!(ctx.finalize(), expected.finalize());
+    }
+
+    #[test]
+    fn blake2b_many() {
+        let items: Vec<Vec<u8>> = (0..100u8).map(|i| vec![i; i as usize]).collect();
+        let items: Vec<&[u8]> = items.iter().map(Vec::as_slice).collect();
+
+        let hashes = hash_many(&items);
+        assert_eq!(hashes.len(), items.len());
+        for (item, hash) in items.iter().zip(hashes) {
+            assert_eq!(hash, easy(item));
+        }
+    }
+
+    #[test]
+    fn blake2b_reader() {
+        let data = vec![7u8; READ_BUFFER_SIZE * 2 + 10];
+        assert_eq!(hash_reader(&data[..]).unwrap(), easy(&data));
+    }
+
+    #[test]
+    fn blake2b_file() {
+        let path = std::env::temp_dir().join("monocypher-blake2b-hash-file");
+        std::fs::write(&path, "TEST").unwrap();
+        let hash = hash_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(hash, easy("TEST".as_bytes()));
+    }
+
+    #[test]
+    fn blake2b_256() {
+        assert_eq!(
+            Blake2b256::hash("TEST".as_bytes()),
+            easy_n::<32>("TEST".as_bytes())
+        );
+
+        let mut ctx = Blake2b256::with_key("test".as_bytes());
+        ctx.update("TE".as_bytes());
+        ctx.update("ST".as_bytes());
+        assert_eq!(
+            hex::encode(ctx.finalize()),
+            "187eb1431a73cf6331b64d60672737471785064a0d4448077b4ab1291ef42e41"
+        );
+        assert_eq!(
+            Blake2b256::hash_keyed("TEST".as_bytes(), "test".as_bytes()),
+            easy_keyed_n::<32>("TEST".as_bytes(), "test".as_bytes())
+        );
+    }
+
+    #[cfg(feature = "memmap2")]
+    #[test]
+    fn blake2b_file_mmap() {
+        let path = std::env::temp_dir().join("monocypher-blake2b-hash-file-mmap");
+        std::fs::write(&path, "TEST").unwrap();
+        let hash = hash_file_mmap(&path).unwrap();
+        std::fs::write(&path, "").unwrap();
+        let empty_hash = hash_file_mmap(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(hash, easy("TEST".as_bytes()));
+        assert_eq!(empty_hash, easy(&[]));
+    }
+
+    #[test]
+    fn blake2b_mac() {
+        let tag = mac("test".as_bytes(), "TEST".as_bytes());
+        assert_eq!(*tag, general_keyed("TEST".as_bytes(), "test".as_bytes()));
+        assert!(verify_mac("test".as_bytes(), "TEST".as_bytes(), &tag));
+        assert!(!verify_mac("test".as_bytes(), "TSET".as_bytes(), &tag));
+        assert!(!verify_mac("tset".as_bytes(), "TEST".as_bytes(), &tag));
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn blake2b_digest() {
+        use digest::Digest;
+
+        let mut ctx = <Context as Digest>::new();
+        Digest::update(&mut ctx, "TE".as_bytes());
+        Digest::update(&mut ctx, "ST".as_bytes());
+        assert_eq!(ctx.finalize().as_slice(), easy("TEST".as_bytes()));
+    }
 }
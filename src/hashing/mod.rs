@@ -1 +1,3 @@
 pub mod blake2b;
+#[cfg(feature = "ed25519")]
+pub mod sha512;
@@ -0,0 +1,115 @@
+//! SHA-512 hash function
+//!
+//! Requires the `ed25519` feature, which compiles Monocypher's optional sources.
+//!
+//! [Official documentation](https://monocypher.org/manual/sha-512)
+
+use monocypher_sys as ffi;
+use std::io::{self, Write};
+use std::mem;
+
+/// Simple function to hash the input data.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::sha512::easy;
+///
+/// let hash = easy("tohash".as_bytes());
+/// ```
+pub fn easy(data: &[u8]) -> [u8; 64] {
+    unsafe {
+        let mut hash = mem::MaybeUninit::<[u8; 64]>::uninit();
+        ffi::crypto_sha512(hash.as_mut_ptr() as *mut u8, data.as_ptr(), data.len());
+        hash.assume_init()
+    }
+}
+
+#[derive(Clone)]
+pub struct Context(ffi::crypto_sha512_ctx);
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Context based hashing for e.g. large inputs.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::hashing::sha512::Context;
+///
+/// let mut ctx = Context::new();
+/// ctx.update("tohash".as_bytes());
+/// ctx.update("moretohash".as_bytes());
+/// let hash = ctx.finalize();
+/// ```
+impl Context {
+    /// Initializes a new context.
+    #[inline]
+    pub fn new() -> Context {
+        unsafe {
+            let mut ctx = mem::MaybeUninit::<ffi::crypto_sha512_ctx>::uninit();
+            ffi::crypto_sha512_init(ctx.as_mut_ptr());
+            Context(ctx.assume_init())
+        }
+    }
+
+    /// Updates the context with the given data.
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) {
+        unsafe {
+            ffi::crypto_sha512_update(&mut self.0, data.as_ptr(), data.len());
+        }
+    }
+
+    /// Finalizes the hash and returns it.
+    #[inline]
+    pub fn finalize(&mut self) -> [u8; 64] {
+        unsafe {
+            let mut hash = mem::MaybeUninit::<[u8; 64]>::uninit();
+            ffi::crypto_sha512_final(&mut self.0, hash.as_mut_ptr() as *mut u8);
+            hash.assume_init()
+        }
+    }
+}
+
+/// Feeds written data into the hash, so readers can be hashed with [`io::copy`].
+impl Write for Context {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sha512_sum() {
+        assert_eq!(
+            hex::encode(easy("TEST".as_bytes())),
+            "7bfa95a688924c47c7d22381f20cc926f524beacb13f84e203d4bd8cb6ba2fce81c57a5f059bf3d509926487bde925b3bcee0635e4f7baeba054e5dba696b2bf"
+        );
+    }
+
+    #[test]
+    fn sha512_incremental() {
+        let mut ctx = Context::new();
+        ctx.update("TE".as_bytes());
+
+        let mut branch = ctx.clone();
+        branch.update("XT".as_bytes());
+        io::copy(&mut "ST".as_bytes(), &mut ctx).unwrap();
+
+        assert_eq!(ctx.finalize(), easy("TEST".as_bytes()));
+        assert_eq!(branch.finalize(), easy("TEXT".as_bytes()));
+    }
+}
@@ -0,0 +1,297 @@
+//! JSON Web Tokens signed with EdDSA
+//!
+//! Tokens are signed with the `EdDSA` algorithm of RFC 8037, using the Ed25519 keys of
+//! [`ed25519`](crate::ed25519). The claims are any type that serializes to a JSON object. On
+//! top of the signature, [`verify`] checks the registered `exp`, `nbf`, `iss` and `aud` claims
+//! as configured by [`Validation`]. Times are integer seconds since the Unix epoch.
+//!
+//! Requires the `jwt` feature, which enables the `ed25519` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use monocypher::ed25519;
+//! use monocypher::jwt::{self, Validation};
+//! use monocypher::{KeyPair, PubPrivKey, Seed};
+//! use serde_json::Value;
+//!
+//! let keypair: KeyPair<ed25519::PrivateKey, ed25519::PublicKey> =
+//!     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+//!
+//! let claims = serde_json::json!({"sub": "alice", "exp": 4_000_000_000u64});
+//! let token = jwt::sign(&claims, &keypair.private_key).unwrap();
+//!
+//! let claims: Value = jwt::verify(&token, &keypair.public_key, &Validation::default()).unwrap();
+//! ```
+
+use crate::ed25519::{PrivateKey, PublicKey};
+use crate::{PrivKey, PubKey, Signature};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+const HEADER: &[u8] = br#"{"alg":"EdDSA","typ":"JWT"}"#;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Token is not made of three base64url encoded parts.")]
+    Malformed,
+    #[error("Token is not signed with EdDSA.")]
+    Algorithm,
+    #[error("Signature check failed!")]
+    Signature,
+    #[error("Claims are not a JSON object of the expected type.")]
+    Claims,
+    #[error("Token has expired.")]
+    Expired,
+    #[error("Token has no expiration time.")]
+    MissingExpiration,
+    #[error("Token is not valid yet.")]
+    NotYetValid,
+    #[error("Token has the wrong issuer.")]
+    Issuer,
+    #[error("Token is meant for another audience.")]
+    Audience,
+}
+
+/// The claims [`verify`] checks besides the signature.
+///
+/// `exp` is required unless [`require_exp`](Self::require_exp) is turned off, `nbf` is checked
+/// whenever it is present, `iss` and `aud` only if an expected value is set.
+#[derive(Clone, Debug)]
+pub struct Validation {
+    /// Allowed clock skew in seconds for `exp` and `nbf`, 60 by default.
+    pub leeway: u64,
+    /// Rejects tokens without an `exp` claim, `true` by default.
+    pub require_exp: bool,
+    /// Required value of the `iss` claim.
+    pub issuer: Option<String>,
+    /// Value the `aud` claim must be or contain.
+    pub audience: Option<String>,
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Validation {
+            leeway: 60,
+            require_exp: true,
+            issuer: None,
+            audience: None,
+        }
+    }
+}
+
+/// Creates a token of the claims, signed with the private key.
+///
+/// Fails with [`Error::Claims`] if the claims do not serialize.
+pub fn sign<C: Serialize>(claims: &C, private_key: &PrivateKey) -> Result<String, Error> {
+    let claims = serde_json::to_vec(claims).map_err(|_| Error::Claims)?;
+    let mut token = URL_SAFE_NO_PAD.encode(HEADER);
+    token.push('.');
+    token.push_str(&URL_SAFE_NO_PAD.encode(claims));
+    let signature = private_key.sign(token.as_bytes());
+    token.push('.');
+    token.push_str(&URL_SAFE_NO_PAD.encode(*signature));
+    Ok(token)
+}
+
+/// Checks the signature and the claims of a token at the current time and decodes the claims.
+pub fn verify<C: DeserializeOwned>(
+    token: &str,
+    public_key: &PublicKey,
+    validation: &Validation,
+) -> Result<C, Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    verify_at(token, public_key, validation, now)
+}
+
+/// Like [`verify`], but checks the times against `now` seconds since the Unix epoch.
+pub fn verify_at<C: DeserializeOwned>(
+    token: &str,
+    public_key: &PublicKey,
+    validation: &Validation,
+    now: u64,
+) -> Result<C, Error> {
+    let payload = check_signature(token, public_key)?;
+    let claims: Value = serde_json::from_slice(&payload).map_err(|_| Error::Claims)?;
+    validate(&claims, validation, now)?;
+    serde_json::from_value(claims).map_err(|_| Error::Claims)
+}
+
+// Checks the header and the signature of a token, returns the decoded payload.
+fn check_signature(token: &str, public_key: &PublicKey) -> Result<Vec<u8>, Error> {
+    let mut parts = token.split('.');
+    let (Some(header), Some(payload), Some(signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Error::Malformed);
+    };
+    let decode = |part: &str| URL_SAFE_NO_PAD.decode(part).map_err(|_| Error::Malformed);
+
+    let signing_input = &token[..header.len() + 1 + payload.len()];
+    let header: Value = serde_json::from_slice(&decode(header)?).map_err(|_| Error::Malformed)?;
+    if header.get("alg").and_then(Value::as_str) != Some("EdDSA") {
+        return Err(Error::Algorithm);
+    }
+    let signature = <[u8; 64]>::try_from(decode(signature)?).map_err(|_| Error::Malformed)?;
+    public_key
+        .check(Signature::from(signature), signing_input.as_bytes())
+        .map_err(|_| Error::Signature)?;
+    decode(payload)
+}
+
+fn validate(claims: &Value, validation: &Validation, now: u64) -> Result<(), Error> {
+    if !claims.is_object() {
+        return Err(Error::Claims);
+    }
+    let time = |name: &str| match claims.get(name) {
+        Some(time) => time.as_u64().map(Some).ok_or(Error::Claims),
+        None => Ok(None),
+    };
+    match time("exp")? {
+        Some(exp) if now >= exp.saturating_add(validation.leeway) => return Err(Error::Expired),
+        None if validation.require_exp => return Err(Error::MissingExpiration),
+        _ => (),
+    }
+    if let Some(nbf) = time("nbf")? {
+        if now.saturating_add(validation.leeway) < nbf {
+            return Err(Error::NotYetValid);
+        }
+    }
+
+    if let Some(issuer) = &validation.issuer {
+        if claims.get("iss").and_then(Value::as_str) != Some(issuer) {
+            return Err(Error::Issuer);
+        }
+    }
+    if let Some(audience) = &validation.audience {
+        let aud = claims.get("aud");
+        let matches = match aud.and_then(Value::as_array) {
+            Some(audiences) => audiences.iter().any(|aud| aud.as_str() == Some(audience)),
+            None => aud.and_then(Value::as_str) == Some(audience),
+        };
+        if !matches {
+            return Err(Error::Audience);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyPair, PubPrivKey, Seed};
+
+    // The key of RFC 8032 section 7.1, test 1, which RFC 8037 uses too.
+    fn keypair() -> KeyPair<PrivateKey, PublicKey> {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60");
+        KeyPair::generate_key_pair(Seed::from(<[u8; 32]>::try_from(seed.unwrap()).unwrap()))
+    }
+
+    fn claims() -> Value {
+        serde_json::from_str(r#"{"sub":"alice","iss":"me","aud":["a","b"],"nbf":1000,"exp":2000}"#)
+            .unwrap()
+    }
+
+    // RFC 8037 appendix A.4.
+    #[test]
+    fn rfc8037() {
+        let token = "eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc.\
+                     hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dW\
+                     bhijcNR4ki4iylGjg5BhVsPt9g7sVvpAr_MuM0KAg";
+        let payload = check_signature(token, &keypair().public_key).unwrap();
+        assert_eq!(payload, b"Example of Ed25519 signing");
+    }
+
+    #[test]
+    fn sign_verify() {
+        let keypair = keypair();
+        let token = sign(&claims(), &keypair.private_key).unwrap();
+        let validation = Validation {
+            issuer: Some("me".to_string()),
+            audience: Some("b".to_string()),
+            ..Validation::default()
+        };
+        let ret: Value = verify_at(&token, &keypair.public_key, &validation, 1500).unwrap();
+        assert_eq!(ret, claims());
+    }
+
+    #[test]
+    fn times() {
+        let keypair = keypair();
+        let token = sign(&claims(), &keypair.private_key).unwrap();
+        let at = |now| verify_at::<Value>(&token, &keypair.public_key, &Validation::default(), now);
+        assert!(at(2059).is_ok());
+        assert_eq!(at(2060), Err(Error::Expired));
+        assert!(at(940).is_ok());
+        assert_eq!(at(939), Err(Error::NotYetValid));
+    }
+
+    #[test]
+    fn missing_expiration() {
+        let keypair = keypair();
+        let claims: Value = serde_json::from_str(r#"{"sub":"alice"}"#).unwrap();
+        let token = sign(&claims, &keypair.private_key).unwrap();
+        let ret = verify_at::<Value>(&token, &keypair.public_key, &Validation::default(), 1500);
+        assert_eq!(ret, Err(Error::MissingExpiration));
+
+        let validation = Validation {
+            require_exp: false,
+            ..Validation::default()
+        };
+        assert!(verify_at::<Value>(&token, &keypair.public_key, &validation, 1500).is_ok());
+    }
+
+    #[test]
+    fn issuer_audience() {
+        let keypair = keypair();
+        let token = sign(&claims(), &keypair.private_key).unwrap();
+        let check = |validation| verify_at::<Value>(&token, &keypair.public_key, &validation, 1500);
+        let issuer = Validation {
+            issuer: Some("you".to_string()),
+            ..Validation::default()
+        };
+        assert_eq!(check(issuer), Err(Error::Issuer));
+        let audience = Validation {
+            audience: Some("c".to_string()),
+            ..Validation::default()
+        };
+        assert_eq!(check(audience), Err(Error::Audience));
+    }
+
+    #[test]
+    fn forged() {
+        let keypair = keypair();
+        let token = sign(&claims(), &keypair.private_key).unwrap();
+        let (signing_input, signature) = token.rsplit_once('.').unwrap();
+        let (header, _) = signing_input.split_once('.').unwrap();
+
+        let forged = format!("{header}.{}.{signature}", URL_SAFE_NO_PAD.encode(b"{}"));
+        let ret = verify_at::<Value>(&forged, &keypair.public_key, &Validation::default(), 1500);
+        assert_eq!(ret, Err(Error::Signature));
+
+        let none = URL_SAFE_NO_PAD.encode(br#"{"alg":"none"}"#);
+        let (_, rest) = token.split_once('.').unwrap();
+        let ret = verify_at::<Value>(
+            &format!("{none}.{rest}"),
+            &keypair.public_key,
+            &Validation::default(),
+            1500,
+        );
+        assert_eq!(ret, Err(Error::Algorithm));
+
+        let ret = verify_at::<Value>(
+            signing_input,
+            &keypair.public_key,
+            &Validation::default(),
+            1500,
+        );
+        assert_eq!(ret, Err(Error::Malformed));
+    }
+}
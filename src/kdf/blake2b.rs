@@ -0,0 +1,145 @@
+//! Labeled key derivation with keyed BLAKE2b
+//!
+//! The subkey is the keyed BLAKE2b digest, with the master key as key and the length of the
+//! output buffer as digest length, of:
+//!
+//! ```text
+//! le64(label length) || label || context
+//! ```
+//!
+//! The length prefix keeps label and context apart, so different pairs never hash the same
+//! input.
+//!
+//! [`expand_label`] hashes the TLS 1.3 `HkdfLabel` encoding instead, for protocols that
+//! describe their key schedule in those terms.
+
+use crate::aead::Key;
+use crate::hashing::blake2b;
+use crate::kdf::{self, Error};
+
+/// Derives a subkey into `subkey`, which is 1 to 64 bytes long, for a purpose named by `label`.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::Key;
+/// use monocypher::kdf::blake2b::derive;
+///
+/// let master = Key::from([137u8; 32]);
+/// let mut encryption_key = [0u8; 32];
+/// derive(&master, "encryption", "session 1".as_bytes(), &mut encryption_key).unwrap();
+/// let mut mac_key = [0u8; 32];
+/// derive(&master, "authentication", "session 1".as_bytes(), &mut mac_key).unwrap();
+/// ```
+pub fn derive(master: &Key, label: &str, context: &[u8], subkey: &mut [u8]) -> Result<(), Error> {
+    check_len(subkey)?;
+    let label_len = (label.len() as u64).to_le_bytes();
+    blake2b::keyed_into(
+        &master[..],
+        &[&label_len, label.as_bytes(), context],
+        subkey,
+    );
+    Ok(())
+}
+
+/// Derives `okm`, which is 1 to 64 bytes long, from a secret with a TLS 1.3 style label.
+///
+/// The output is the keyed BLAKE2b digest, with the secret as key and the length of `okm` as
+/// digest length, of:
+///
+/// ```text
+/// be16(okm length) || u8(label length) || label || u8(context length) || context
+/// ```
+///
+/// Label and context are at most 255 bytes long, no prefix is added to the label.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::Key;
+/// use monocypher::kdf::blake2b::expand_label;
+///
+/// let secret = Key::from([137u8; 32]);
+/// let mut key = [0u8; 32];
+/// expand_label(&secret, "client key", "transcript hash".as_bytes(), &mut key).unwrap();
+/// ```
+pub fn expand_label(
+    secret: &Key,
+    label: &str,
+    context: &[u8],
+    okm: &mut [u8],
+) -> Result<(), Error> {
+    check_len(okm)?;
+    let info = kdf::hkdf_label(okm.len(), label, context)?;
+    blake2b::keyed_into(&secret[..], &[&info], okm);
+    Ok(())
+}
+
+fn check_len(out: &[u8]) -> Result<(), Error> {
+    if (1..=64).contains(&out.len()) {
+        Ok(())
+    } else {
+        Err(Error::InvalidLength)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vector() {
+        let mut subkey = [0u8; 32];
+        derive(
+            &Key::from([1u8; 32]),
+            "encryption",
+            "session 1".as_bytes(),
+            &mut subkey,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(subkey),
+            "f73541b0af9e9ba140aec73f15ae936e7b096c642ef1c37b9ccb7c17571781ea"
+        );
+    }
+
+    #[test]
+    fn separated() {
+        let master = Key::from([1u8; 32]);
+        let mut a = [0u8; 32];
+        derive(&master, "ab", "c".as_bytes(), &mut a).unwrap();
+        let mut b = [0u8; 32];
+        derive(&master, "a", "bc".as_bytes(), &mut b).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn invalid_length() {
+        let master = Key::from([1u8; 32]);
+        assert_eq!(
+            derive(&master, "a", &[], &mut []),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(
+            derive(&master, "a", &[], &mut [0u8; 65]),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn label_vector() {
+        let mut okm = [0u8; 32];
+        expand_label(&Key::from([1u8; 32]), "key", "hello".as_bytes(), &mut okm).unwrap();
+        assert_eq!(
+            hex::encode(okm),
+            "8d5a528ac16f7d1e092086d6014eb862c71adb0980cd964e5a8dc831d2f7e007"
+        );
+    }
+
+    #[test]
+    fn label_too_long() {
+        let label = "a".repeat(256);
+        let ret = expand_label(&Key::from([1u8; 32]), &label, &[], &mut [0u8; 32]);
+        assert_eq!(ret, Err(Error::InvalidLabel));
+    }
+}
@@ -0,0 +1,193 @@
+//! HKDF-SHA-512 (RFC 5869)
+//!
+//! Requires the `ed25519` feature, which compiles Monocypher's optional sources.
+//!
+//! [Official documentation](https://monocypher.org/manual/sha-512)
+
+use crate::kdf::{self, Error};
+use crate::utils;
+use derive_more::From;
+use monocypher_sys as ffi;
+use std::ops::Deref;
+
+/// Maximum length of the output keying material, 255 SHA-512 blocks.
+pub const MAX_OKM_SIZE: usize = 255 * 64;
+
+/// A pseudorandom key produced by [`extract`], wiped when dropped.
+#[derive(From)]
+pub struct Prk([u8; 64]);
+
+impl_zeroize!(Prk);
+
+impl Deref for Prk {
+    type Target = [u8; 64];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for Prk {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// Extracts a pseudorandom key from input keying material.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::kdf::hkdf_sha512;
+///
+/// let prk = hkdf_sha512::extract("salt".as_bytes(), &[1u8; 32]);
+/// ```
+pub fn extract(salt: &[u8], ikm: &[u8]) -> Prk {
+    let mut prk = [0u8; 64];
+    unsafe {
+        ffi::crypto_sha512_hmac(
+            prk.as_mut_ptr(),
+            salt.as_ptr(),
+            salt.len(),
+            ikm.as_ptr(),
+            ikm.len(),
+        );
+    }
+    Prk(prk)
+}
+
+/// Expands a pseudorandom key into `okm`, which can be up to [`MAX_OKM_SIZE`] bytes long.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::kdf::hkdf_sha512;
+///
+/// let prk = hkdf_sha512::extract("salt".as_bytes(), &[1u8; 32]);
+/// let mut key = [0u8; 32];
+/// hkdf_sha512::expand(&prk, "encryption key".as_bytes(), &mut key).unwrap();
+/// ```
+pub fn expand(prk: &Prk, info: &[u8], okm: &mut [u8]) -> Result<(), Error> {
+    check_len(okm)?;
+    unsafe {
+        ffi::crypto_sha512_hkdf_expand(
+            okm.as_mut_ptr(),
+            okm.len(),
+            prk.as_ptr(),
+            prk.len(),
+            info.as_ptr(),
+            info.len(),
+        );
+    }
+    Ok(())
+}
+
+/// Extracts and expands in one step.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::kdf::hkdf_sha512;
+///
+/// let mut key = [0u8; 32];
+/// hkdf_sha512::derive(&[1u8; 32], "salt".as_bytes(), "encryption key".as_bytes(), &mut key)
+///     .unwrap();
+/// ```
+pub fn derive(ikm: &[u8], salt: &[u8], info: &[u8], okm: &mut [u8]) -> Result<(), Error> {
+    check_len(okm)?;
+    unsafe {
+        ffi::crypto_sha512_hkdf(
+            okm.as_mut_ptr(),
+            okm.len(),
+            ikm.as_ptr(),
+            ikm.len(),
+            salt.as_ptr(),
+            salt.len(),
+            info.as_ptr(),
+            info.len(),
+        );
+    }
+    Ok(())
+}
+
+/// Expands a pseudorandom key into `okm` with a TLS 1.3 style label.
+///
+/// This is [`expand`] with the TLS 1.3 `HkdfLabel` encoding of the output length, label and
+/// context as info. Label and context are at most 255 bytes long, no prefix is added to the
+/// label.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::kdf::hkdf_sha512;
+///
+/// let prk = hkdf_sha512::extract("salt".as_bytes(), &[1u8; 32]);
+/// let mut key = [0u8; 32];
+/// hkdf_sha512::expand_label(&prk, "client key", "transcript hash".as_bytes(), &mut key)
+///     .unwrap();
+/// ```
+pub fn expand_label(prk: &Prk, label: &str, context: &[u8], okm: &mut [u8]) -> Result<(), Error> {
+    let info = kdf::hkdf_label(okm.len(), label, context)?;
+    expand(prk, &info, okm)
+}
+
+fn check_len(okm: &[u8]) -> Result<(), Error> {
+    if okm.len() > MAX_OKM_SIZE {
+        return Err(Error::InvalidLength);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Inputs of RFC 5869 test case 1 with SHA-512
+    const IKM: [u8; 22] = [0x0b; 22];
+    const SALT: [u8; 13] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+    const INFO: [u8; 10] = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+    const OKM: &str =
+        "832390086cda71fb47625bb5ceb168e4c8e26a1a16ed34d9fc7fe92c1481579338da362cb8d9f925d7cb";
+
+    #[test]
+    fn extract_expand() {
+        let prk = extract(&SALT, &IKM);
+        assert_eq!(
+            hex::encode(*prk),
+            "665799823737ded04a88e47e54a5890bb2c3d247c7a4254a8e61350723590a26\
+             c36238127d8661b88cf80ef802d57e2f7cebcf1e00e083848be19929c61b4237"
+        );
+
+        let mut okm = [0u8; 42];
+        expand(&prk, &INFO, &mut okm).unwrap();
+        assert_eq!(hex::encode(okm), OKM);
+    }
+
+    #[test]
+    fn derive_one_step() {
+        let mut okm = [0u8; 42];
+        derive(&IKM, &SALT, &INFO, &mut okm).unwrap();
+        assert_eq!(hex::encode(okm), OKM);
+    }
+
+    #[test]
+    fn too_long() {
+        let prk = extract(&SALT, &IKM);
+        let mut okm = vec![0u8; MAX_OKM_SIZE + 1];
+        assert_eq!(expand(&prk, &INFO, &mut okm), Err(Error::InvalidLength));
+        assert_eq!(
+            derive(&IKM, &SALT, &INFO, &mut okm),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn label() {
+        let mut okm = [0u8; 32];
+        expand_label(&Prk::from([1u8; 64]), "key", "hello".as_bytes(), &mut okm).unwrap();
+        assert_eq!(
+            hex::encode(okm),
+            "af90883ee758e3499d1a57dd645cbe4bf21a169c2f9c00a6ff9e28838350e67c"
+        );
+    }
+}
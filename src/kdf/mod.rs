@@ -0,0 +1,37 @@
+//! Key derivation functions
+
+use thiserror::Error;
+
+pub mod blake2b;
+#[cfg(feature = "ed25519")]
+pub mod hkdf_sha512;
+mod ratchet;
+mod sodium;
+
+pub use ratchet::{MessageKey, Ratchet};
+pub use sodium::derive_subkey;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Requested output length is not supported.")]
+    InvalidLength,
+    #[error("Label or context is longer than 255 bytes.")]
+    InvalidLabel,
+}
+
+// Encodes the info input of `expand_label` like the TLS 1.3 HkdfLabel structure:
+//
+// be16(out_len) || u8(label length) || label || u8(context length) || context
+fn hkdf_label(out_len: usize, label: &str, context: &[u8]) -> Result<Vec<u8>, Error> {
+    let out_len = u16::try_from(out_len).map_err(|_| Error::InvalidLength)?;
+    let label_len = u8::try_from(label.len()).map_err(|_| Error::InvalidLabel)?;
+    let context_len = u8::try_from(context.len()).map_err(|_| Error::InvalidLabel)?;
+
+    let mut info = Vec::with_capacity(4 + label.len() + context.len());
+    info.extend_from_slice(&out_len.to_be_bytes());
+    info.push(label_len);
+    info.extend_from_slice(label.as_bytes());
+    info.push(context_len);
+    info.extend_from_slice(context);
+    Ok(info)
+}
@@ -0,0 +1,117 @@
+//! Symmetric key ratchet
+//!
+//! Every step derives a message key and the next chain key from the current chain key with
+//! keyed BLAKE2b, then wipes the current chain key. A leaked chain key does not reveal the
+//! message keys of earlier steps.
+//!
+//! ```text
+//! message key     = BLAKE2b-256(key = chain key, 0x01)
+//! next chain key  = BLAKE2b-256(key = chain key, 0x02)
+//! ```
+
+use crate::hashing::blake2b::Blake2b256;
+use crate::utils;
+use std::ops::Deref;
+
+const MESSAGE_KEY_CONSTANT: [u8; 1] = [0x01];
+const CHAIN_KEY_CONSTANT: [u8; 1] = [0x02];
+
+/// A 32 byte key for a single message, wiped when dropped.
+pub struct MessageKey([u8; 32]);
+
+impl_ct_eq!(MessageKey);
+impl_zeroize!(MessageKey);
+
+impl Deref for MessageKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for MessageKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// KDF chain that yields one [`MessageKey`] per step.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::kdf::Ratchet;
+///
+/// let mut sender = Ratchet::new([137u8; 32]);
+/// let mut receiver = Ratchet::new([137u8; 32]);
+///
+/// let key = sender.step();
+/// assert_eq!(*key, *receiver.step());
+/// ```
+#[derive(Clone)]
+pub struct Ratchet {
+    chain_key: [u8; 32],
+    steps: u64,
+}
+
+impl_zeroize!(Ratchet, chain_key);
+
+impl Ratchet {
+    /// Creates a chain from an initial chain key.
+    pub fn new(chain_key: [u8; 32]) -> Ratchet {
+        Ratchet {
+            chain_key,
+            steps: 0,
+        }
+    }
+
+    /// Derives the next message key and replaces the chain key.
+    pub fn step(&mut self) -> MessageKey {
+        let message_key = Blake2b256::hash_keyed(&MESSAGE_KEY_CONSTANT, &self.chain_key);
+        let mut next_chain_key = Blake2b256::hash_keyed(&CHAIN_KEY_CONSTANT, &self.chain_key);
+        self.chain_key.copy_from_slice(&next_chain_key);
+        utils::wipe(&mut next_chain_key);
+        self.steps += 1;
+        MessageKey(message_key)
+    }
+
+    /// Returns the number of message keys derived so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+}
+
+impl Drop for Ratchet {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.chain_key);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vector() {
+        let mut ratchet = Ratchet::new([1u8; 32]);
+        assert_eq!(
+            hex::encode(*ratchet.step()),
+            "336bbe7b6402694318d68130281fd32ffe3e54d70eee54077ab2fcd8b8c7d360"
+        );
+        assert_eq!(
+            hex::encode(*ratchet.step()),
+            "da1bf8c19c7ea92c622932e11a2622ac8df85f1dcd883e8a7e3e657cfe1210b6"
+        );
+        assert_eq!(ratchet.steps(), 2);
+    }
+
+    #[test]
+    fn distinct_keys() {
+        let mut ratchet = Ratchet::new([1u8; 32]);
+        let first = ratchet.step();
+        let second = ratchet.step();
+        assert_ne!(*first, *second);
+        assert_ne!(ratchet.chain_key, [1u8; 32]);
+    }
+}
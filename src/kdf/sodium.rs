@@ -0,0 +1,74 @@
+//! Subkey derivation compatible with libsodium's `crypto_kdf_blake2b_derive_from_key`.
+
+use crate::hashing::blake2b;
+use crate::kdf::Error;
+
+/// Derives the subkey `subkey_id` into `subkey`, which is 16 to 64 bytes long, from a master key.
+///
+/// The output is byte for byte the one of libsodium's `crypto_kdf_derive_from_key`, so key
+/// hierarchies created with libsodium or sodiumoxide can be kept.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::kdf::derive_subkey;
+///
+/// let master = [137u8; 32];
+/// let mut subkey = [0u8; 32];
+/// derive_subkey(&master, 1, *b"Examples", &mut subkey).unwrap();
+/// ```
+pub fn derive_subkey(
+    master: &[u8; 32],
+    subkey_id: u64,
+    context: [u8; 8],
+    subkey: &mut [u8],
+) -> Result<(), Error> {
+    if !(16..=64).contains(&subkey.len()) {
+        return Err(Error::InvalidLength);
+    }
+    let mut salt = [0u8; 16];
+    salt[..8].copy_from_slice(&subkey_id.to_le_bytes());
+    let mut personal = [0u8; 16];
+    personal[..8].copy_from_slice(&context);
+
+    blake2b::salt_personal_into(master, &salt, &personal, subkey);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn master() -> [u8; 32] {
+        let mut master = [0u8; 32];
+        for (i, byte) in master.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        master
+    }
+
+    #[test]
+    fn libsodium() {
+        let mut subkey = [0u8; 32];
+        derive_subkey(&master(), 0, *b"KDF test", &mut subkey).unwrap();
+        assert_eq!(
+            hex::encode(subkey),
+            "c13fcc2e6cd0cd0f82d93b163a5696c5105378f8c629d36baf3ae0239de9c280"
+        );
+        let mut subkey = [0u8; 16];
+        derive_subkey(&master(), 7, *b"KDF test", &mut subkey).unwrap();
+        assert_eq!(hex::encode(subkey), "6a322f7881868cb337cc8b7136830318");
+    }
+
+    #[test]
+    fn invalid_length() {
+        assert_eq!(
+            derive_subkey(&master(), 0, *b"KDF test", &mut [0u8; 15]),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(
+            derive_subkey(&master(), 0, *b"KDF test", &mut [0u8; 65]),
+            Err(Error::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Conversions between the X25519 keys and [`x25519_dalek`]'s.
+//!
+//! Requires the `interop-dalek` feature.
+
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::utils;
+
+impl From<&x25519_dalek::StaticSecret> for SecretKey {
+    fn from(secret_key: &x25519_dalek::StaticSecret) -> Self {
+        SecretKey(*secret_key.as_bytes())
+    }
+}
+
+impl From<&SecretKey> for x25519_dalek::StaticSecret {
+    fn from(secret_key: &SecretKey) -> Self {
+        let mut bytes = secret_key.0;
+        let ret = x25519_dalek::StaticSecret::from(bytes);
+        utils::wipe(&mut bytes);
+        ret
+    }
+}
+
+impl From<x25519_dalek::PublicKey> for PublicKey {
+    fn from(public_key: x25519_dalek::PublicKey) -> Self {
+        PublicKey(public_key.to_bytes())
+    }
+}
+
+impl From<PublicKey> for x25519_dalek::PublicKey {
+    fn from(public_key: PublicKey) -> Self {
+        x25519_dalek::PublicKey::from(public_key.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_shared_secret() {
+        let alice = SecretKey::from([1u8; 32]);
+        let bob = x25519_dalek::StaticSecret::from([2u8; 32]);
+
+        let alice_public = x25519_dalek::PublicKey::from(&x25519_dalek::StaticSecret::from(&alice));
+        assert_eq!(PublicKey::from(alice_public), alice.public_key());
+        let bob_public = PublicKey::from(x25519_dalek::PublicKey::from(&bob));
+        assert_eq!(bob_public, SecretKey::from(&bob).public_key());
+
+        let ours = alice.diffie_hellman(&bob_public).unwrap();
+        let theirs = bob.diffie_hellman(&alice.public_key().into());
+        assert_eq!(*ours, *theirs.as_bytes());
+    }
+}
@@ -0,0 +1,87 @@
+//! Single use secret keys
+
+use crate::key_exchange::{Error, PublicKey, SecretKey, SharedSecret};
+
+/// An ephemeral secret key that computes a single shared secret.
+///
+/// [`shared`](Ephemeral::shared) consumes the key, which is wiped right after the computation.
+/// The key cannot be used twice or outlive the exchange, which is what gives protocols built on
+/// ephemeral keys their forward secrecy.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::key_exchange::{Ephemeral, SecretKey};
+///
+/// let bob = SecretKey::from([2u8; 32]);
+///
+/// let ephemeral = Ephemeral::generate().unwrap();
+/// let ephemeral_pk = ephemeral.public_key();
+/// let shared = ephemeral.shared(&bob.public_key()).unwrap();
+/// assert_eq!(*shared, *bob.diffie_hellman(&ephemeral_pk).unwrap());
+/// ```
+///
+/// The key is gone after the first use:
+///
+/// ```compile_fail
+/// use monocypher::key_exchange::{Ephemeral, SecretKey};
+///
+/// let bob = SecretKey::from([2u8; 32]).public_key();
+/// let ephemeral = Ephemeral::from(SecretKey::from([1u8; 32]));
+/// let first = ephemeral.shared(&bob);
+/// let second = ephemeral.shared(&bob);
+/// ```
+pub struct Ephemeral(SecretKey);
+
+impl Ephemeral {
+    /// Wraps a secret key that must only be used once.
+    pub fn new(secret_key: SecretKey) -> Ephemeral {
+        Ephemeral(secret_key)
+    }
+
+    /// Generates an ephemeral key with the operating system's random number generator.
+    ///
+    /// Requires the `getrandom` feature.
+    #[cfg(feature = "getrandom")]
+    pub fn generate() -> Result<Ephemeral, Error> {
+        SecretKey::generate().map(Ephemeral)
+    }
+
+    /// Computes the public key to send to the other party.
+    pub fn public_key(&self) -> PublicKey {
+        self.0.public_key()
+    }
+
+    /// Computes the shared secret with their public key and wipes the ephemeral key.
+    pub fn shared(self, their_public_key: &PublicKey) -> Result<SharedSecret, Error> {
+        self.0.diffie_hellman(their_public_key)
+    }
+}
+
+impl From<SecretKey> for Ephemeral {
+    fn from(secret_key: SecretKey) -> Self {
+        Ephemeral(secret_key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shared() {
+        let alice = Ephemeral::from(SecretKey::from([1u8; 32]));
+        let alice_pk = alice.public_key();
+        let bob = SecretKey::from([2u8; 32]);
+
+        let shared = alice.shared(&bob.public_key()).unwrap();
+        assert_eq!(*shared, *bob.diffie_hellman(&alice_pk).unwrap());
+    }
+
+    #[test]
+    fn low_order() {
+        let alice = Ephemeral::new(SecretKey::from([1u8; 32]));
+        let ret = alice.shared(&PublicKey::from([0u8; 32]));
+        assert!(matches!(ret, Err(Error::LowOrder)));
+    }
+}
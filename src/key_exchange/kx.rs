@@ -0,0 +1,121 @@
+//! Session keys from a key exchange, compatible with libsodium's `crypto_kx`
+//!
+//! Both parties hash the X25519 shared secret with the client and the server public key using
+//! BLAKE2b-512. The first half of the hash is the key from the server to the client, the second
+//! half the key from the client to the server.
+
+use crate::aead::Key;
+use crate::hashing::blake2b;
+use crate::key_exchange::{Error, PublicKey, SecretKey};
+use crate::utils;
+
+/// Keys for the two directions of a session.
+pub struct SessionKeys {
+    /// Key to decrypt what the other party sends.
+    pub rx: Key,
+    /// Key to encrypt what is sent to the other party.
+    pub tx: Key,
+}
+
+/// Computes the session keys of the client.
+///
+/// Fails if the server public key has a low order.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::key_exchange::kx::{client_session_keys, server_session_keys};
+/// use monocypher::key_exchange::SecretKey;
+///
+/// let client = SecretKey::from([1u8; 32]);
+/// let server = SecretKey::from([2u8; 32]);
+///
+/// let client_keys = client_session_keys(&client, &server.public_key()).unwrap();
+/// let server_keys = server_session_keys(&server, &client.public_key()).unwrap();
+/// assert_eq!(*client_keys.tx, *server_keys.rx);
+/// assert_eq!(*client_keys.rx, *server_keys.tx);
+/// ```
+pub fn client_session_keys(
+    client_sk: &SecretKey,
+    server_pk: &PublicKey,
+) -> Result<SessionKeys, Error> {
+    let (server_to_client, client_to_server) =
+        session_keys(client_sk, server_pk, &client_sk.public_key(), server_pk)?;
+    Ok(SessionKeys {
+        rx: server_to_client,
+        tx: client_to_server,
+    })
+}
+
+/// Computes the session keys of the server.
+///
+/// Fails if the client public key has a low order.
+pub fn server_session_keys(
+    server_sk: &SecretKey,
+    client_pk: &PublicKey,
+) -> Result<SessionKeys, Error> {
+    let (server_to_client, client_to_server) =
+        session_keys(server_sk, client_pk, client_pk, &server_sk.public_key())?;
+    Ok(SessionKeys {
+        rx: client_to_server,
+        tx: server_to_client,
+    })
+}
+
+// Returns the server to client and the client to server key.
+fn session_keys(
+    secret_key: &SecretKey,
+    their_public_key: &PublicKey,
+    client_pk: &PublicKey,
+    server_pk: &PublicKey,
+) -> Result<(Key, Key), Error> {
+    let shared_secret = secret_key.diffie_hellman(their_public_key)?;
+    let mut ctx = blake2b::Context::new();
+    ctx.update(&*shared_secret);
+    ctx.update(&**client_pk);
+    ctx.update(&**server_pk);
+    let mut keys = ctx.finalize();
+
+    let to_key = |half: &[u8]| {
+        let mut key = [0u8; 32];
+        key.copy_from_slice(half);
+        let ret = Key::from(key);
+        utils::wipe(&mut key);
+        ret
+    };
+    let ret = (to_key(&keys[..32]), to_key(&keys[32..]));
+    utils::wipe(&mut keys);
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn client_server() {
+        let client = SecretKey::from([1u8; 32]);
+        let server = SecretKey::from([2u8; 32]);
+        let client_keys = client_session_keys(&client, &server.public_key()).unwrap();
+        let server_keys = server_session_keys(&server, &client.public_key()).unwrap();
+
+        // Computed with libsodium's crypto_kx layout.
+        assert_eq!(
+            hex::encode(*client_keys.rx),
+            "4569885e3fc87a31376ed87c220f5aba6941fbdb8f18ae14880028c7af6a6401"
+        );
+        assert_eq!(
+            hex::encode(*client_keys.tx),
+            "9c5ef3ae6de0d9f80408d03495e5ebd68028b17d58e03dccf4ac736a007220dd"
+        );
+        assert_eq!(*client_keys.rx, *server_keys.tx);
+        assert_eq!(*client_keys.tx, *server_keys.rx);
+    }
+
+    #[test]
+    fn low_order() {
+        let client = SecretKey::from([1u8; 32]);
+        let ret = client_session_keys(&client, &PublicKey::from([0u8; 32]));
+        assert!(matches!(ret, Err(Error::LowOrder)));
+    }
+}
@@ -0,0 +1,296 @@
+//! Elliptic Curve Diffie-Hellman key exchange
+//!
+//! //! [Official documentation](https://monocypher.org/manual/key_exchange)
+
+use crate::fingerprint::Fingerprint;
+use crate::utils;
+use derive_more::From;
+use monocypher_sys as ffi;
+use std::mem;
+use std::ops::Deref;
+use thiserror::Error;
+
+#[cfg(feature = "interop-dalek")]
+mod dalek;
+mod ephemeral;
+pub mod kx;
+
+pub use ephemeral::Ephemeral;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Public key has a low order, the shared secret is all zero.")]
+    LowOrder,
+}
+
+#[cfg(feature = "getrandom")]
+impl From<crate::random::Error> for Error {
+    fn from(_: crate::random::Error) -> Self {
+        Error::Random
+    }
+}
+
+// Encodings of the points of low order, including non canonical ones, as listed by libsodium.
+const LOW_ORDER_POINTS: [[u8; 32]; 7] = [
+    [0; 32],
+    [
+        1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ],
+    [
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ],
+    [
+        0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83, 0xef,
+        0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd, 0xd0, 0x9f,
+        0x11, 0x57,
+    ],
+    [
+        0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xed, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xee, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+];
+
+/// An X25519 secret key, wiped on drop.
+#[derive(From)]
+pub struct SecretKey([u8; 32]);
+
+impl_ct_eq!(SecretKey);
+impl_zeroize!(SecretKey);
+
+impl SecretKey {
+    /// Generates a clamped secret key with the operating system's random number generator.
+    ///
+    /// Requires the `getrandom` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::key_exchange::SecretKey;
+    ///
+    /// let secret_key = SecretKey::generate().unwrap();
+    /// let public_key = secret_key.public_key();
+    /// ```
+    #[cfg(feature = "getrandom")]
+    pub fn generate() -> Result<SecretKey, Error> {
+        let mut secret_key = SecretKey([0u8; 32]);
+        crate::random::fill(&mut secret_key.0)?;
+        secret_key.0[0] &= 248;
+        secret_key.0[31] &= 127;
+        secret_key.0[31] |= 64;
+        Ok(secret_key)
+    }
+
+    /// Computes the public key of the secret key.
+    pub fn public_key(&self) -> PublicKey {
+        public(self)
+    }
+
+    /// Computes the shared secret with their public key, see [`shared_secret`].
+    pub fn diffie_hellman(&self, their_public_key: &PublicKey) -> Result<SharedSecret, Error> {
+        shared_secret(self, their_public_key)
+    }
+}
+
+impl Deref for SecretKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// An X25519 public key.
+#[derive(Clone, Copy, Debug, From)]
+pub struct PublicKey([u8; 32]);
+
+impl_ct_eq!(PublicKey);
+
+impl PublicKey {
+    /// Returns the fingerprint of the key, to compare it manually.
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new("x25519", &self.0)
+    }
+
+    /// Checks whether the key is one of the known points of low order.
+    ///
+    /// The shared secret with such a key does not depend on the secret key. Checking public keys
+    /// when they are received rejects them before any computation, [`shared_secret`] rejects
+    /// them in any case.
+    pub fn is_low_order(&self) -> bool {
+        // The most significant bit is ignored by X25519.
+        let mut key = self.0;
+        key[31] &= 127;
+        LOW_ORDER_POINTS.iter().fold(false, |found, point| {
+            found | utils::verify(&key, &point[..])
+        })
+    }
+}
+
+impl Deref for PublicKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// The raw X25519 shared secret, wiped on drop.
+///
+/// It is not uniformly random, hash it before using it as a key.
+pub struct SharedSecret([u8; 32]);
+
+impl_ct_eq!(SharedSecret);
+impl_zeroize!(SharedSecret);
+
+impl Deref for SharedSecret {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// Deterministically computes the public key from a random secret key.
+///
+/// # Example
+/// ```
+/// use monocypher::key_exchange::{public, SecretKey};
+///
+/// let secret_key = SecretKey::from([2u8; 32]);
+/// public(&secret_key);
+/// ```
+pub fn public(secret_key: &SecretKey) -> PublicKey {
+    unsafe {
+        let mut public_key = mem::MaybeUninit::<[u8; 32]>::uninit();
+        ffi::crypto_x25519_public_key(public_key.as_mut_ptr() as *mut u8, secret_key.as_ptr());
+        PublicKey(public_key.assume_init())
+    }
+}
+
+/// Computes the shared secret between a secret key and their public key.
+///
+/// Fails with [`Error::LowOrder`] when the shared secret is all zero, which happens for public
+/// keys of low order. Protocols relying on both parties contributing to the shared secret need
+/// this check.
+///
+/// # Example
+/// ```
+/// use monocypher::key_exchange::{public, shared_secret, SecretKey};
+///
+/// let alice = SecretKey::from([1u8; 32]);
+/// let bob = SecretKey::from([2u8; 32]);
+///
+/// let alice_secret = shared_secret(&alice, &public(&bob)).unwrap();
+/// let bob_secret = shared_secret(&bob, &public(&alice)).unwrap();
+/// assert_eq!(*alice_secret, *bob_secret);
+/// ```
+pub fn shared_secret(
+    secret_key: &SecretKey,
+    their_public_key: &PublicKey,
+) -> Result<SharedSecret, Error> {
+    let mut shared_secret = SharedSecret([0u8; 32]);
+    unsafe {
+        ffi::crypto_x25519(
+            shared_secret.0.as_mut_ptr(),
+            secret_key.as_ptr(),
+            their_public_key.as_ptr(),
+        );
+    }
+    if utils::verify(&shared_secret.0, &[0u8; 32]) {
+        return Err(Error::LowOrder);
+    }
+    Ok(shared_secret)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::key_exchange;
+    use crate::key_exchange::{Error, PublicKey, SecretKey};
+
+    #[test]
+    fn public() {
+        let secret_key = SecretKey::from([2u8; 32]);
+        let public_key = key_exchange::public(&secret_key);
+
+        assert_eq!(
+            *public_key,
+            [
+                206, 141, 58, 209, 204, 182, 51, 236, 123, 112, 193, 120, 20, 165, 199, 110, 205,
+                2, 150, 133, 5, 13, 52, 71, 69, 186, 5, 135, 14, 88, 125, 89
+            ]
+        )
+    }
+
+    // RFC 7748 section 6.1
+    #[test]
+    fn shared_secret() {
+        let key = |hex: &str| <[u8; 32]>::try_from(hex::decode(hex).unwrap()).unwrap();
+        let alice = SecretKey::from(key(
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+        ));
+        let bob = SecretKey::from(key(
+            "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+        ));
+        let bob_public = PublicKey::from(key(
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f",
+        ));
+        assert_eq!(bob.public_key(), bob_public);
+
+        let shared = key("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(*alice.diffie_hellman(&bob_public).unwrap(), shared);
+        assert_eq!(*bob.diffie_hellman(&alice.public_key()).unwrap(), shared);
+    }
+
+    #[test]
+    fn low_order() {
+        let secret_key = SecretKey::from([1u8; 32]);
+        for point in super::LOW_ORDER_POINTS {
+            let public_key = PublicKey::from(point);
+            assert!(public_key.is_low_order());
+            let ret = secret_key.diffie_hellman(&public_key);
+            assert_eq!(ret.err(), Some(Error::LowOrder));
+
+            let mut high_bit = point;
+            high_bit[31] |= 128;
+            assert!(PublicKey::from(high_bit).is_low_order());
+        }
+        assert!(!secret_key.public_key().is_low_order());
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn generate() {
+        let secret_key = SecretKey::generate().unwrap();
+        assert_eq!(secret_key[0] & 7, 0);
+        assert_eq!(secret_key[31] & 192, 64);
+        assert_ne!(*secret_key, *SecretKey::generate().unwrap());
+    }
+}
@@ -0,0 +1,252 @@
+//! Password protected key files
+//!
+//! A key file holds the magic bytes `MCKS` and the format version, followed by the key encrypted
+//! with [`password::lock`] under an Argon2id derived key.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::password;
+use crate::password::argon2::{ArgonAlgorithm, Config};
+use crate::utils;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"MCKS";
+const VERSION: u8 = 2;
+const PREFIX_LEN: usize = MAGIC.len() + 1;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Password(#[from] password::Error),
+    #[error("Key file is malformed or has an unsupported version.")]
+    Malformed,
+    #[error("Wrong password or corrupt key file.")]
+    InvalidMac,
+}
+
+/// A key loaded from a key file or unwrapped with [`keywrap::unwrap`](crate::keywrap::unwrap),
+/// wiped on drop.
+pub struct Secret(pub(crate) Vec<u8>);
+
+impl_zeroize!(Secret);
+
+impl Deref for Secret {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// Encrypts a key under a password and writes it to `path`.
+///
+/// The `config` must use Argon2id. The key file is written to a new sibling file, synced to disk
+/// and then renamed over `path`, so a failed save leaves an existing file intact. On unix, the
+/// file is readable by its owner only, also when `path` existed with looser permissions.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::keystore::{load, save};
+/// use monocypher::password::argon2::{ArgonAlgorithm, Config};
+///
+/// let path = std::env::temp_dir().join("monocypher-keystore-example");
+/// let config = Config {
+///     algorithm: ArgonAlgorithm::Argon2id,
+///     blocks: 19 * 1024,
+///     passes: 2,
+///     lanes: 1,
+/// };
+///
+/// save(&path, &[1u8; 32], "pass".as_bytes(), config).unwrap();
+/// let key = load(&path, "pass".as_bytes()).unwrap();
+/// assert_eq!(&*key, [1u8; 32]);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    key: &[u8],
+    password: &[u8],
+    config: Config,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let sealed = seal(key, password, config)?;
+
+    let temp = temp_path(path)?;
+    if let Err(err) = write_new(&temp, &sealed).and_then(|()| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+// Sibling of the key file that `save` writes before renaming it over the key file.
+fn temp_path(path: &Path) -> io::Result<PathBuf> {
+    let mut name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?
+        .to_os_string();
+    name.push(format!(".{}.tmp", std::process::id()));
+    Ok(path.with_file_name(name))
+}
+
+// Creates the file, failing if it exists, and syncs the data to disk.
+fn write_new(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// Reads a key file and decrypts the key with the password.
+///
+/// Fails without deriving a key if the header is malformed.
+pub fn load<P: AsRef<Path>>(path: P, password: &[u8]) -> Result<Secret, Error> {
+    open(&fs::read(path)?, password)
+}
+
+fn seal(key: &[u8], password: &[u8], config: Config) -> Result<Vec<u8>, Error> {
+    if config.algorithm != ArgonAlgorithm::Argon2id {
+        return Err(password::Error::InvalidParams.into());
+    }
+    let mut sealed = MAGIC.to_vec();
+    sealed.push(VERSION);
+    sealed.extend_from_slice(&password::lock(key, password, config)?);
+    Ok(sealed)
+}
+
+fn open(sealed: &[u8], password: &[u8]) -> Result<Secret, Error> {
+    if sealed.len() < PREFIX_LEN || &sealed[..4] != MAGIC || sealed[4] != VERSION {
+        return Err(Error::Malformed);
+    }
+    match password::unlock(&sealed[PREFIX_LEN..], password) {
+        Ok(key) => Ok(Secret(key)),
+        Err(password::Error::Malformed) => Err(Error::Malformed),
+        Err(password::Error::InvalidMac) => Err(Error::InvalidMac),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: 16,
+            passes: 1,
+            lanes: 1,
+        }
+    }
+
+    #[test]
+    fn save_load() {
+        let path = std::env::temp_dir().join(format!(
+            "monocypher-keystore-save-load-{}",
+            std::process::id()
+        ));
+        save(&path, &[2u8; 64], "password".as_bytes(), config()).unwrap();
+        assert!(!temp_path(&path).unwrap().exists());
+        let key = load(&path, "password".as_bytes()).unwrap();
+        let wrong = load(&path, "passwort".as_bytes());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(&*key, [2u8; 64]);
+        assert!(matches!(wrong, Err(Error::InvalidMac)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!(
+            "monocypher-keystore-permissions-{}",
+            std::process::id()
+        ));
+        fs::write(&path, "public").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        save(&path, &[2u8; 32], "password".as_bytes(), config()).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn replace() {
+        let path = std::env::temp_dir().join(format!(
+            "monocypher-keystore-replace-{}",
+            std::process::id()
+        ));
+        save(&path, &[2u8; 32], "password".as_bytes(), config()).unwrap();
+        save(&path, &[3u8; 32], "password".as_bytes(), config()).unwrap();
+        let key = load(&path, "password".as_bytes()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(&*key, [3u8; 32]);
+    }
+
+    #[test]
+    fn envelope() {
+        let sealed = seal(&[2u8; 32], "password".as_bytes(), config()).unwrap();
+        let key = password::unlock(&sealed[PREFIX_LEN..], "password".as_bytes()).unwrap();
+        assert_eq!(key, [2u8; 32]);
+    }
+
+    #[test]
+    fn header_authenticated() {
+        let sealed = seal(&[2u8; 32], "password".as_bytes(), config()).unwrap();
+
+        // Passes, salt and nonce.
+        for i in [PREFIX_LEN + 6, PREFIX_LEN + 14, sealed.len() - 33] {
+            let mut forged = sealed.clone();
+            forged[i] ^= 1;
+            let ret = open(&forged, "password".as_bytes());
+            assert!(matches!(ret, Err(Error::InvalidMac)));
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let sealed = seal(&[2u8; 32], "password".as_bytes(), config()).unwrap();
+        for i in [0, 4] {
+            let mut forged = sealed.clone();
+            forged[i] ^= 1;
+            let ret = open(&forged, "password".as_bytes());
+            assert!(matches!(ret, Err(Error::Malformed)));
+        }
+        let ret = open(&sealed[..sealed.len() - 33], "password".as_bytes());
+        assert!(matches!(ret, Err(Error::Malformed)));
+        let ret = open(&sealed[..PREFIX_LEN - 1], "password".as_bytes());
+        assert!(matches!(ret, Err(Error::Malformed)));
+    }
+
+    #[test]
+    fn argon2id_only() {
+        let config = Config {
+            algorithm: ArgonAlgorithm::Argon2i,
+            ..config()
+        };
+        let ret = seal(&[2u8; 32], "password".as_bytes(), config);
+        assert!(matches!(
+            ret,
+            Err(Error::Password(password::Error::InvalidParams))
+        ));
+    }
+}
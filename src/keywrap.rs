@@ -0,0 +1,118 @@
+//! Key wrapping
+//!
+//! Encrypts data encryption keys under a key encryption key with XChaCha20-Poly1305, a random
+//! nonce and a fixed additional data string. A wrapped key consists of the nonce (24 bytes), the
+//! MAC (16 bytes) and the encrypted key.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::aead::{lock, unlock, Key};
+use crate::keystore::Secret;
+use thiserror::Error;
+
+const AD: &[u8] = b"monocypher-rs keywrap v1";
+const OVERHEAD: usize = 24 + 16;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Wrapped key is too short.")]
+    Malformed,
+    #[error("Wrapped key is corrupt.")]
+    InvalidMac,
+}
+
+impl From<crate::random::Error> for Error {
+    fn from(_: crate::random::Error) -> Self {
+        Error::Random
+    }
+}
+
+/// Wraps a key under the key encryption key.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::Key;
+/// use monocypher::keywrap::{unwrap, wrap};
+///
+/// let kek = Key::from([1u8; 32]);
+/// let wrapped = wrap(&kek, &[2u8; 32]).unwrap();
+/// let key = unwrap(&kek, &wrapped).unwrap();
+/// assert_eq!(&*key, [2u8; 32]);
+/// ```
+pub fn wrap(kek: &Key, key: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut nonce = [0u8; 24];
+    crate::random::fill(&mut nonce)?;
+
+    let (cipher_text, mac) = lock::aead(key, **kek, nonce, AD);
+    let mut wrapped = Vec::with_capacity(OVERHEAD + cipher_text.len());
+    wrapped.extend_from_slice(&nonce);
+    wrapped.extend_from_slice(&mac);
+    wrapped.extend_from_slice(&cipher_text);
+    Ok(wrapped)
+}
+
+/// Unwraps a key that was wrapped under the key encryption key.
+///
+/// The key is wiped when the returned [`Secret`] is dropped.
+pub fn unwrap(kek: &Key, wrapped: &[u8]) -> Result<Secret, Error> {
+    if wrapped.len() < OVERHEAD {
+        return Err(Error::Malformed);
+    }
+    let mut nonce = [0u8; 24];
+    nonce.copy_from_slice(&wrapped[..24]);
+    let mut mac = [0u8; 16];
+    mac.copy_from_slice(&wrapped[24..OVERHEAD]);
+
+    unlock::aead(&wrapped[OVERHEAD..], **kek, nonce, mac, AD)
+        .map(Secret)
+        .map_err(|_| Error::InvalidMac)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wrap_unwrap() {
+        let kek = Key::from([1u8; 32]);
+        let wrapped = wrap(&kek, &[2u8; 32]).unwrap();
+        assert_eq!(wrapped.len(), OVERHEAD + 32);
+
+        assert_eq!(&*unwrap(&kek, &wrapped).unwrap(), [2u8; 32]);
+    }
+
+    #[test]
+    fn random_nonce() {
+        let kek = Key::from([1u8; 32]);
+        assert_ne!(wrap(&kek, &[2u8; 32]), wrap(&kek, &[2u8; 32]));
+    }
+
+    #[test]
+    fn wrong_kek() {
+        let wrapped = wrap(&Key::from([1u8; 32]), &[2u8; 32]).unwrap();
+        assert_eq!(
+            unwrap(&Key::from([3u8; 32]), &wrapped).err(),
+            Some(Error::InvalidMac)
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let kek = Key::from([1u8; 32]);
+        assert_eq!(
+            unwrap(&kek, &[0u8; OVERHEAD - 1]).err(),
+            Some(Error::Malformed)
+        );
+    }
+
+    #[test]
+    fn corrupt_nonce() {
+        let kek = Key::from([1u8; 32]);
+        let mut wrapped = wrap(&kek, &[2u8; 32]).unwrap();
+        wrapped[0] ^= 1;
+        assert_eq!(unwrap(&kek, &wrapped).err(), Some(Error::InvalidMac));
+    }
+}
@@ -9,22 +9,159 @@ use derive_more::From;
 use std::ops::{Deref, DerefMut};
 use thiserror::Error;
 
+// Byte and hex conversions of the key and signature types. The optional `$check` validates the
+// decoded bytes, the value is wiped on drop if it fails.
+macro_rules! impl_bytes {
+    ($name:ident, $len:expr) => {
+        impl_bytes!($name, $len, |_| Ok(()));
+    };
+    ($name:ident, $len:expr, $check:expr) => {
+        impl $name {
+            /// Returns the raw bytes.
+            pub fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+
+            /// Copies the raw bytes.
+            pub fn to_bytes(&self) -> [u8; $len] {
+                self.0
+            }
+
+            /// Encodes the bytes as lower case hex.
+            pub fn to_hex(&self) -> String {
+                hex::encode(self.0)
+            }
+
+            /// Decodes hex of exactly the right length.
+            pub fn from_hex(encoded: &str) -> Result<Self, $crate::Error> {
+                let mut bytes = hex::decode(encoded).map_err(|_| $crate::Error::Encoding)?;
+                let ret = Self::try_from(&bytes[..]);
+                $crate::utils::wipe(&mut bytes);
+                ret
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = $crate::Error;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                let value = bytes
+                    .try_into()
+                    .map(Self)
+                    .map_err(|_| $crate::Error::Encoding)?;
+                let check: fn(&[u8; $len]) -> Result<(), $crate::Error> = $check;
+                check(&value.0)?;
+                Ok(value)
+            }
+        }
+    };
+}
+
+// Constant time equality of the key and signature types, comparing them with `==` does not
+// leak where they differ.
+macro_rules! impl_ct_eq {
+    ($name:ident) => {
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::utils::verify(&self.0, &other.0)
+            }
+        }
+
+        impl Eq for $name {}
+
+        /// Requires the `subtle` feature.
+        #[cfg(feature = "subtle")]
+        impl subtle::ConstantTimeEq for $name {
+            fn ct_eq(&self, other: &Self) -> subtle::Choice {
+                self.0[..].ct_eq(&other.0[..])
+            }
+        }
+    };
+}
+
+// `zeroize` integration of the secret types, wiping the given fields (the newtype field by
+// default) with `crypto_wipe`. The types wipe themselves on drop already.
+macro_rules! impl_zeroize {
+    ($name:ident) => {
+        impl_zeroize!($name, 0);
+    };
+    ($name:ident, $($field:tt),+) => {
+        /// Requires the `zeroize` feature.
+        #[cfg(feature = "zeroize")]
+        impl zeroize::Zeroize for $name {
+            fn zeroize(&mut self) {
+                $($crate::utils::wipe(&mut self.$field);)+
+            }
+        }
+
+        #[cfg(feature = "zeroize")]
+        impl zeroize::ZeroizeOnDrop for $name {}
+    };
+}
+
+pub mod advanced;
 pub mod aead;
+pub mod boxy;
+pub mod chacha20;
+pub mod fingerprint;
 pub mod hashing;
 pub mod password;
 pub mod pubkey;
+pub mod signing;
 pub mod utils;
 
+pub mod kdf;
 pub mod key_exchange;
+pub mod mac;
+pub mod multisig;
 pub mod poly1305;
 
+#[cfg(feature = "getrandom")]
+pub mod envelope;
+#[cfg(feature = "getrandom")]
+pub mod handshake;
+#[cfg(feature = "getrandom")]
+pub mod keystore;
+#[cfg(feature = "getrandom")]
+pub mod keywrap;
+#[cfg(feature = "getrandom")]
+pub mod net;
+#[cfg(feature = "getrandom")]
+pub mod oprf;
+#[cfg(feature = "getrandom")]
+pub mod pake;
+#[cfg(feature = "getrandom")]
+pub mod random;
+#[cfg(feature = "getrandom")]
+pub mod ratchet;
+#[cfg(feature = "getrandom")]
+pub mod sealed;
+
 #[cfg(feature = "ed25519")]
 pub mod ed25519;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+#[cfg(feature = "bip39")]
+pub mod mnemonic;
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Signature check failed!")]
     Signature,
+    #[error("Signature context must be 1 to 255 bytes long.")]
+    Context,
+    #[error("Key or signature has the wrong length or is not hex encoded.")]
+    Encoding,
+    #[error("Public half of the private key does not match its seed.")]
+    KeyMismatch,
+    #[error("Failed to read the message: {0}")]
+    Io(#[from] std::io::Error),
 }
 #[derive(Debug)]
 pub struct KeyPair<S, P>
@@ -53,6 +190,9 @@ where
 #[derive(Debug, From)]
 pub struct Signature([u8; 64]);
 
+impl_bytes!(Signature, 64);
+impl_ct_eq!(Signature);
+
 impl Deref for Signature {
     type Target = [u8; 64];
 
@@ -64,6 +204,30 @@ impl Deref for Signature {
 #[derive(Debug, From)]
 pub struct Seed([u8; 32]);
 
+impl_zeroize!(Seed);
+
+impl Seed {
+    /// Generates a seed with the operating system's random number generator.
+    ///
+    /// Requires the `getrandom` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::pubkey::{PrivateKey, PublicKey};
+    /// use monocypher::{KeyPair, PubPrivKey, Seed};
+    ///
+    /// let keypair: KeyPair<PrivateKey, PublicKey> =
+    ///     KeyPair::generate_key_pair(Seed::random().unwrap());
+    /// ```
+    #[cfg(feature = "getrandom")]
+    pub fn random() -> Result<Seed, random::Error> {
+        let mut seed = Seed([0u8; 32]);
+        random::fill(&mut seed.0)?;
+        Ok(seed)
+    }
+}
+
 impl Deref for Seed {
     type Target = [u8; 32];
 
@@ -78,6 +242,12 @@ impl DerefMut for Seed {
     }
 }
 
+impl Drop for Seed {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
 pub trait PubPrivKey {
     fn generate_key_pair(seed: Seed) -> Self;
 }
@@ -0,0 +1,135 @@
+//! HMAC-SHA-512 (RFC 2104)
+//!
+//! Requires the `ed25519` feature, which compiles Monocypher's optional sources.
+//!
+//! [Official documentation](https://monocypher.org/manual/sha-512)
+
+use crate::mac::Tag;
+use monocypher_sys as ffi;
+use std::mem;
+use std::os::raw::c_void;
+
+/// Authenticates the message with the given key.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::mac::hmac_sha512;
+///
+/// let mac = hmac_sha512::easy("key".as_bytes(), "message".as_bytes());
+/// ```
+pub fn easy(key: &[u8], message: &[u8]) -> Tag<64> {
+    unsafe {
+        let mut mac = mem::MaybeUninit::<[u8; 64]>::uninit();
+        ffi::crypto_sha512_hmac(
+            mac.as_mut_ptr() as *mut u8,
+            key.as_ptr(),
+            key.len(),
+            message.as_ptr(),
+            message.len(),
+        );
+        Tag::from(mac.assume_init())
+    }
+}
+
+/// Checks a MAC produced by [`easy`] or [`Context`] in constant time.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::mac::hmac_sha512;
+///
+/// let mac = hmac_sha512::easy("key".as_bytes(), "message".as_bytes());
+/// assert!(hmac_sha512::verify("key".as_bytes(), "message".as_bytes(), &mac));
+/// ```
+pub fn verify(key: &[u8], message: &[u8], mac: &Tag<64>) -> bool {
+    easy(key, message) == *mac
+}
+
+/// Incremental HMAC-SHA-512, the key material is wiped when dropped.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::mac::hmac_sha512::Context;
+///
+/// let mut ctx = Context::new("key".as_bytes());
+/// ctx.update("mess".as_bytes());
+/// ctx.update("age".as_bytes());
+/// let mac = ctx.finalize();
+/// ```
+pub struct Context(ffi::crypto_sha512_hmac_ctx);
+
+impl Context {
+    /// Initializes a new context with the given key.
+    #[inline]
+    pub fn new(key: &[u8]) -> Context {
+        unsafe {
+            let mut ctx = mem::MaybeUninit::<ffi::crypto_sha512_hmac_ctx>::uninit();
+            ffi::crypto_sha512_hmac_init(ctx.as_mut_ptr(), key.as_ptr(), key.len());
+            Context(ctx.assume_init())
+        }
+    }
+
+    /// Authenticates the message chunk by chunk.
+    #[inline]
+    pub fn update(&mut self, message: &[u8]) {
+        unsafe {
+            ffi::crypto_sha512_hmac_update(&mut self.0, message.as_ptr(), message.len());
+        }
+    }
+
+    /// Produces the message authentication code.
+    #[inline]
+    pub fn finalize(&mut self) -> Tag<64> {
+        unsafe {
+            let mut mac = mem::MaybeUninit::<[u8; 64]>::uninit();
+            ffi::crypto_sha512_hmac_final(&mut self.0, mac.as_mut_ptr() as *mut u8);
+            Tag::from(mac.assume_init())
+        }
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::crypto_wipe(
+                &mut self.0 as *mut ffi::crypto_sha512_hmac_ctx as *mut c_void,
+                mem::size_of::<ffi::crypto_sha512_hmac_ctx>(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // RFC 4231 test case 2
+    const MAC: &str = "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+                       9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737";
+
+    #[test]
+    fn rfc4231() {
+        let mac = easy("Jefe".as_bytes(), "what do ya want for nothing?".as_bytes());
+        assert_eq!(hex::encode(mac), MAC);
+    }
+
+    #[test]
+    fn incremental() {
+        let mut ctx = Context::new("Jefe".as_bytes());
+        ctx.update("what do ya want ".as_bytes());
+        ctx.update("for nothing?".as_bytes());
+        assert_eq!(hex::encode(ctx.finalize()), MAC);
+    }
+
+    #[test]
+    fn verify_forged() {
+        let mac = easy("Jefe".as_bytes(), "message".as_bytes());
+        assert!(verify("Jefe".as_bytes(), "message".as_bytes(), &mac));
+        let mut forged = *mac;
+        forged[0] ^= 1;
+        let mac = Tag::from(forged);
+        assert!(!verify("Jefe".as_bytes(), "message".as_bytes(), &mac));
+    }
+}
@@ -0,0 +1,126 @@
+//! Message authentication codes
+
+use crate::utils;
+use std::fmt;
+use std::ops::Deref;
+
+#[cfg(feature = "ed25519")]
+pub mod hmac_sha512;
+
+/// Compares two tags in constant time, see [`utils::verify`].
+///
+/// Tags must be 16, 32 or 64 bytes long, anything else or tags of different lengths return
+/// false.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::mac::verify;
+///
+/// assert!(verify(&[1u8; 32], &[1u8; 32]));
+/// assert!(!verify(&[1u8; 32], &[2u8; 32]));
+/// ```
+pub fn verify(tag_a: &[u8], tag_b: &[u8]) -> bool {
+    utils::verify(tag_a, tag_b)
+}
+
+/// An authentication tag of `N` bytes that compares in constant time.
+///
+/// `N` is one of the lengths supported by [`verify`], 16, 32 or 64. Creating a tag of any other
+/// length fails to compile:
+///
+/// ```compile_fail
+/// use monocypher::mac::Tag;
+///
+/// let tag = Tag::from([0u8; 8]);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Tag<const N: usize>([u8; N]);
+
+impl<const N: usize> Tag<N> {
+    const SUPPORTED_LEN: () = assert!(
+        N == 16 || N == 32 || N == 64,
+        "tags are 16, 32 or 64 bytes long"
+    );
+}
+
+impl<const N: usize> From<[u8; N]> for Tag<N> {
+    fn from(tag: [u8; N]) -> Self {
+        let () = Self::SUPPORTED_LEN;
+        Tag(tag)
+    }
+}
+
+impl<const N: usize> Deref for Tag<N> {
+    type Target = [u8; N];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Tag<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> fmt::Debug for Tag<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Tag").field(&self.0).finish()
+    }
+}
+
+impl<const N: usize> PartialEq for Tag<N> {
+    fn eq(&self, other: &Self) -> bool {
+        utils::verify(&self.0, &other.0)
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for Tag<N> {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        utils::verify(&self.0, other)
+    }
+}
+
+impl<const N: usize> Eq for Tag<N> {}
+
+/// Requires the `subtle` feature.
+#[cfg(feature = "subtle")]
+impl<const N: usize> subtle::ConstantTimeEq for Tag<N> {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn verify_lengths() {
+        for len in [16, 32, 64] {
+            let a = vec![1u8; len];
+            let mut b = a.clone();
+            assert!(verify(&a, &b));
+            b[len - 1] ^= 1;
+            assert!(!verify(&a, &b));
+        }
+        assert!(!verify(&[1u8; 8], &[1u8; 8]));
+        assert!(!verify(&[1u8; 16], &[1u8; 32]));
+    }
+
+    #[test]
+    fn tag_eq() {
+        let tag = Tag::from([1u8; 16]);
+        assert_eq!(tag, Tag::from([1u8; 16]));
+        assert_eq!(tag, [1u8; 16]);
+        assert_ne!(tag, Tag::from([2u8; 16]));
+
+        for tag in [Tag::from([3u8; 32]), Tag::from([4u8; 32])] {
+            assert_eq!(tag, tag);
+        }
+        let tag = Tag::from([5u8; 64]);
+        assert_eq!(tag, tag);
+    }
+}
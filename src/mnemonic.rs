@@ -0,0 +1,123 @@
+//! BIP39 mnemonics for backing up seeds
+//!
+//! A mnemonic encodes random entropy as English words with a checksum, so it can be written
+//! down and typed back in. [`Seed::from_mnemonic`] derives a key pair seed from a mnemonic and
+//! an optional passphrase as BIP39 does: PBKDF2-HMAC-SHA512 with 2048 iterations. The seed is
+//! the first half of the 64 byte BIP39 seed.
+//!
+//! Requires the `bip39` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use monocypher::{mnemonic, pubkey};
+//! use monocypher::{KeyPair, PubPrivKey, Seed};
+//!
+//! let phrase = mnemonic::from_entropy(&[7u8; 32]);
+//! assert_eq!(phrase.split(' ').count(), 24);
+//!
+//! let seed = Seed::from_mnemonic(&phrase, "passphrase").unwrap();
+//! let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> = KeyPair::generate_key_pair(seed);
+//! ```
+
+use crate::{utils, Seed};
+use bip39::Mnemonic;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Mnemonic has unknown words, the wrong length or a wrong checksum.")]
+    Invalid,
+}
+
+#[cfg(feature = "getrandom")]
+impl From<crate::random::Error> for Error {
+    fn from(_: crate::random::Error) -> Self {
+        Error::Random
+    }
+}
+
+impl Seed {
+    /// Derives a seed from a BIP39 mnemonic and a passphrase, which may be empty.
+    ///
+    /// Mnemonics of 12 to 24 words are accepted. Different passphrases give unrelated seeds.
+    pub fn from_mnemonic(phrase: &str, passphrase: &str) -> Result<Seed, Error> {
+        let mnemonic = Mnemonic::parse(phrase).map_err(|_| Error::Invalid)?;
+        let mut bip39_seed = mnemonic.to_seed(passphrase);
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&bip39_seed[..32]);
+        utils::wipe(&mut bip39_seed);
+        Ok(Seed::from(seed))
+    }
+}
+
+/// Encodes 32 bytes of entropy as a mnemonic of 24 words.
+pub fn from_entropy(entropy: &[u8; 32]) -> String {
+    Mnemonic::from_entropy(entropy).unwrap().to_string()
+}
+
+/// Generates a mnemonic of 24 words from 32 random bytes.
+///
+/// Requires the `getrandom` feature.
+#[cfg(feature = "getrandom")]
+pub fn generate() -> Result<String, Error> {
+    let mut entropy = [0u8; 32];
+    crate::random::fill(&mut entropy)?;
+    let ret = from_entropy(&entropy);
+    utils::wipe(&mut entropy);
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The vectors of the BIP39 reference implementation, all with the passphrase "TREZOR".
+    #[test]
+    fn vectors() {
+        let phrase = from_entropy(&[0u8; 32]);
+        assert_eq!(phrase, format!("{}art", "abandon ".repeat(23)));
+        let seed = Seed::from_mnemonic(&phrase, "TREZOR").unwrap();
+        assert_eq!(
+            hex::encode(*seed),
+            "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd30971"
+        );
+
+        let phrase = format!("{}about", "abandon ".repeat(11));
+        let seed = Seed::from_mnemonic(&phrase, "TREZOR").unwrap();
+        assert_eq!(
+            hex::encode(*seed),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553"
+        );
+    }
+
+    #[test]
+    fn passphrase() {
+        let phrase = from_entropy(&[7u8; 32]);
+        let seed = Seed::from_mnemonic(&phrase, "").unwrap();
+        assert_ne!(*seed, *Seed::from_mnemonic(&phrase, "other").unwrap());
+    }
+
+    #[test]
+    fn invalid() {
+        let phrase = format!("{}abandon", "abandon ".repeat(23));
+        assert_eq!(
+            Seed::from_mnemonic(&phrase, "").unwrap_err(),
+            Error::Invalid
+        );
+        assert_eq!(
+            Seed::from_mnemonic("not a mnemonic", "").unwrap_err(),
+            Error::Invalid
+        );
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn generate() {
+        let phrase = super::generate().unwrap();
+        assert!(Seed::from_mnemonic(&phrase, "").is_ok());
+        assert_ne!(phrase, super::generate().unwrap());
+    }
+}
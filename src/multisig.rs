@@ -0,0 +1,247 @@
+//! Threshold checks of signatures collected from several signers
+//!
+//! Every signer makes a normal [`pubkey`](crate::pubkey) signature of the same message, which
+//! are collected in a [`Bundle`]. A verifier then checks that at least `threshold` of the keys
+//! it trusts signed the message. There is no aggregation, the bundle simply stores the public
+//! key and signature of every signer:
+//!
+//! | Size      | Content                                       |
+//! |-----------|-----------------------------------------------|
+//! | 1         | Version, currently 1                          |
+//! | 4         | Number of signatures `n`, little endian       |
+//! | `n` * 96  | Public key (32) and signature (64) per signer |
+//!
+//! # Example
+//!
+//! ```
+//! use monocypher::multisig::Bundle;
+//! use monocypher::pubkey;
+//! use monocypher::{KeyPair, PubPrivKey, Seed};
+//!
+//! let signers: Vec<KeyPair<pubkey::PrivateKey, pubkey::PublicKey>> = (1..=3)
+//!     .map(|i| KeyPair::generate_key_pair(Seed::from([i; 32])))
+//!     .collect();
+//! let trusted: Vec<_> = signers.iter().map(|s| s.private_key.public_key()).collect();
+//!
+//! let mut bundle = Bundle::new();
+//! bundle.sign("release".as_bytes(), &signers[0].private_key);
+//! bundle.sign("release".as_bytes(), &signers[2].private_key);
+//!
+//! let bundle = Bundle::from_bytes(&bundle.to_bytes()).unwrap();
+//! bundle.verify("release".as_bytes(), &trusted, 2).unwrap();
+//! assert!(bundle.verify("release".as_bytes(), &trusted, 3).is_err());
+//! ```
+
+use crate::pubkey::{PrivateKey, PublicKey};
+use crate::{PrivKey, PubKey, Signature};
+use thiserror::Error;
+
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 1 + 4;
+const ENTRY_SIZE: usize = 32 + 64;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Bundle is malformed.")]
+    Malformed,
+    #[error("Threshold must be between 1 and the number of trusted keys, which must be distinct.")]
+    InvalidThreshold,
+    #[error("Only {valid} of the required {threshold} signatures are valid.")]
+    Threshold { valid: usize, threshold: usize },
+}
+
+/// Signatures of one message by several signers.
+#[derive(Debug, Default)]
+pub struct Bundle {
+    entries: Vec<(PublicKey, Signature)>,
+}
+
+impl Bundle {
+    pub fn new() -> Bundle {
+        Bundle::default()
+    }
+
+    /// Signs the message and adds the signature.
+    pub fn sign(&mut self, message: &[u8], private_key: &PrivateKey) {
+        self.add(private_key.public_key(), private_key.sign(message));
+    }
+
+    /// Adds a signature made elsewhere.
+    ///
+    /// The signature is not checked here, invalid ones are ignored by [`verify`](Self::verify).
+    pub fn add(&mut self, public_key: PublicKey, signature: Signature) {
+        self.entries.push((public_key, signature));
+    }
+
+    /// Returns the number of signatures.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Checks that at least `threshold` of the `trusted` keys signed the message.
+    ///
+    /// Signatures by other keys and invalid signatures are ignored, every trusted key counts at
+    /// most once. Returns the number of trusted keys with a valid signature.
+    ///
+    /// Fails with [`Error::InvalidThreshold`] if `trusted` lists a key more than once, it would
+    /// otherwise count a single signer several times.
+    pub fn verify(
+        &self,
+        message: &[u8],
+        trusted: &[PublicKey],
+        threshold: usize,
+    ) -> Result<usize, Error> {
+        let duplicates = trusted
+            .iter()
+            .enumerate()
+            .any(|(i, key)| trusted[..i].contains(key));
+        if threshold == 0 || threshold > trusted.len() || duplicates {
+            return Err(Error::InvalidThreshold);
+        }
+        let valid = trusted
+            .iter()
+            .filter(|key| {
+                self.entries.iter().any(|(public_key, signature)| {
+                    public_key == *key
+                        && public_key
+                            .check(Signature::from(**signature), message)
+                            .is_ok()
+                })
+            })
+            .count();
+        if valid < threshold {
+            return Err(Error::Threshold { valid, threshold });
+        }
+        Ok(valid)
+    }
+
+    /// Encodes the bundle.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.entries.len() * ENTRY_SIZE);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for (public_key, signature) in &self.entries {
+            bytes.extend_from_slice(public_key.as_bytes());
+            bytes.extend_from_slice(signature.as_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a bundle.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Bundle, Error> {
+        if bytes.len() < HEADER_SIZE || bytes[0] != VERSION {
+            return Err(Error::Malformed);
+        }
+        let mut count = [0u8; 4];
+        count.copy_from_slice(&bytes[1..HEADER_SIZE]);
+        let count = u32::from_le_bytes(count) as usize;
+        let entries = &bytes[HEADER_SIZE..];
+        if count.checked_mul(ENTRY_SIZE) != Some(entries.len()) {
+            return Err(Error::Malformed);
+        }
+
+        let entries = entries
+            .chunks(ENTRY_SIZE)
+            .map(|entry| {
+                let (public_key, signature) = entry.split_at(32);
+                (
+                    PublicKey::try_from(public_key).unwrap(),
+                    Signature::try_from(signature).unwrap(),
+                )
+            })
+            .collect();
+        Ok(Bundle { entries })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyPair, PubPrivKey, Seed};
+
+    fn private_key(seed: u8) -> PrivateKey {
+        let keypair: KeyPair<PrivateKey, PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([seed; 32]));
+        keypair.private_key
+    }
+
+    fn trusted() -> Vec<PublicKey> {
+        (1..=3).map(|seed| private_key(seed).public_key()).collect()
+    }
+
+    #[test]
+    fn threshold() {
+        let mut bundle = Bundle::new();
+        bundle.sign(b"release", &private_key(1));
+        bundle.sign(b"release", &private_key(3));
+        assert_eq!(bundle.verify(b"release", &trusted(), 2), Ok(2));
+        assert_eq!(
+            bundle.verify(b"release", &trusted(), 3),
+            Err(Error::Threshold {
+                valid: 2,
+                threshold: 3
+            })
+        );
+        assert!(bundle.verify(b"other", &trusted(), 1).is_err());
+    }
+
+    #[test]
+    fn counts_trusted_keys_once() {
+        let mut bundle = Bundle::new();
+        bundle.sign(b"release", &private_key(1));
+        bundle.sign(b"release", &private_key(1));
+        bundle.sign(b"release", &private_key(4));
+        let forged = private_key(1).sign(b"other");
+        bundle.add(private_key(2).public_key(), forged);
+        assert_eq!(bundle.verify(b"release", &trusted(), 1), Ok(1));
+        assert!(bundle.verify(b"release", &trusted(), 2).is_err());
+    }
+
+    #[test]
+    fn invalid_threshold() {
+        let bundle = Bundle::new();
+        assert_eq!(
+            bundle.verify(b"release", &trusted(), 0),
+            Err(Error::InvalidThreshold)
+        );
+        assert_eq!(
+            bundle.verify(b"release", &trusted(), 4),
+            Err(Error::InvalidThreshold)
+        );
+    }
+
+    #[test]
+    fn duplicate_trusted_keys() {
+        let mut bundle = Bundle::new();
+        bundle.sign(b"release", &private_key(1));
+        let trusted: Vec<_> = (0..3).map(|_| private_key(1).public_key()).collect();
+        assert_eq!(
+            bundle.verify(b"release", &trusted, 3),
+            Err(Error::InvalidThreshold)
+        );
+        assert_eq!(bundle.verify(b"release", &trusted[..1], 1), Ok(1));
+    }
+
+    #[test]
+    fn bytes() {
+        let mut bundle = Bundle::new();
+        bundle.sign(b"release", &private_key(1));
+        bundle.sign(b"release", &private_key(2));
+        let bytes = bundle.to_bytes();
+        assert_eq!(bytes.len(), HEADER_SIZE + 2 * ENTRY_SIZE);
+
+        let decoded = Bundle::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded.verify(b"release", &trusted(), 2), Ok(2));
+
+        assert!(Bundle::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Bundle::from_bytes(&[]).is_err());
+        let mut count = bytes.clone();
+        count[1] = 3;
+        assert_eq!(Bundle::from_bytes(&count).unwrap_err(), Error::Malformed);
+    }
+}
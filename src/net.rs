@@ -0,0 +1,299 @@
+//! Encrypted framed channels
+//!
+//! A [`FramedChannel`] sends every message as a frame consisting of the cipher text length (4
+//! bytes, little endian, authenticated as additional data), the MAC (16 bytes) and the cipher
+//! text. The nonce of a frame is its 64 bit little endian sequence number, so dropped, reordered,
+//! replayed or modified frames fail to authenticate.
+//!
+//! When the channel is opened both sides send a random 32 byte salt. Each direction uses its own
+//! key, derived with keyed BLAKE2b from the shared key and both salts. Reusing the shared key for
+//! several connections therefore never reuses a key and nonce pair, as long as either side's
+//! random number generator works.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::aead::{lock, unlock};
+use crate::hashing::blake2b::Blake2b256;
+use crate::{random, utils};
+use std::io::{self, Read, Write};
+
+/// Maximum size of a single message.
+pub const MAX_MESSAGE_SIZE: usize = 1 << 24;
+
+const HEADER_SIZE: usize = 4 + 16;
+const SALT_SIZE: usize = 32;
+
+/// An encrypted, authenticated message channel over a byte stream such as a `TcpStream`.
+///
+/// Opening a channel exchanges the salts, so it blocks until the peer has sent its salt.
+///
+/// # Example
+///
+/// ```no_run
+/// use monocypher::net::FramedChannel;
+/// use std::net::TcpStream;
+///
+/// let stream = TcpStream::connect("127.0.0.1:4433").unwrap();
+/// let mut channel = FramedChannel::client(stream, [137u8; 32]).unwrap();
+/// channel.send("hello".as_bytes()).unwrap();
+/// let reply = channel.recv().unwrap();
+/// ```
+pub struct FramedChannel<S> {
+    stream: S,
+    send_key: [u8; 32],
+    recv_key: [u8; 32],
+    send_counter: u64,
+    recv_counter: u64,
+}
+
+impl<S: Read + Write> FramedChannel<S> {
+    /// Opens the client side of a channel.
+    pub fn client(stream: S, key: [u8; 32]) -> io::Result<FramedChannel<S>> {
+        let salt = random::bytes().map_err(io::Error::other)?;
+        Self::open(stream, &key, salt, true)
+    }
+
+    /// Opens the server side of a channel.
+    pub fn server(stream: S, key: [u8; 32]) -> io::Result<FramedChannel<S>> {
+        let salt = random::bytes().map_err(io::Error::other)?;
+        Self::open(stream, &key, salt, false)
+    }
+
+    // Exchanges the salts and derives the keys of both directions.
+    fn open(
+        mut stream: S,
+        key: &[u8; 32],
+        salt: [u8; SALT_SIZE],
+        client: bool,
+    ) -> io::Result<FramedChannel<S>> {
+        stream.write_all(&salt)?;
+        stream.flush()?;
+        let mut peer_salt = [0u8; SALT_SIZE];
+        stream.read_exact(&mut peer_salt)?;
+
+        let salts = if client {
+            [&salt, &peer_salt]
+        } else {
+            [&peer_salt, &salt]
+        };
+        let client_key = direction_key(key, b"client to server", salts);
+        let server_key = direction_key(key, b"server to client", salts);
+        let (send_key, recv_key) = if client {
+            (client_key, server_key)
+        } else {
+            (server_key, client_key)
+        };
+
+        Ok(FramedChannel {
+            stream,
+            send_key,
+            recv_key,
+            send_counter: 0,
+            recv_counter: 0,
+        })
+    }
+
+    /// Encrypts and sends a message.
+    pub fn send(&mut self, message: &[u8]) -> io::Result<()> {
+        if message.len() > MAX_MESSAGE_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Message is too large.",
+            ));
+        }
+        let nonce = next_nonce(&mut self.send_counter)?;
+        let len = (message.len() as u32).to_le_bytes();
+        let (cipher_text, mac) = lock::aead(message, self.send_key, nonce, &len);
+
+        let mut frame = Vec::with_capacity(HEADER_SIZE + cipher_text.len());
+        frame.extend_from_slice(&len);
+        frame.extend_from_slice(&mac);
+        frame.extend_from_slice(&cipher_text);
+        self.stream.write_all(&frame)?;
+        self.stream.flush()
+    }
+
+    /// Receives and decrypts the next message.
+    pub fn recv(&mut self) -> io::Result<Vec<u8>> {
+        let mut header = [0u8; HEADER_SIZE];
+        self.stream.read_exact(&mut header)?;
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&header[..4]);
+        let mut mac = [0u8; 16];
+        mac.copy_from_slice(&header[4..]);
+
+        let body_len = u32::from_le_bytes(len) as usize;
+        if body_len > MAX_MESSAGE_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Frame is too large.",
+            ));
+        }
+        let mut cipher_text = vec![0u8; body_len];
+        self.stream.read_exact(&mut cipher_text)?;
+
+        let mut counter = self.recv_counter;
+        let nonce = next_nonce(&mut counter)?;
+        let message = unlock::aead(&cipher_text, self.recv_key, nonce, mac, &len)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.recv_counter = counter;
+        Ok(message)
+    }
+
+    /// Returns the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+}
+
+impl<S> Drop for FramedChannel<S> {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.send_key);
+        utils::wipe(&mut self.recv_key);
+    }
+}
+
+// Derives the key of one direction of the channel from the shared key, the direction label and
+// the client and server salts.
+fn direction_key(key: &[u8; 32], label: &[u8], salts: [&[u8; SALT_SIZE]; 2]) -> [u8; 32] {
+    let mut ctx = Blake2b256::with_key(key);
+    ctx.update(label);
+    ctx.update(salts[0]);
+    ctx.update(salts[1]);
+    ctx.finalize()
+}
+
+// Returns the nonce for the current counter value and advances the counter.
+fn next_nonce(counter: &mut u64) -> io::Result<[u8; 24]> {
+    let mut nonce = [0u8; 24];
+    nonce[..8].copy_from_slice(&counter.to_le_bytes());
+    *counter = counter
+        .checked_add(1)
+        .ok_or_else(|| io::Error::other("Nonce counter exhausted."))?;
+    Ok(nonce)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    const CLIENT_SALT: [u8; SALT_SIZE] = [2u8; SALT_SIZE];
+    const SERVER_SALT: [u8; SALT_SIZE] = [3u8; SALT_SIZE];
+
+    // A stream reading from a fixed buffer and recording everything written to it.
+    struct Pipe {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for Pipe {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Pipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Opens one side of a channel reading `input`.
+    fn open(input: Vec<u8>, salt: [u8; SALT_SIZE], client: bool) -> FramedChannel<Pipe> {
+        let pipe = Pipe {
+            input: Cursor::new(input),
+            output: Vec::new(),
+        };
+        FramedChannel::open(pipe, &[1u8; 32], salt, client).unwrap()
+    }
+
+    // Returns the frames sent by a channel after its salt.
+    fn frames(channel: &FramedChannel<Pipe>) -> Vec<u8> {
+        channel.get_ref().output[SALT_SIZE..].to_vec()
+    }
+
+    #[test]
+    fn tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut channel = FramedChannel::server(stream, [1u8; 32]).unwrap();
+            let message = channel.recv().unwrap();
+            channel.send(&message.repeat(2)).unwrap();
+        });
+
+        let stream = TcpStream::connect(addr).unwrap();
+        let mut channel = FramedChannel::client(stream, [1u8; 32]).unwrap();
+        channel.send("ping".as_bytes()).unwrap();
+        assert_eq!(channel.recv().unwrap(), "pingping".as_bytes());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn directions() {
+        let mut client = open(SERVER_SALT.to_vec(), CLIENT_SALT, true);
+        client.send("secret".as_bytes()).unwrap();
+        let frames = frames(&client);
+
+        let mut server = open([&CLIENT_SALT[..], &frames].concat(), SERVER_SALT, false);
+        assert_eq!(server.recv().unwrap(), "secret".as_bytes());
+
+        let mut reflected = open([&SERVER_SALT[..], &frames].concat(), CLIENT_SALT, true);
+        let ret = reflected.recv();
+        assert_eq!(ret.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn replay() {
+        let mut client = open(SERVER_SALT.to_vec(), CLIENT_SALT, true);
+        client.send("secret".as_bytes()).unwrap();
+        let frames = frames(&client);
+
+        let input = [&CLIENT_SALT[..], &frames, &frames].concat();
+        let mut server = open(input, SERVER_SALT, false);
+        assert!(server.recv().is_ok());
+        assert_eq!(
+            server.recv().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn reused_key() {
+        let mut first = open(SERVER_SALT.to_vec(), CLIENT_SALT, true);
+        let mut second = open([4u8; SALT_SIZE].to_vec(), CLIENT_SALT, true);
+        first.send("secret".as_bytes()).unwrap();
+        second.send("secret".as_bytes()).unwrap();
+        assert_ne!(frames(&first), frames(&second));
+
+        // Frames of an earlier connection do not authenticate in a new one.
+        let input = [&CLIENT_SALT[..], &frames(&first)].concat();
+        let mut server = open(input, [4u8; SALT_SIZE], false);
+        assert_eq!(
+            server.recv().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn too_large() {
+        let mut input = CLIENT_SALT.to_vec();
+        input.extend_from_slice(&((MAX_MESSAGE_SIZE + 1) as u32).to_le_bytes());
+        input.extend_from_slice(&[0u8; 16]);
+
+        let mut server = open(input, SERVER_SALT, false);
+        assert_eq!(
+            server.recv().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
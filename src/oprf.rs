@@ -0,0 +1,205 @@
+//! Oblivious pseudo random function over X25519
+//!
+//! A 2HashDH construction: the client hashes its input to a curve point with BLAKE2b and
+//! Elligator, blinds it with a random scalar and sends it to the server. The server multiplies
+//! the point with its secret key, and the client removes the blind with
+//! `crypto_x25519_inverse`. The output is the BLAKE2b hash of the input and the unblinded point.
+//! The server learns nothing about the input, the client nothing about the key.
+//!
+//! Requires the `getrandom` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use monocypher::key_exchange::SecretKey;
+//! use monocypher::oprf::{blind, evaluate, unblind};
+//!
+//! let server_key = SecretKey::from([7u8; 32]);
+//!
+//! let (blind_1, blinded) = blind("password".as_bytes()).unwrap();
+//! let evaluated = evaluate(&server_key, &blinded).unwrap();
+//! let output = unblind(blind_1, "password".as_bytes(), &evaluated);
+//!
+//! let (blind_2, blinded) = blind("password".as_bytes()).unwrap();
+//! let evaluated = evaluate(&server_key, &blinded).unwrap();
+//! assert_eq!(*output, *unblind(blind_2, "password".as_bytes(), &evaluated));
+//! ```
+
+use crate::hashing::blake2b;
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::utils;
+use derive_more::From;
+use monocypher_sys as ffi;
+use std::ops::Deref;
+use thiserror::Error;
+
+const HASH_TO_CURVE_DST: &[u8] = b"monocypher-rs oprf hash to curve";
+const OUTPUT_DST: &[u8] = b"monocypher-rs oprf output";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Point has a low order.")]
+    LowOrder,
+}
+
+impl From<crate::random::Error> for Error {
+    fn from(_: crate::random::Error) -> Self {
+        Error::Random
+    }
+}
+
+/// The client's random blinding scalar, wiped on drop.
+pub struct Blind([u8; 32]);
+
+impl_zeroize!(Blind);
+
+impl Drop for Blind {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// The blinded input, sent from the client to the server.
+#[derive(Clone, Copy, Debug, From, PartialEq, Eq)]
+pub struct BlindedElement([u8; 32]);
+
+impl Deref for BlindedElement {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// The blinded input multiplied with the server key, sent back to the client.
+#[derive(Clone, Copy, Debug, From, PartialEq, Eq)]
+pub struct EvaluatedElement([u8; 32]);
+
+impl Deref for EvaluatedElement {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// The output of the function, wiped on drop.
+pub struct Output([u8; 64]);
+
+impl_ct_eq!(Output);
+impl_zeroize!(Output);
+
+impl Deref for Output {
+    type Target = [u8; 64];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// Hashes the input to a curve point and blinds it with a random scalar.
+pub fn blind(input: &[u8]) -> Result<(Blind, BlindedElement), Error> {
+    let point = hash_to_curve(input);
+    let mut blind = Blind([0u8; 32]);
+    crate::random::fill(&mut blind.0)?;
+
+    let mut blinded = [0u8; 32];
+    unsafe {
+        ffi::crypto_x25519(blinded.as_mut_ptr(), blind.0.as_ptr(), point.as_ptr());
+    }
+    Ok((blind, BlindedElement(blinded)))
+}
+
+/// Multiplies a blinded input with the server key.
+///
+/// Fails if the blinded input has a low order, the result would not depend on the key.
+pub fn evaluate(key: &SecretKey, blinded: &BlindedElement) -> Result<EvaluatedElement, Error> {
+    let evaluated = key
+        .diffie_hellman(&PublicKey::from(blinded.0))
+        .map_err(|_| Error::LowOrder)?;
+    Ok(EvaluatedElement(*evaluated))
+}
+
+/// Removes the blind from the server's answer and computes the output for `input`.
+pub fn unblind(blind: Blind, input: &[u8], evaluated: &EvaluatedElement) -> Output {
+    let mut unblinded = [0u8; 32];
+    unsafe {
+        ffi::crypto_x25519_inverse(unblinded.as_mut_ptr(), blind.0.as_ptr(), evaluated.as_ptr());
+    }
+
+    let mut ctx = blake2b::Context::new();
+    ctx.update(OUTPUT_DST);
+    ctx.update(&(input.len() as u64).to_le_bytes());
+    ctx.update(input);
+    ctx.update(&unblinded);
+    utils::wipe(&mut unblinded);
+    Output(ctx.finalize())
+}
+
+// Maps the input to a point of the curve with Elligator.
+fn hash_to_curve(input: &[u8]) -> [u8; 32] {
+    let mut ctx = blake2b::Context::<32>::new_n();
+    ctx.update(HASH_TO_CURVE_DST);
+    ctx.update(input);
+    let mut hidden = [0u8; 32];
+    ctx.finalize_into(&mut hidden);
+
+    let mut point = [0u8; 32];
+    unsafe {
+        ffi::crypto_elligator_map(point.as_mut_ptr(), hidden.as_ptr());
+    }
+    point
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(key: &SecretKey, input: &[u8]) -> Output {
+        let (blind, blinded) = blind(input).unwrap();
+        let evaluated = evaluate(key, &blinded).unwrap();
+        unblind(blind, input, &evaluated)
+    }
+
+    #[test]
+    fn deterministic() {
+        let key = SecretKey::from([7u8; 32]);
+        assert_eq!(
+            *run(&key, "input".as_bytes()),
+            *run(&key, "input".as_bytes())
+        );
+    }
+
+    #[test]
+    fn blinded() {
+        let (_, first) = blind("input".as_bytes()).unwrap();
+        let (_, second) = blind("input".as_bytes()).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn depends_on_key_and_input() {
+        let key = SecretKey::from([7u8; 32]);
+        let output = run(&key, "input".as_bytes());
+        assert_ne!(*output, *run(&key, "other".as_bytes()));
+        assert_ne!(
+            *output,
+            *run(&SecretKey::from([8u8; 32]), "input".as_bytes())
+        );
+    }
+
+    #[test]
+    fn low_order() {
+        let key = SecretKey::from([7u8; 32]);
+        let ret = evaluate(&key, &BlindedElement::from([0u8; 32]));
+        assert_eq!(ret, Err(Error::LowOrder));
+    }
+}
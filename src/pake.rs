@@ -0,0 +1,221 @@
+//! Password authenticated key exchange
+//!
+//! A CPace style balanced PAKE over X25519: both parties stretch the password with Argon2id,
+//! using a salt derived from the context, and map the result to a curve point with Elligator.
+//! That point serves as the base point of an X25519 exchange of ephemeral keys. Only parties
+//! that know the password compute the same shared secret, and an eavesdropper cannot test
+//! guesses offline. The session key is the BLAKE2b hash of the shared secret, both messages in
+//! ascending order and the context, so the roles of the parties do not matter.
+//!
+//! The key is not confirmed, a wrong password shows when the first message under the key fails
+//! to decrypt. The context should identify the parties and the session.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::hashing::blake2b;
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::password::argon2::{Argon2Builder, Config};
+use crate::password::{self, Salt};
+use crate::utils;
+use derive_more::From;
+use monocypher_sys as ffi;
+use std::ops::Deref;
+use thiserror::Error;
+
+const SALT_DST: &[u8] = b"monocypher-rs pake salt";
+const GENERATOR_DST: &[u8] = b"monocypher-rs pake generator";
+const KEY_DST: &[u8] = b"monocypher-rs pake key";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error(transparent)]
+    Password(#[from] password::Error),
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Point has a low order.")]
+    LowOrder,
+}
+
+/// The message each party sends to the other.
+#[derive(Clone, Copy, Debug, From, PartialEq, Eq)]
+pub struct Message([u8; 32]);
+
+impl Deref for Message {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// The agreed session key, wiped on drop.
+pub struct SharedKey([u8; 32]);
+
+impl_ct_eq!(SharedKey);
+impl_zeroize!(SharedKey);
+
+impl Deref for SharedKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for SharedKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// State of one party between sending its message and receiving the other one.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::pake::Pake;
+/// use monocypher::password::argon2::{ArgonAlgorithm, Config};
+///
+/// let config = Config {
+///     algorithm: ArgonAlgorithm::Argon2id,
+///     blocks: 19 * 1024,
+///     passes: 2,
+///     lanes: 1,
+/// };
+/// let context = "pairing of device 1 and 2".as_bytes();
+///
+/// let (alice, alice_message) = Pake::start("1234".as_bytes(), context, config).unwrap();
+/// let (bob, bob_message) = Pake::start("1234".as_bytes(), context, config).unwrap();
+///
+/// let alice_key = alice.finish(&bob_message).unwrap();
+/// let bob_key = bob.finish(&alice_message).unwrap();
+/// assert_eq!(*alice_key, *bob_key);
+/// ```
+pub struct Pake {
+    secret_key: SecretKey,
+    message: Message,
+    context: Vec<u8>,
+}
+
+impl Pake {
+    /// Derives the base point from the password and generates the message to send.
+    ///
+    /// Both parties must use the same context and Argon2 configuration.
+    pub fn start(
+        password: &[u8],
+        context: &[u8],
+        config: Config,
+    ) -> Result<(Pake, Message), Error> {
+        let mut generator = generator(password, context, config)?;
+        let secret_key = SecretKey::generate().map_err(|_| Error::Random)?;
+        let message = secret_key.diffie_hellman(&PublicKey::from(generator));
+        utils::wipe(&mut generator);
+        let message = Message(*message.map_err(|_| Error::LowOrder)?);
+
+        let pake = Pake {
+            secret_key,
+            message,
+            context: context.to_vec(),
+        };
+        Ok((pake, message))
+    }
+
+    /// Computes the session key from the other party's message.
+    pub fn finish(self, their_message: &Message) -> Result<SharedKey, Error> {
+        let shared_secret = self
+            .secret_key
+            .diffie_hellman(&PublicKey::from(their_message.0))
+            .map_err(|_| Error::LowOrder)?;
+        let (first, second) = if self.message.0 <= their_message.0 {
+            (&self.message, their_message)
+        } else {
+            (their_message, &self.message)
+        };
+
+        let mut ctx = blake2b::Context::<32>::new_n();
+        ctx.update(KEY_DST);
+        ctx.update(&*shared_secret);
+        ctx.update(&**first);
+        ctx.update(&**second);
+        ctx.update(&self.context);
+        let mut key = SharedKey([0u8; 32]);
+        ctx.finalize_into(&mut key.0);
+        Ok(key)
+    }
+}
+
+// Stretches the password and maps it to a curve point.
+fn generator(password: &[u8], context: &[u8], config: Config) -> Result<[u8; 32], Error> {
+    let mut salt = [0u8; 16];
+    let mut ctx = blake2b::Context::<16>::new_n();
+    ctx.update(SALT_DST);
+    ctx.update(context);
+    ctx.finalize_into(&mut salt);
+
+    let hasher = Argon2Builder::from(config).build()?;
+    let stretched = hasher.derive_key(password, &Salt::from(salt))?;
+
+    let mut ctx = blake2b::Context::<32>::new_n();
+    ctx.update(GENERATOR_DST);
+    ctx.update(&*stretched);
+    let mut hidden = [0u8; 32];
+    ctx.finalize_into(&mut hidden);
+
+    let mut point = [0u8; 32];
+    unsafe {
+        ffi::crypto_elligator_map(point.as_mut_ptr(), hidden.as_ptr());
+    }
+    utils::wipe(&mut hidden);
+    Ok(point)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::password::argon2::ArgonAlgorithm;
+
+    fn config() -> Config {
+        Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: 16,
+            passes: 1,
+            lanes: 1,
+        }
+    }
+
+    fn exchange(password_a: &[u8], password_b: &[u8], context: &[u8]) -> (SharedKey, SharedKey) {
+        let (alice, alice_message) = Pake::start(password_a, context, config()).unwrap();
+        let (bob, bob_message) = Pake::start(password_b, context, config()).unwrap();
+        assert_ne!(alice_message, bob_message);
+        (
+            alice.finish(&bob_message).unwrap(),
+            bob.finish(&alice_message).unwrap(),
+        )
+    }
+
+    #[test]
+    fn same_password() {
+        let (alice, bob) = exchange(b"1234", b"1234", b"context");
+        assert_eq!(*alice, *bob);
+    }
+
+    #[test]
+    fn different_password() {
+        let (alice, bob) = exchange(b"1234", b"1235", b"context");
+        assert_ne!(*alice, *bob);
+    }
+
+    #[test]
+    fn generator_depends_on_context() {
+        let first = generator(b"1234", b"context", config()).unwrap();
+        assert_eq!(first, generator(b"1234", b"context", config()).unwrap());
+        assert_ne!(first, generator(b"1234", b"other", config()).unwrap());
+    }
+
+    #[test]
+    fn low_order() {
+        let (alice, _) = Pake::start(b"1234", b"context", config()).unwrap();
+        let ret = alice.finish(&Message::from([0u8; 32]));
+        assert_eq!(ret.err(), Some(Error::LowOrder));
+    }
+}
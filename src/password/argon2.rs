@@ -2,77 +2,302 @@
 //!
 //! [Official documentation](https://monocypher.org/manual/argon2)
 
-use libc::{self};
+use crate::password::phc::{self, Phc};
+use crate::password::{AdditionalData, Error, Pepper, Salt};
+use crate::utils;
 use monocypher_sys as ffi;
 use std::mem;
+use std::ops::{Deref, DerefMut};
 use std::os::raw;
+use std::time::{Duration, Instant};
 
-// Allocates the workarea that is used for the argon2 key derivation function.
-#[inline]
-fn alloc_workarea(size: u32) -> Result<*mut libc::c_void, String> {
-    unsafe {
-        let work_area: *mut libc::c_void = libc::calloc(1024, size as usize);
-        if work_area.is_null() {
-            return Err("Failed to allocate needed memory.".to_owned());
-        }
-        Ok(work_area)
-    }
-}
+#[cfg(feature = "password-hash")]
+pub use crate::password::traits::{Argon2, Params};
 
-/// Simple function to derive a key from a password.
+/// Memory for the Argon2 computation that can be reused across derivations.
+///
+/// Allocating the work area dominates the cost of cheap configurations, services deriving many
+/// keys can allocate it once. The memory is wiped before it is freed.
 ///
 /// # Example
 ///
 /// ```
-/// use monocypher::password::argon2::easy;
+/// use monocypher::password::argon2::{general_with_workarea, Config, Inputs, WorkArea};
+/// use monocypher::password::Salt;
 ///
-/// easy("pass".as_bytes(), "salt".as_bytes()).unwrap();
+/// let config = Config::default();
+/// let mut work_area = WorkArea::new(config.blocks).unwrap();
+///
+/// for password in ["pass", "word"] {
+///     let inputs = Inputs {
+///         password: password.as_bytes().into(),
+///         salt: Salt::from([1u8; 16]),
+///     };
+///     general_with_workarea(&mut work_area, Config::default(), inputs, None).unwrap();
+/// }
 /// ```
-pub fn easy(password: &[u8], salt: &[u8]) -> Result<[u8; 32], String> {
-    unsafe {
-        let config = ffi::crypto_argon2_config {
-            algorithm: ffi::CRYPTO_ARGON2_I,
-            nb_blocks: 100000,
-            nb_passes: 3,
-            nb_lanes: 1,
-        };
+pub struct WorkArea {
+    memory: Box<dyn DerefMut<Target = [u64]> + Send>,
+    blocks: u32,
+}
 
-        let inputs = ffi::crypto_argon2_inputs {
-            pass: password.as_ptr(),
-            salt: salt.as_ptr(),
-            pass_size: password.len() as u32,
-            salt_size: salt.len() as u32,
-        };
+impl WorkArea {
+    /// Allocates a work area of `blocks` blocks of 1 KiB.
+    pub fn new(blocks: u32) -> Result<WorkArea, Error> {
+        WorkArea::new_in(blocks, &Heap)
+    }
 
-        let extras = ffi::crypto_argon2_extras {
-            key: std::ptr::null(),
-            ad: std::ptr::null(),
-            key_size: 0,
-            ad_size: 0,
-        };
+    /// Allocates a work area of `blocks` blocks of 1 KiB with the given allocator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::password::argon2::{Allocator, WorkArea};
+    ///
+    /// // Hands out memory from a pool that was set up in advance.
+    /// struct Pool;
+    ///
+    /// impl Allocator for Pool {
+    ///     type Memory = Vec<u64>;
+    ///
+    ///     fn allocate(&self, words: usize) -> Option<Vec<u64>> {
+    ///         Some(vec![0u64; words])
+    ///     }
+    /// }
+    ///
+    /// let work_area = WorkArea::new_in(64, &Pool).unwrap();
+    /// assert_eq!(work_area.blocks(), 64);
+    /// ```
+    pub fn new_in<A: Allocator>(blocks: u32, allocator: &A) -> Result<WorkArea, Error> {
+        let words = (blocks as usize)
+            .checked_mul(BLOCK_WORDS)
+            .ok_or(Error::Allocation)?;
+        let memory = allocator.allocate(words).ok_or(Error::Allocation)?;
+        if memory.len() < words {
+            return Err(Error::Allocation);
+        }
+        Ok(WorkArea {
+            memory: Box::new(memory),
+            blocks,
+        })
+    }
 
-        let work_area = match alloc_workarea(100000) {
-            Ok(wa) => wa,
-            Err(e) => return Err(e),
-        };
+    /// Returns the number of 1 KiB blocks of the work area.
+    pub fn blocks(&self) -> u32 {
+        self.blocks
+    }
+}
+
+impl Drop for WorkArea {
+    fn drop(&mut self) {
+        let memory: &mut [u64] = &mut self.memory;
+        unsafe {
+            ffi::crypto_wipe(
+                memory.as_mut_ptr() as *mut raw::c_void,
+                mem::size_of_val(memory),
+            );
+        }
+    }
+}
+
+/// Allocation strategy for the memory of a [`WorkArea`].
+///
+/// Implement it to take the memory from a locked region or a dedicated memory pool. The memory
+/// is wiped before it is dropped.
+pub trait Allocator {
+    /// Memory handed out by the allocator, it is released when dropped.
+    type Memory: DerefMut<Target = [u64]> + Send + 'static;
+
+    /// Allocates at least `words` 64 bit words, or returns `None` on failure.
+    fn allocate(&self, words: usize) -> Option<Self::Memory>;
+}
+
+/// Allocator taking the memory from the global heap, used by [`WorkArea::new`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Heap;
+
+impl Allocator for Heap {
+    type Memory = Box<[u64]>;
+
+    fn allocate(&self, words: usize) -> Option<Box<[u64]>> {
+        let mut memory = Vec::new();
+        memory.try_reserve_exact(words).ok()?;
+        memory.resize(words, 0u64);
+        Some(memory.into_boxed_slice())
+    }
+}
+
+/// A 32 byte key derived from a password, wiped on drop.
+pub struct DerivedKey([u8; 32]);
+
+impl_ct_eq!(DerivedKey);
+impl_zeroize!(DerivedKey);
 
-        let mut hash = mem::MaybeUninit::<[u8; 32]>::uninit();
+impl Deref for DerivedKey {
+    type Target = [u8; 32];
 
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for DerivedKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for DerivedKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+// Number of 64 bit words in an Argon2 block.
+const BLOCK_WORDS: usize = 1024 / 8;
+
+// Checks the parameters Monocypher relies on without checking them itself.
+fn check_config(config: &ffi::crypto_argon2_config) -> Result<(), Error> {
+    let min_blocks = config.nb_lanes.checked_mul(8).ok_or(Error::InvalidParams)?;
+    if config.nb_lanes == 0 || config.nb_passes == 0 || config.nb_blocks < min_blocks {
+        return Err(Error::InvalidParams);
+    }
+    Ok(())
+}
+
+// Runs Argon2 in the given work area.
+fn derive(
+    work_area: &mut WorkArea,
+    config: ffi::crypto_argon2_config,
+    password: &[u8],
+    salt: &[u8],
+    extras: ffi::crypto_argon2_extras,
+) -> Result<DerivedKey, Error> {
+    let mut key = DerivedKey([0u8; 32]);
+    derive_into(work_area, config, password, salt, extras, &mut key.0)?;
+    Ok(key)
+}
+
+// Runs Argon2 in the given work area, producing a hash of any length.
+pub(super) fn derive_into(
+    work_area: &mut WorkArea,
+    config: ffi::crypto_argon2_config,
+    password: &[u8],
+    salt: &[u8],
+    extras: ffi::crypto_argon2_extras,
+    hash: &mut [u8],
+) -> Result<(), Error> {
+    derive_in(&mut work_area.memory, config, password, salt, extras, hash)
+}
+
+// Runs Argon2 in memory of at least `config.nb_blocks` blocks.
+fn derive_in(
+    memory: &mut [u64],
+    config: ffi::crypto_argon2_config,
+    password: &[u8],
+    salt: &[u8],
+    extras: ffi::crypto_argon2_extras,
+    hash: &mut [u8],
+) -> Result<(), Error> {
+    check_config(&config)?;
+    if memory.len() / BLOCK_WORDS < config.nb_blocks as usize {
+        return Err(Error::WorkAreaTooSmall);
+    }
+
+    let inputs = ffi::crypto_argon2_inputs {
+        pass: password.as_ptr(),
+        salt: salt.as_ptr(),
+        pass_size: password.len() as u32,
+        salt_size: salt.len() as u32,
+    };
+
+    unsafe {
         ffi::crypto_argon2(
-            hash.as_mut_ptr() as *mut u8,
-            hash.assume_init().len() as u32,
-            work_area as *mut raw::c_void,
+            hash.as_mut_ptr(),
+            hash.len() as u32,
+            memory.as_mut_ptr() as *mut raw::c_void,
             config,
             inputs,
             extras,
         );
-
-        libc::free(work_area);
-        Ok(hash.assume_init())
     }
+    Ok(())
+}
+
+// Runs Argon2 in a caller provided buffer and wipes the buffer afterwards.
+fn derive_in_buffer(
+    buffer: &mut [u8],
+    config: ffi::crypto_argon2_config,
+    password: &[u8],
+    salt: &[u8],
+    extras: ffi::crypto_argon2_extras,
+) -> Result<DerivedKey, Error> {
+    // Monocypher accesses the work area as 64 bit words.
+    let (_, memory, _) = unsafe { buffer.align_to_mut::<u64>() };
+    let mut key = DerivedKey([0u8; 32]);
+    let ret = derive_in(memory, config, password, salt, extras, &mut key.0);
+    utils::wipe(buffer);
+    ret.map(|_| key)
 }
 
-#[derive(Default, Debug)]
+pub(super) const NO_EXTRAS: ffi::crypto_argon2_extras = ffi::crypto_argon2_extras {
+    key: std::ptr::null(),
+    ad: std::ptr::null(),
+    key_size: 0,
+    ad_size: 0,
+};
+
+/// Simple function to derive a key from a password.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::easy;
+///
+/// easy("pass".as_bytes(), "salt".as_bytes()).unwrap();
+/// ```
+#[deprecated(note = "use Argon2Builder")]
+pub fn easy(password: &[u8], salt: &[u8]) -> Result<DerivedKey, Error> {
+    let config = ffi::crypto_argon2_config {
+        algorithm: ffi::CRYPTO_ARGON2_I,
+        nb_blocks: 100000,
+        nb_passes: 3,
+        nb_lanes: 1,
+    };
+
+    let mut work_area = WorkArea::new(config.nb_blocks)?;
+    derive(&mut work_area, config, password, salt, NO_EXTRAS)
+}
+
+/// Derives a key like [`easy`] in a caller provided buffer instead of heap memory.
+///
+/// The buffer must hold 100000 blocks of 1 KiB, plus up to 7 bytes if it is not aligned on 8
+/// bytes. It is wiped before this function returns.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::easy_with_buffer;
+///
+/// let mut buffer = vec![0u8; 100000 * 1024];
+/// easy_with_buffer(&mut buffer, "pass".as_bytes(), "salt".as_bytes()).unwrap();
+/// ```
+#[deprecated(note = "use Argon2Builder")]
+pub fn easy_with_buffer(
+    buffer: &mut [u8],
+    password: &[u8],
+    salt: &[u8],
+) -> Result<DerivedKey, Error> {
+    let config = ffi::crypto_argon2_config {
+        algorithm: ffi::CRYPTO_ARGON2_I,
+        nb_blocks: 100000,
+        nb_passes: 3,
+        nb_lanes: 1,
+    };
+    derive_in_buffer(buffer, config, password, salt, NO_EXTRAS)
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
 pub enum ArgonAlgorithm {
     #[default]
     Argon2i,
@@ -90,10 +315,19 @@ impl From<ArgonAlgorithm> for u32 {
     }
 }
 
+/// Argon2 cost parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Config {
     pub algorithm: ArgonAlgorithm,
+    /// Memory in blocks of 1 KiB, at least 8 per lane.
     pub blocks: u32,
+    /// Number of passes over the memory.
     pub passes: u32,
+    /// Number of lanes.
+    ///
+    /// Monocypher computes all lanes on the calling thread and has no interface to compute
+    /// them separately, so more lanes do not make the derivation faster. Use more than one
+    /// lane only to match hashes produced by another implementation.
     pub lanes: u32,
 }
 
@@ -120,9 +354,114 @@ impl Default for Config {
     }
 }
 
+impl Config {
+    /// Argon2id with 64 MiB and 2 passes, for logins and other online operations.
+    ///
+    /// The presets follow libsodium's `crypto_pwhash` limits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::password::argon2::Config;
+    ///
+    /// assert_eq!(Config::interactive().memory_kib(), 64 * 1024);
+    /// ```
+    pub fn interactive() -> Config {
+        Config::argon2id(64 * 1024, 2)
+    }
+
+    /// Argon2id with 256 MiB and 3 passes.
+    pub fn moderate() -> Config {
+        Config::argon2id(256 * 1024, 3)
+    }
+
+    /// Argon2id with 1 GiB and 4 passes, for offline operations such as encrypting files.
+    pub fn sensitive() -> Config {
+        Config::argon2id(1024 * 1024, 4)
+    }
+
+    fn argon2id(memory_kib: u32, passes: u32) -> Config {
+        Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: memory_kib,
+            passes,
+            lanes: 1,
+        }
+    }
+
+    /// Returns the memory in KiB, blocks are 1 KiB each.
+    pub fn memory_kib(&self) -> u32 {
+        self.blocks
+    }
+
+    // Fails if the blocks, passes or lanes are above the ones of `max`, the algorithm is not
+    // compared.
+    pub(crate) fn check_limits(&self, max: &Config) -> Result<(), Error> {
+        if self.blocks > max.blocks || self.passes > max.passes || self.lanes > max.lanes {
+            return Err(Error::LimitExceeded);
+        }
+        Ok(())
+    }
+
+    /// Picks Argon2id parameters that take about `target` to compute on this machine.
+    ///
+    /// The derivation uses as much memory as possible, as recommended by libsodium's `pwhash`
+    /// guidance: one pass over `max_memory` bytes is benchmarked, then the passes are raised to
+    /// fill the target. If a single pass is already slower than the target, the memory is scaled
+    /// down instead. The result depends on the load of the machine, so it should be computed
+    /// once, at setup time, and stored with the hashes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::password::argon2::Config;
+    /// use std::time::Duration;
+    ///
+    /// let config = Config::calibrate(Duration::from_millis(50), 1 << 20).unwrap();
+    /// assert!(config.blocks <= 1024);
+    /// ```
+    pub fn calibrate(target: Duration, max_memory: usize) -> Result<Config, Error> {
+        let blocks = u32::try_from(max_memory / 1024).unwrap_or(u32::MAX);
+        let mut config = Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: blocks.max(8),
+            passes: 1,
+            lanes: 1,
+        };
+
+        let mut work_area = WorkArea::new(config.blocks)?;
+        let start = Instant::now();
+        derive(
+            &mut work_area,
+            config.into(),
+            &[0u8; 16],
+            &[0u8; 16],
+            NO_EXTRAS,
+        )?;
+        let elapsed = start.elapsed().as_nanos().max(1);
+        let target = target.as_nanos();
+
+        if elapsed > target {
+            let blocks = config.blocks as u128 * target / elapsed;
+            config.blocks = (blocks as u32).max(8);
+        } else {
+            let passes = target / elapsed;
+            config.passes = u32::try_from(passes).unwrap_or(u32::MAX);
+        }
+        Ok(config)
+    }
+}
+
+/// Password and salt of a derivation, the password is wiped on drop.
 pub struct Inputs {
     pub password: Vec<u8>,
-    pub salt: [u8; 16],
+    pub salt: Salt,
+}
+
+impl Drop for Inputs {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.password);
+    }
 }
 
 impl From<Inputs> for ffi::crypto_argon2_inputs {
@@ -136,22 +475,293 @@ impl From<Inputs> for ffi::crypto_argon2_inputs {
     }
 }
 
+/// Optional inputs of a derivation.
+#[derive(Clone)]
 pub struct Extras {
-    pub key: Vec<u8>,
-    pub additional_data: Vec<u8>,
+    /// Secret key, see [`Pepper`].
+    pub pepper: Pepper,
+    pub additional_data: AdditionalData,
 }
 
-impl From<Extras> for ffi::crypto_argon2_extras {
-    fn from(extras: Extras) -> Self {
-        Self {
-            key: extras.key.as_ptr(),
-            ad: extras.additional_data.as_ptr(),
-            key_size: extras.key.len() as u32,
-            ad_size: extras.additional_data.len() as u32,
+impl Extras {
+    // Borrows the extras for Monocypher.
+    fn to_ffi(&self) -> ffi::crypto_argon2_extras {
+        ffi::crypto_argon2_extras {
+            key: self.pepper.as_ptr(),
+            ad: self.additional_data.as_ptr(),
+            key_size: self.pepper.len() as u32,
+            ad_size: self.additional_data.len() as u32,
+        }
+    }
+}
+
+/// Builder of a reusable Argon2 [`Hasher`].
+///
+/// It starts from [`Config::default`] and 32 byte hashes.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{Argon2Builder, ArgonAlgorithm};
+/// use monocypher::password::Salt;
+///
+/// let hasher = Argon2Builder::new()
+///     .algorithm(ArgonAlgorithm::Argon2id)
+///     .memory_kib(19 * 1024)
+///     .passes(2)
+///     .lanes(1)
+///     .output_len(64)
+///     .pepper([7u8; 32].as_slice())
+///     .ad("user")
+///     .build()
+///     .unwrap();
+///
+/// let hash = hasher.hash("pass".as_bytes(), &Salt::from([1u8; 16])).unwrap();
+/// assert_eq!(hash.len(), 64);
+/// ```
+#[derive(Default)]
+pub struct Argon2Builder {
+    config: Config,
+    output_len: Option<usize>,
+    pepper: Option<Pepper>,
+    ad: AdditionalData,
+}
+
+impl From<Config> for Argon2Builder {
+    /// Starts from the given cost parameters instead of the default ones.
+    fn from(config: Config) -> Self {
+        Argon2Builder {
+            config,
+            ..Default::default()
         }
     }
 }
 
+impl Argon2Builder {
+    /// Starts from [`Config::default`], 32 byte hashes, no pepper and no additional data.
+    pub fn new() -> Argon2Builder {
+        Default::default()
+    }
+
+    /// Sets the Argon2 variant.
+    pub fn algorithm(mut self, algorithm: ArgonAlgorithm) -> Self {
+        self.config.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the memory in KiB, one block per KiB.
+    pub fn memory_kib(mut self, kib: u32) -> Self {
+        self.config.blocks = kib;
+        self
+    }
+
+    /// Sets the number of passes over the memory.
+    pub fn passes(mut self, passes: u32) -> Self {
+        self.config.passes = passes;
+        self
+    }
+
+    /// Sets the number of lanes, the memory must hold at least 8 blocks per lane.
+    pub fn lanes(mut self, lanes: u32) -> Self {
+        self.config.lanes = lanes;
+        self
+    }
+
+    /// Sets the length of the hashes in bytes, at least 4.
+    pub fn output_len(mut self, output_len: usize) -> Self {
+        self.output_len = Some(output_len);
+        self
+    }
+
+    /// Sets a secret key mixed into every hash, see [`Pepper`].
+    pub fn pepper(mut self, pepper: impl Into<Pepper>) -> Self {
+        self.pepper = Some(pepper.into());
+        self
+    }
+
+    /// Sets additional data the hashes are bound to, e.g. a user name.
+    pub fn ad(mut self, ad: impl Into<AdditionalData>) -> Self {
+        self.ad = ad.into();
+        self
+    }
+
+    /// Checks the parameters and builds the hasher.
+    pub fn build(self) -> Result<Hasher, Error> {
+        let output_len = self.output_len.unwrap_or(32);
+        check_config(&self.config.into())?;
+        if output_len < 4 || u32::try_from(output_len).is_err() {
+            return Err(Error::InvalidParams);
+        }
+        Ok(Hasher {
+            config: self.config,
+            output_len,
+            extras: Extras {
+                pepper: self.pepper.unwrap_or_else(|| Pepper::from(Vec::new())),
+                additional_data: self.ad,
+            },
+        })
+    }
+}
+
+/// Argon2 hasher with fixed parameters, built by [`Argon2Builder`].
+#[derive(Clone)]
+pub struct Hasher {
+    config: Config,
+    output_len: usize,
+    extras: Extras,
+}
+
+impl Hasher {
+    /// Returns the cost parameters of the hasher.
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Hashes a password.
+    pub fn hash(&self, password: &[u8], salt: &Salt) -> Result<Hash, Error> {
+        let mut work_area = WorkArea::new(self.config.blocks)?;
+        self.hash_with_workarea(&mut work_area, password, salt)
+    }
+
+    /// Hashes a password in a work area that is reused across calls.
+    pub fn hash_with_workarea(
+        &self,
+        work_area: &mut WorkArea,
+        password: &[u8],
+        salt: &Salt,
+    ) -> Result<Hash, Error> {
+        let mut hash = Hash(vec![0u8; self.output_len]);
+        derive_into(
+            work_area,
+            self.config.into(),
+            password,
+            &salt[..],
+            self.extras.to_ffi(),
+            &mut hash.0,
+        )?;
+        Ok(hash)
+    }
+
+    /// Hashes a password in a caller provided buffer instead of heap memory.
+    ///
+    /// The buffer must hold [`memory_kib`](Argon2Builder::memory_kib) blocks of 1 KiB, plus up
+    /// to 7 bytes if it is not aligned on 8 bytes, otherwise [`Error::WorkAreaTooSmall`] is
+    /// returned. It is wiped before this function returns.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::password::argon2::Argon2Builder;
+    /// use monocypher::password::Salt;
+    ///
+    /// let hasher = Argon2Builder::new().memory_kib(64).build().unwrap();
+    /// let mut buffer = [0u8; 64 * 1024 + 8];
+    /// let salt = Salt::from([1u8; 16]);
+    /// hasher.hash_with_buffer(&mut buffer, "pass".as_bytes(), &salt).unwrap();
+    /// ```
+    pub fn hash_with_buffer(
+        &self,
+        buffer: &mut [u8],
+        password: &[u8],
+        salt: &Salt,
+    ) -> Result<Hash, Error> {
+        // Monocypher accesses the work area as 64 bit words.
+        let (_, memory, _) = unsafe { buffer.align_to_mut::<u64>() };
+        let mut hash = Hash(vec![0u8; self.output_len]);
+        let ret = derive_in(
+            memory,
+            self.config.into(),
+            password,
+            &salt[..],
+            self.extras.to_ffi(),
+            &mut hash.0,
+        );
+        utils::wipe(buffer);
+        ret.map(|_| hash)
+    }
+
+    /// Hashes a password on tokio's blocking thread pool.
+    ///
+    /// A derivation takes long enough to stall every task of an executor thread it runs on, async
+    /// code should await this function instead. Requires the `tokio` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::password::argon2::Argon2Builder;
+    /// use monocypher::password::Salt;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let hasher = Argon2Builder::new().build().unwrap();
+    /// let salt = Salt::from([1u8; 16]);
+    /// hasher.hash_blocking("pass".as_bytes(), &salt).await.unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn hash_blocking(&self, password: &[u8], salt: &Salt) -> Result<Hash, Error> {
+        let hasher = self.clone();
+        let inputs = Inputs {
+            password: password.to_vec(),
+            salt: *salt,
+        };
+        let derivation =
+            ::tokio::task::spawn_blocking(move || hasher.hash(&inputs.password, &inputs.salt));
+        match derivation.await {
+            Ok(ret) => ret,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+
+    /// Derives a 32 byte key, e.g. for encryption, regardless of the configured output length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::password::argon2::Argon2Builder;
+    /// use monocypher::password::Salt;
+    ///
+    /// let hasher = Argon2Builder::new().build().unwrap();
+    /// let key = hasher.derive_key("pass".as_bytes(), &Salt::from([1u8; 16])).unwrap();
+    /// assert_eq!(key.len(), 32);
+    /// ```
+    pub fn derive_key(&self, password: &[u8], salt: &Salt) -> Result<DerivedKey, Error> {
+        let mut work_area = WorkArea::new(self.config.blocks)?;
+        derive(
+            &mut work_area,
+            self.config.into(),
+            password,
+            &salt[..],
+            self.extras.to_ffi(),
+        )
+    }
+}
+
+/// A password hash of any length, wiped on drop.
+pub struct Hash(Vec<u8>);
+
+impl_zeroize!(Hash);
+
+impl Deref for Hash {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Hash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for Hash {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
 /// Function to derive a key from a password with additional data.
 ///
 /// # Example
@@ -159,56 +769,244 @@ impl From<Extras> for ffi::crypto_argon2_extras {
 /// ```
 /// use monocypher::password::argon2::general;
 /// use monocypher::password::argon2::Inputs;
+/// use monocypher::password::Salt;
 ///
 /// let inputs = Inputs {
 ///     password: "pass".as_bytes().into(),
-///     salt: [1u8; 16],
+///     salt: Salt::from([1u8; 16]),
 /// };
 ///
 /// general(Default::default(), inputs, None).unwrap();
 /// ```
-pub fn general(config: Config, inputs: Inputs, extras: Option<Extras>) -> Result<[u8; 32], String> {
-    let work_area = match alloc_workarea(config.blocks) {
-        Ok(wa) => wa,
-        Err(e) => return Err(e),
-    };
+#[deprecated(note = "use Argon2Builder")]
+pub fn general(
+    config: Config,
+    inputs: Inputs,
+    extras: Option<Extras>,
+) -> Result<DerivedKey, Error> {
+    let mut work_area = WorkArea::new(config.blocks)?;
+    let extras = extras.as_ref().map_or(NO_EXTRAS, Extras::to_ffi);
+    derive(
+        &mut work_area,
+        config.into(),
+        &inputs.password,
+        &inputs.salt[..],
+        extras,
+    )
+}
 
-    unsafe {
-        let inputs = ffi::crypto_argon2_inputs {
-            pass: inputs.password.as_ptr(),
-            salt: inputs.salt.as_ptr(),
-            pass_size: inputs.password.len() as u32,
-            salt_size: inputs.salt.len() as u32,
-        };
+/// Derives a key like [`general`] in a work area that is reused across calls.
+///
+/// The work area must have at least `config.blocks` blocks.
+#[deprecated(note = "use Argon2Builder and Hasher::hash_with_workarea")]
+pub fn general_with_workarea(
+    work_area: &mut WorkArea,
+    config: Config,
+    inputs: Inputs,
+    extras: Option<Extras>,
+) -> Result<DerivedKey, Error> {
+    let extras = extras.as_ref().map_or(NO_EXTRAS, Extras::to_ffi);
 
-        let mut hash = mem::MaybeUninit::<[u8; 32]>::uninit();
+    derive(
+        work_area,
+        config.into(),
+        &inputs.password,
+        &inputs.salt[..],
+        extras,
+    )
+}
 
-        let extras = if let Some(extras) = extras {
-            extras.into()
-        } else {
-            ffi::crypto_argon2_extras {
-                key: std::ptr::null(),
-                ad: std::ptr::null(),
-                key_size: 0,
-                ad_size: 0,
-            }
-        };
+/// Derives a key like [`general`] in a caller provided buffer instead of heap memory.
+///
+/// The buffer must hold `config.blocks` blocks of 1 KiB, plus up to 7 bytes if it is not
+/// aligned on 8 bytes, otherwise [`Error::WorkAreaTooSmall`] is returned. It is wiped before this
+/// function returns.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{general_with_buffer, Config, Inputs};
+/// use monocypher::password::Salt;
+///
+/// let mut buffer = [0u8; 64 * 1024 + 8];
+/// let config = Config {
+///     blocks: 64,
+///     ..Default::default()
+/// };
+/// let inputs = Inputs {
+///     password: "pass".as_bytes().into(),
+///     salt: Salt::from([1u8; 16]),
+/// };
+/// general_with_buffer(&mut buffer, config, inputs, None).unwrap();
+/// ```
+#[deprecated(note = "use Argon2Builder and Hasher::hash_with_buffer")]
+pub fn general_with_buffer(
+    buffer: &mut [u8],
+    config: Config,
+    inputs: Inputs,
+    extras: Option<Extras>,
+) -> Result<DerivedKey, Error> {
+    let extras = extras.as_ref().map_or(NO_EXTRAS, Extras::to_ffi);
 
-        ffi::crypto_argon2(
-            hash.as_mut_ptr() as *mut u8,
-            hash.assume_init().len() as u32,
-            work_area as *mut raw::c_void,
+    derive_in_buffer(
+        buffer,
+        config.into(),
+        &inputs.password,
+        &inputs.salt[..],
+        extras,
+    )
+}
+
+/// Derives a key like [`general`] on tokio's blocking thread pool.
+///
+/// A derivation takes long enough to stall every task of an executor thread it runs on, async
+/// code should await this function instead. Requires the `tokio` feature.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{derive_blocking, Inputs};
+/// use monocypher::password::Salt;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let inputs = Inputs {
+///     password: "pass".as_bytes().into(),
+///     salt: Salt::from([1u8; 16]),
+/// };
+/// derive_blocking(Default::default(), inputs).await.unwrap();
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[deprecated(note = "use Argon2Builder and Hasher::hash_blocking")]
+pub async fn derive_blocking(config: Config, inputs: Inputs) -> Result<DerivedKey, Error> {
+    let derivation = ::tokio::task::spawn_blocking(move || {
+        let mut work_area = WorkArea::new(config.blocks)?;
+        derive(
+            &mut work_area,
             config.into(),
-            inputs,
-            extras,
-        );
+            &inputs.password,
+            &inputs.salt[..],
+            NO_EXTRAS,
+        )
+    });
+    match derivation.await {
+        Ok(ret) => ret,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
+
+/// Derives a hash with a random salt and encodes it as a PHC string.
+///
+/// The string holds the algorithm, the parameters and the salt, so it can be stored as is and
+/// checked with [`verify_phc`], also by other Argon2 implementations.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{hash_phc, verify_phc, ArgonAlgorithm, Config};
+///
+/// let config = Config {
+///     algorithm: ArgonAlgorithm::Argon2id,
+///     blocks: 19 * 1024,
+///     passes: 2,
+///     lanes: 1,
+/// };
+/// let phc = hash_phc("pass".as_bytes(), config).unwrap();
+/// assert!(verify_phc("pass".as_bytes(), &phc).unwrap());
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn hash_phc(password: &[u8], config: Config) -> Result<String, Error> {
+    let salt = Salt::random()?.to_vec();
 
-        libc::free(work_area);
-        Ok(hash.assume_init())
+    let mut hash = vec![0u8; 32];
+    let mut work_area = WorkArea::new(config.blocks)?;
+    derive_into(
+        &mut work_area,
+        config.into(),
+        password,
+        &salt,
+        NO_EXTRAS,
+        &mut hash,
+    )?;
+    Ok(Phc { config, salt, hash }.format())
+}
+
+/// Checks a password against a PHC string.
+///
+/// Returns whether the password matches, or an error if the string is malformed or uses
+/// parameters Monocypher cannot compute. The parameters are read from the string, use
+/// [`verify_phc_with_limits`] for strings that do not come from a trusted store.
+pub fn verify_phc(password: &[u8], phc: &str) -> Result<bool, Error> {
+    verify(password, Phc::parse(phc)?)
+}
+
+/// Checks a password against a PHC string whose parameters are at most the ones of `max`.
+///
+/// Strings asking for more blocks, passes or lanes than `max` fail with
+/// [`Error::LimitExceeded`] before any memory is allocated.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{verify_phc_with_limits, Config};
+/// use monocypher::password::Error;
+///
+/// let phc = "$argon2i$v=19$m=100000,t=3,p=1$AQEBAQEBAQEBAQEBAQEBAQ$\
+///            mYLIw+rayhakE9LAocjoKKuubk146Xa89cIH1EsX27Q";
+/// let ret = verify_phc_with_limits("password".as_bytes(), phc, &Config::interactive());
+/// assert_eq!(ret, Err(Error::LimitExceeded));
+/// ```
+pub fn verify_phc_with_limits(password: &[u8], phc: &str, max: &Config) -> Result<bool, Error> {
+    let phc = Phc::parse(phc)?;
+    phc.config.check_limits(max)?;
+    verify(password, phc)
+}
+
+fn verify(password: &[u8], phc: Phc) -> Result<bool, Error> {
+    let mut hash = vec![0u8; phc.hash.len()];
+    let mut work_area = WorkArea::new(phc.config.blocks)?;
+    derive_into(
+        &mut work_area,
+        phc.config.into(),
+        password,
+        &phc.salt,
+        NO_EXTRAS,
+        &mut hash,
+    )?;
+    Ok(phc::hash_eq(&hash, &phc.hash))
+}
+
+/// Checks whether a PHC string was derived with other parameters than `config`.
+///
+/// Call it after a successful [`verify_phc`] and store a new hash of the password when it
+/// returns true, stored hashes then follow the configuration as its costs are raised.
+/// Malformed strings also need a new hash.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{needs_rehash, Config};
+///
+/// let phc = "$argon2i$v=19$m=100000,t=3,p=1$AQEBAQEBAQEBAQEBAQEBAQ$\
+///            mYLIw+rayhakE9LAocjoKKuubk146Xa89cIH1EsX27Q";
+/// assert!(!needs_rehash(phc, &Config::default()));
+///
+/// let config = Config {
+///     passes: 4,
+///     ..Default::default()
+/// };
+/// assert!(needs_rehash(phc, &config));
+/// ```
+pub fn needs_rehash(phc: &str, config: &Config) -> bool {
+    match Phc::parse(phc) {
+        Ok(phc) => phc.config != *config,
+        Err(_) => true,
     }
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod test {
     use super::*;
     use hex;
@@ -232,7 +1030,7 @@ mod test {
     fn argon2_general() {
         let inputs = Inputs {
             password: "password".as_bytes().to_vec(),
-            salt: [1; 16],
+            salt: Salt::from([1; 16]),
         };
 
         let pass = hex::encode(general(Default::default(), inputs, None).unwrap());
@@ -246,7 +1044,7 @@ mod test {
     fn argon2_general_key_fail() {
         let inputs = Inputs {
             password: "password".as_bytes().to_vec(),
-            salt: [1; 16],
+            salt: Salt::from([1; 16]),
         };
         let pass = hex::encode(general(Default::default(), inputs, None).unwrap());
         assert_ne!(
@@ -259,7 +1057,7 @@ mod test {
     fn argon2_general_ad_fail() {
         let inputs = Inputs {
             password: "password".as_bytes().to_vec(),
-            salt: [1; 16],
+            salt: Salt::from([1; 16]),
         };
         let pass = hex::encode(general(Default::default(), inputs, None).unwrap());
         assert_ne!(
@@ -269,11 +1067,377 @@ mod test {
     }
 
     #[test]
-    fn workarea_zero() {
-        let wa = alloc_workarea(0);
-        assert_eq!(wa.is_ok(), true);
-        unsafe {
-            libc::free(wa.unwrap());
+    fn argon2_extras() {
+        let config = || Config {
+            blocks: 8,
+            ..Default::default()
+        };
+        let inputs = || Inputs {
+            password: "password".as_bytes().to_vec(),
+            salt: Salt::from([1; 16]),
+        };
+        let extras = |pepper: &[u8], ad: &str| Extras {
+            pepper: Pepper::from(pepper),
+            additional_data: AdditionalData::from(ad),
+        };
+
+        let plain = general(config(), inputs(), None).unwrap();
+        let peppered = general(config(), inputs(), Some(extras(&[7; 32], ""))).unwrap();
+        assert_ne!(*plain, *peppered);
+        assert_eq!(
+            *plain,
+            *general(config(), inputs(), Some(extras(&[], ""))).unwrap()
+        );
+
+        let again = general(config(), inputs(), Some(extras(&[7; 32], ""))).unwrap();
+        assert_eq!(*peppered, *again);
+        let bound = general(config(), inputs(), Some(extras(&[7; 32], "user"))).unwrap();
+        assert_ne!(*peppered, *bound);
+    }
+
+    #[test]
+    fn argon2_builder() {
+        let inputs = || Inputs {
+            password: "password".as_bytes().to_vec(),
+            salt: Salt::from([1; 16]),
+        };
+        let hasher = Argon2Builder::new().build().unwrap();
+        let hash = hasher
+            .hash("password".as_bytes(), &Salt::from([1; 16]))
+            .unwrap();
+        assert_eq!(*hash, *general(Default::default(), inputs(), None).unwrap());
+
+        let hasher = Argon2Builder::new()
+            .algorithm(ArgonAlgorithm::Argon2id)
+            .memory_kib(8)
+            .passes(1)
+            .pepper([7u8; 32].as_slice())
+            .ad("user")
+            .build()
+            .unwrap();
+        let extras = Extras {
+            pepper: Pepper::from([7u8; 32].as_slice()),
+            additional_data: AdditionalData::from("user"),
+        };
+        let hash = hasher
+            .hash("password".as_bytes(), &Salt::from([1; 16]))
+            .unwrap();
+        assert_eq!(
+            *hash,
+            *general(hasher.config(), inputs(), Some(extras)).unwrap()
+        );
+
+        let hasher = Argon2Builder::new().memory_kib(8).output_len(64).build();
+        let hash = hasher
+            .unwrap()
+            .hash("password".as_bytes(), &Salt::from([1; 16]));
+        assert_eq!(hash.unwrap().len(), 64);
+
+        let ret = Argon2Builder::new().output_len(3).build();
+        assert!(matches!(ret, Err(Error::InvalidParams)));
+        let ret = Argon2Builder::new().lanes(0).build();
+        assert!(matches!(ret, Err(Error::InvalidParams)));
+    }
+
+    #[test]
+    fn argon2_hasher_buffer() {
+        let hasher = Argon2Builder::new().memory_kib(8).build().unwrap();
+        let salt = Salt::from([1; 16]);
+        let expected = hasher.hash("password".as_bytes(), &salt).unwrap();
+
+        let mut buffer = vec![0u8; 8 * 1024 + 8];
+        let hash = hasher
+            .hash_with_buffer(&mut buffer[1..], "password".as_bytes(), &salt)
+            .unwrap();
+        assert_eq!(*hash, *expected);
+        assert!(buffer.iter().all(|&b| b == 0));
+
+        let mut buffer = vec![0u8; 8 * 1024 - 1];
+        let ret = hasher.hash_with_buffer(&mut buffer, "password".as_bytes(), &salt);
+        assert_eq!(ret.err(), Some(Error::WorkAreaTooSmall));
+    }
+
+    #[test]
+    fn argon2_derive_key() {
+        let config = Config {
+            blocks: 8,
+            ..Default::default()
+        };
+        let inputs = Inputs {
+            password: "password".as_bytes().to_vec(),
+            salt: Salt::from([1; 16]),
+        };
+        let hasher = Argon2Builder::from(config).output_len(64).build().unwrap();
+        assert_eq!(hasher.config(), config);
+
+        let key = hasher
+            .derive_key("password".as_bytes(), &Salt::from([1; 16]))
+            .unwrap();
+        assert_eq!(*key, *general(config, inputs, None).unwrap());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[::tokio::test]
+    async fn argon2_hash_blocking() {
+        let hasher = Argon2Builder::new().memory_kib(8).build().unwrap();
+        let salt = Salt::from([1; 16]);
+
+        let hash = hasher
+            .hash_blocking("password".as_bytes(), &salt)
+            .await
+            .unwrap();
+        assert_eq!(*hash, *hasher.hash("password".as_bytes(), &salt).unwrap());
+    }
+
+    #[test]
+    fn argon2_workarea_reuse() {
+        let config = || Config {
+            blocks: 8,
+            ..Default::default()
+        };
+        let inputs = || Inputs {
+            password: "password".as_bytes().to_vec(),
+            salt: Salt::from([1; 16]),
+        };
+
+        let mut work_area = WorkArea::new(16).unwrap();
+        let first = general_with_workarea(&mut work_area, config(), inputs(), None).unwrap();
+        let second = general_with_workarea(&mut work_area, config(), inputs(), None).unwrap();
+        assert_eq!(*first, *second);
+        assert_eq!(*first, *general(config(), inputs(), None).unwrap());
+    }
+
+    #[test]
+    fn argon2_workarea_too_small() {
+        let inputs = Inputs {
+            password: "password".as_bytes().to_vec(),
+            salt: Salt::from([1; 16]),
+        };
+        let mut work_area = WorkArea::new(8).unwrap();
+        let ret = general_with_workarea(&mut work_area, Default::default(), inputs, None);
+        assert_eq!(ret.err(), Some(Error::WorkAreaTooSmall));
+    }
+
+    #[test]
+    fn argon2_buffer() {
+        let config = || Config {
+            blocks: 8,
+            ..Default::default()
+        };
+        let inputs = || Inputs {
+            password: "password".as_bytes().to_vec(),
+            salt: Salt::from([1; 16]),
+        };
+        let expected = general(config(), inputs(), None).unwrap();
+
+        // Unaligned on purpose, the 7 extra bytes make up for the alignment.
+        let mut buffer = vec![0u8; 8 * 1024 + 8];
+        let key = general_with_buffer(&mut buffer[1..], config(), inputs(), None).unwrap();
+        assert_eq!(*key, *expected);
+        assert!(buffer.iter().all(|&b| b == 0));
+
+        let mut buffer = vec![0u8; 8 * 1024 - 1];
+        let ret = general_with_buffer(&mut buffer, config(), inputs(), None);
+        assert_eq!(ret.err(), Some(Error::WorkAreaTooSmall));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[::tokio::test]
+    async fn argon2_derive_blocking() {
+        let config = || Config {
+            blocks: 8,
+            ..Default::default()
+        };
+        let inputs = || Inputs {
+            password: "password".as_bytes().to_vec(),
+            salt: Salt::from([1; 16]),
+        };
+
+        let key = derive_blocking(config(), inputs()).await.unwrap();
+        assert_eq!(*key, *general(config(), inputs(), None).unwrap());
+
+        let ret = derive_blocking(
+            Config {
+                passes: 0,
+                ..config()
+            },
+            inputs(),
+        )
+        .await;
+        assert_eq!(ret.err(), Some(Error::InvalidParams));
+    }
+
+    #[test]
+    fn argon2_verify_phc() {
+        let phc = "$argon2i$v=19$m=100000,t=3,p=1$AQEBAQEBAQEBAQEBAQEBAQ$\
+                   mYLIw+rayhakE9LAocjoKKuubk146Xa89cIH1EsX27Q";
+        assert_eq!(verify_phc("password".as_bytes(), phc), Ok(true));
+        assert_eq!(verify_phc("passwort".as_bytes(), phc), Ok(false));
+        assert_eq!(
+            verify_phc("password".as_bytes(), "$argon2i$v=19$"),
+            Err(Error::InvalidPhc)
+        );
+    }
+
+    #[test]
+    fn argon2_verify_phc_with_limits() {
+        let phc = "$argon2i$v=19$m=100000,t=3,p=1$AQEBAQEBAQEBAQEBAQEBAQ$\
+                   mYLIw+rayhakE9LAocjoKKuubk146Xa89cIH1EsX27Q";
+        let max = Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: 100000,
+            passes: 3,
+            lanes: 1,
+        };
+        assert_eq!(
+            verify_phc_with_limits("password".as_bytes(), phc, &max),
+            Ok(true)
+        );
+        for max in [
+            Config {
+                blocks: 99999,
+                ..max
+            },
+            Config { passes: 2, ..max },
+            Config { lanes: 0, ..max },
+        ] {
+            assert_eq!(
+                verify_phc_with_limits("password".as_bytes(), phc, &max),
+                Err(Error::LimitExceeded)
+            );
         }
     }
+
+    #[test]
+    fn argon2_needs_rehash() {
+        let phc = "$argon2id$v=19$m=16,t=1,p=2$AQEBAQEBAQEBAQEBAQEBAQ$\
+                   mYLIw+rayhakE9LAocjoKKuubk146Xa89cIH1EsX27Q";
+        let config = Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: 16,
+            passes: 1,
+            lanes: 2,
+        };
+        assert!(!needs_rehash(phc, &config));
+        assert!(needs_rehash(
+            phc,
+            &Config {
+                blocks: 32,
+                ..config
+            }
+        ));
+        assert!(needs_rehash(
+            phc,
+            &Config {
+                passes: 2,
+                ..config
+            }
+        ));
+        assert!(needs_rehash(phc, &Config { lanes: 1, ..config }));
+        assert!(needs_rehash(
+            phc,
+            &Config {
+                algorithm: ArgonAlgorithm::Argon2i,
+                ..config
+            }
+        ));
+        assert!(needs_rehash("$argon2id$v=19$", &config));
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn argon2_hash_phc() {
+        let config = Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: 16,
+            passes: 1,
+            lanes: 2,
+        };
+        let phc = hash_phc("password".as_bytes(), config).unwrap();
+        assert!(phc.starts_with("$argon2id$v=19$m=16,t=1,p=2$"));
+        assert_eq!(verify_phc("password".as_bytes(), &phc), Ok(true));
+        assert_ne!(phc, hash_phc("password".as_bytes(), config).unwrap());
+    }
+
+    #[test]
+    fn argon2_invalid_params() {
+        let inputs = || Inputs {
+            password: "password".as_bytes().to_vec(),
+            salt: Salt::from([1; 16]),
+        };
+        let config = |blocks, passes, lanes| Config {
+            blocks,
+            passes,
+            lanes,
+            ..Default::default()
+        };
+
+        let ret = general(config(8, 0, 1), inputs(), None);
+        assert_eq!(ret.err(), Some(Error::InvalidParams));
+        let ret = general(config(8, 1, 0), inputs(), None);
+        assert_eq!(ret.err(), Some(Error::InvalidParams));
+        let ret = general(config(15, 1, 2), inputs(), None);
+        assert_eq!(ret.err(), Some(Error::InvalidParams));
+    }
+
+    #[test]
+    fn argon2_presets() {
+        let presets = [
+            (Config::interactive(), 64 * 1024, 2),
+            (Config::moderate(), 256 * 1024, 3),
+            (Config::sensitive(), 1024 * 1024, 4),
+        ];
+        for (config, memory_kib, passes) in presets {
+            assert_eq!(config.algorithm, ArgonAlgorithm::Argon2id);
+            assert_eq!(config.memory_kib(), memory_kib);
+            assert_eq!(config.passes, passes);
+            assert_eq!(config.lanes, 1);
+        }
+    }
+
+    #[test]
+    fn argon2_calibrate() {
+        let config = Config::calibrate(Duration::from_millis(20), 64 * 1024).unwrap();
+        assert_eq!(config.algorithm, ArgonAlgorithm::Argon2id);
+        assert_eq!(config.lanes, 1);
+        assert!((8..=64).contains(&config.blocks));
+        assert!(config.passes >= 1);
+
+        let config = Config::calibrate(Duration::ZERO, 1024).unwrap();
+        assert_eq!(config.blocks, 8);
+        assert_eq!(config.passes, 1);
+    }
+
+    #[test]
+    fn workarea_allocator() {
+        struct Short;
+
+        impl Allocator for Short {
+            type Memory = Vec<u64>;
+
+            fn allocate(&self, words: usize) -> Option<Vec<u64>> {
+                Some(vec![0u64; words - 1])
+            }
+        }
+
+        struct Failing;
+
+        impl Allocator for Failing {
+            type Memory = Vec<u64>;
+
+            fn allocate(&self, _: usize) -> Option<Vec<u64>> {
+                None
+            }
+        }
+
+        assert_eq!(WorkArea::new_in(8, &Short).err(), Some(Error::Allocation));
+        assert_eq!(WorkArea::new_in(8, &Failing).err(), Some(Error::Allocation));
+        assert_eq!(WorkArea::new_in(8, &Heap).unwrap().blocks(), 8);
+    }
+
+    #[test]
+    fn workarea_zero() {
+        let wa = WorkArea::new(0);
+        assert!(wa.is_ok());
+    }
 }
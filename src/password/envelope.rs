@@ -0,0 +1,210 @@
+//! Password based encryption
+//!
+//! The key is derived from the password with Argon2 and a random salt, the message is encrypted
+//! with XChaCha20-Poly1305 and a random nonce. The output starts with a header holding the
+//! format version, the Argon2 parameters, the salt and the nonce, followed by the MAC (16 bytes)
+//! and the encrypted message. The header is authenticated as additional data.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::aead::{lock as aead_lock, unlock as aead_unlock};
+use crate::password::argon2::{Argon2Builder, ArgonAlgorithm, Config};
+use crate::password::{Error, Salt};
+
+const VERSION: u8 = 1;
+// Version, algorithm, blocks, passes, lanes, salt and nonce.
+const HEADER_LEN: usize = 1 + 1 + 3 * 4 + 16 + 24;
+const OVERHEAD: usize = HEADER_LEN + 16;
+
+/// Encrypts a message under a password.
+///
+/// Every call derives the key again, the cost is set by `config`.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{ArgonAlgorithm, Config};
+/// use monocypher::password::{lock, unlock};
+///
+/// let config = Config {
+///     algorithm: ArgonAlgorithm::Argon2id,
+///     blocks: 19 * 1024,
+///     passes: 2,
+///     lanes: 1,
+/// };
+/// let locked = lock("plaintext".as_bytes(), "pass".as_bytes(), config).unwrap();
+/// assert_eq!(unlock(&locked, "pass".as_bytes()).unwrap(), "plaintext".as_bytes());
+/// ```
+pub fn lock(plain_text: &[u8], password: &[u8], config: Config) -> Result<Vec<u8>, Error> {
+    let salt = Salt::random()?;
+    let mut nonce = [0u8; 24];
+    crate::random::fill(&mut nonce)?;
+
+    let mut header = Vec::with_capacity(OVERHEAD + plain_text.len());
+    header.push(VERSION);
+    header.push(match config.algorithm {
+        ArgonAlgorithm::Argon2i => 0,
+        ArgonAlgorithm::Argon2d => 1,
+        ArgonAlgorithm::Argon2id => 2,
+    });
+    header.extend_from_slice(&config.blocks.to_le_bytes());
+    header.extend_from_slice(&config.passes.to_le_bytes());
+    header.extend_from_slice(&config.lanes.to_le_bytes());
+    header.extend_from_slice(&*salt);
+    header.extend_from_slice(&nonce);
+
+    let key = Argon2Builder::from(config)
+        .build()?
+        .derive_key(password, &salt)?;
+    let (cipher_text, mac) = aead_lock::aead(plain_text, *key, nonce, &header);
+
+    let mut locked = header;
+    locked.extend_from_slice(&mac);
+    locked.extend_from_slice(&cipher_text);
+    Ok(locked)
+}
+
+/// Decrypts a message encrypted with [`lock`].
+///
+/// The Argon2 parameters are read from the message, a forged message can make this function
+/// allocate as much memory as its header asks for. Use [`unlock_with_limits`] for messages that
+/// do not come from a trusted source.
+pub fn unlock(locked: &[u8], password: &[u8]) -> Result<Vec<u8>, Error> {
+    open(locked, password, None)
+}
+
+/// Decrypts a message encrypted with [`lock`] whose Argon2 parameters are at most the ones of
+/// `max`.
+///
+/// Messages asking for more blocks, passes or lanes than `max` fail with
+/// [`Error::LimitExceeded`] before the key is derived.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{ArgonAlgorithm, Config};
+/// use monocypher::password::{lock, unlock_with_limits, Error};
+///
+/// let config = Config {
+///     algorithm: ArgonAlgorithm::Argon2id,
+///     blocks: 19 * 1024,
+///     passes: 2,
+///     lanes: 1,
+/// };
+/// let locked = lock("plaintext".as_bytes(), "pass".as_bytes(), config).unwrap();
+///
+/// let max = Config {
+///     blocks: 16 * 1024,
+///     ..config
+/// };
+/// let ret = unlock_with_limits(&locked, "pass".as_bytes(), &max);
+/// assert_eq!(ret, Err(Error::LimitExceeded));
+/// ```
+pub fn unlock_with_limits(locked: &[u8], password: &[u8], max: &Config) -> Result<Vec<u8>, Error> {
+    open(locked, password, Some(max))
+}
+
+fn open(locked: &[u8], password: &[u8], max: Option<&Config>) -> Result<Vec<u8>, Error> {
+    if locked.len() < OVERHEAD || locked[0] != VERSION {
+        return Err(Error::Malformed);
+    }
+    let (header, rest) = locked.split_at(HEADER_LEN);
+    let word = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+
+    let config = Config {
+        algorithm: match header[1] {
+            0 => ArgonAlgorithm::Argon2i,
+            1 => ArgonAlgorithm::Argon2d,
+            2 => ArgonAlgorithm::Argon2id,
+            _ => return Err(Error::Malformed),
+        },
+        blocks: word(2),
+        passes: word(6),
+        lanes: word(10),
+    };
+    if let Some(max) = max {
+        config.check_limits(max)?;
+    }
+    let salt = Salt::from_slice(&header[14..30])?;
+    let mut nonce = [0u8; 24];
+    nonce.copy_from_slice(&header[30..]);
+    let mut mac = [0u8; 16];
+    mac.copy_from_slice(&rest[..16]);
+
+    let key = Argon2Builder::from(config)
+        .build()?
+        .derive_key(password, &salt)?;
+    aead_unlock::aead(&rest[16..], *key, nonce, mac, header).map_err(|_| Error::InvalidMac)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: 16,
+            passes: 1,
+            lanes: 1,
+        }
+    }
+
+    #[test]
+    fn lock_unlock() {
+        let locked = lock("secret".as_bytes(), "password".as_bytes(), config()).unwrap();
+        assert_eq!(locked.len(), OVERHEAD + 6);
+        assert_eq!(
+            unlock(&locked, "password".as_bytes()).unwrap(),
+            "secret".as_bytes()
+        );
+
+        let ret = unlock(&locked, "passwort".as_bytes());
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn header_authenticated() {
+        let mut locked = lock("secret".as_bytes(), "password".as_bytes(), config()).unwrap();
+        // Two passes instead of one.
+        locked[6] = 2;
+        let ret = unlock(&locked, "password".as_bytes());
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn limits() {
+        let mut locked = lock("secret".as_bytes(), "password".as_bytes(), config()).unwrap();
+        assert_eq!(
+            unlock_with_limits(&locked, "password".as_bytes(), &config()).unwrap(),
+            "secret".as_bytes()
+        );
+
+        let max = Config {
+            blocks: 8,
+            ..config()
+        };
+        let ret = unlock_with_limits(&locked, "password".as_bytes(), &max);
+        assert_eq!(ret, Err(Error::LimitExceeded));
+
+        // A forged header asking for 4 TiB is rejected before the work area is allocated.
+        locked[2..6].copy_from_slice(&u32::MAX.to_le_bytes());
+        let ret = unlock_with_limits(&locked, "password".as_bytes(), &config());
+        assert_eq!(ret, Err(Error::LimitExceeded));
+    }
+
+    #[test]
+    fn malformed() {
+        let ret = unlock(&[VERSION; OVERHEAD - 1], "password".as_bytes());
+        assert_eq!(ret, Err(Error::Malformed));
+
+        let mut locked = [0u8; OVERHEAD];
+        let ret = unlock(&locked, "password".as_bytes());
+        assert_eq!(ret, Err(Error::Malformed));
+
+        locked[0] = VERSION;
+        locked[1] = 3;
+        let ret = unlock(&locked, "password".as_bytes());
+        assert_eq!(ret, Err(Error::Malformed));
+    }
+}
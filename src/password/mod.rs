@@ -1 +1,48 @@
+//! Password hashing and key derivation
+
+use thiserror::Error;
+
 pub mod argon2;
+#[cfg(feature = "getrandom")]
+mod envelope;
+mod pepper;
+mod phc;
+mod salt;
+#[cfg(feature = "password-hash")]
+mod traits;
+
+#[cfg(feature = "getrandom")]
+pub use envelope::{lock, unlock, unlock_with_limits};
+pub use pepper::{AdditionalData, Pepper};
+pub use salt::Salt;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to allocate needed memory.")]
+    Allocation,
+    #[error("Argon2 parameters are invalid.")]
+    InvalidParams,
+    #[error("Work area is too small for the parameters.")]
+    WorkAreaTooSmall,
+    #[error("Salt must be 16 bytes long.")]
+    InvalidSalt,
+    #[error("Pepper is missing or not hex encoded.")]
+    InvalidPepper,
+    #[error("PHC string is malformed.")]
+    InvalidPhc,
+    #[error("Argon2 parameters exceed the accepted limits.")]
+    LimitExceeded,
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Encrypted message is malformed.")]
+    Malformed,
+    #[error("Wrong password or corrupt message.")]
+    InvalidMac,
+}
+
+#[cfg(feature = "getrandom")]
+impl From<crate::random::Error> for Error {
+    fn from(_: crate::random::Error) -> Self {
+        Error::Random
+    }
+}
@@ -0,0 +1,123 @@
+//! Secret and additional inputs of Argon2
+
+use crate::password::Error;
+use crate::utils;
+use std::ops::Deref;
+use std::path::Path;
+use std::{env, fs, io};
+
+/// A secret key mixed into every password hash, wiped on drop.
+///
+/// Unlike the salt, the pepper is not stored next to the hashes, so a leaked database alone is
+/// not enough to guess the passwords.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::Pepper;
+///
+/// let pepper = Pepper::from(vec![7u8; 32]);
+/// assert_eq!(pepper.len(), 32);
+/// ```
+#[derive(Clone)]
+pub struct Pepper(Vec<u8>);
+
+impl_zeroize!(Pepper);
+
+impl Pepper {
+    /// Reads a hex encoded pepper from the environment variable `name`.
+    pub fn from_env(name: &str) -> Result<Pepper, Error> {
+        let mut encoded = env::var(name).map_err(|_| Error::InvalidPepper)?;
+        let pepper = hex::decode(encoded.trim());
+        unsafe { utils::wipe(encoded.as_bytes_mut()) };
+        pepper.map(Pepper).map_err(|_| Error::InvalidPepper)
+    }
+
+    /// Reads a raw pepper from a file, such as a mounted secret.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Pepper> {
+        fs::read(path).map(Pepper)
+    }
+}
+
+impl From<Vec<u8>> for Pepper {
+    fn from(pepper: Vec<u8>) -> Self {
+        Pepper(pepper)
+    }
+}
+
+impl From<&[u8]> for Pepper {
+    fn from(pepper: &[u8]) -> Self {
+        Pepper(pepper.to_vec())
+    }
+}
+
+impl Deref for Pepper {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for Pepper {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// Public data bound to a password hash, such as a user or application identifier.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdditionalData(Vec<u8>);
+
+impl From<Vec<u8>> for AdditionalData {
+    fn from(data: Vec<u8>) -> Self {
+        AdditionalData(data)
+    }
+}
+
+impl From<&[u8]> for AdditionalData {
+    fn from(data: &[u8]) -> Self {
+        AdditionalData(data.to_vec())
+    }
+}
+
+impl From<&str> for AdditionalData {
+    fn from(data: &str) -> Self {
+        AdditionalData(data.as_bytes().to_vec())
+    }
+}
+
+impl Deref for AdditionalData {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pepper_from_env() {
+        env::set_var("MONOCYPHER_TEST_PEPPER", "0102ff\n");
+        let pepper = Pepper::from_env("MONOCYPHER_TEST_PEPPER").unwrap();
+        assert_eq!(&*pepper, [1, 2, 255]);
+
+        env::set_var("MONOCYPHER_TEST_PEPPER_BAD", "xyz");
+        let ret = Pepper::from_env("MONOCYPHER_TEST_PEPPER_BAD");
+        assert_eq!(ret.err(), Some(Error::InvalidPepper));
+        let ret = Pepper::from_env("MONOCYPHER_TEST_PEPPER_MISSING");
+        assert_eq!(ret.err(), Some(Error::InvalidPepper));
+    }
+
+    #[test]
+    fn pepper_from_file() {
+        let path = env::temp_dir().join(format!("monocypher_test_pepper_{}", std::process::id()));
+        fs::write(&path, [3u8; 32]).unwrap();
+        let pepper = Pepper::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(&*pepper, [3u8; 32]);
+    }
+}
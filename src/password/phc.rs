@@ -0,0 +1,196 @@
+//! PHC string format for Argon2 hashes
+//!
+//! `$argon2id$v=19$m=<blocks>,t=<passes>,p=<lanes>$<salt>$<hash>`, salt and hash are encoded
+//! with the standard base64 alphabet without padding.
+
+use crate::hashing::blake2b;
+use crate::password::argon2::{ArgonAlgorithm, Config};
+use crate::password::Error;
+use crate::utils;
+
+const B64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Argon2 version 1.3, the only one Monocypher implements.
+const VERSION: &str = "v=19";
+
+// An Argon2 hash along with the parameters needed to recompute it.
+pub(crate) struct Phc {
+    pub(crate) config: Config,
+    pub(crate) salt: Vec<u8>,
+    pub(crate) hash: Vec<u8>,
+}
+
+impl Phc {
+    pub(crate) fn parse(phc: &str) -> Result<Phc, Error> {
+        let mut fields = phc.split('$');
+        if fields.next() != Some("") {
+            return Err(Error::InvalidPhc);
+        }
+        let algorithm = match fields.next() {
+            Some("argon2i") => ArgonAlgorithm::Argon2i,
+            Some("argon2d") => ArgonAlgorithm::Argon2d,
+            Some("argon2id") => ArgonAlgorithm::Argon2id,
+            _ => return Err(Error::InvalidPhc),
+        };
+        if fields.next() != Some(VERSION) {
+            return Err(Error::InvalidPhc);
+        }
+
+        let mut params = fields.next().ok_or(Error::InvalidPhc)?.split(',');
+        let mut param = |name: &str| {
+            params
+                .next()
+                .and_then(|param| param.strip_prefix(name))
+                .and_then(|value| value.parse::<u32>().ok())
+                .ok_or(Error::InvalidPhc)
+        };
+        let config = Config {
+            algorithm,
+            blocks: param("m=")?,
+            passes: param("t=")?,
+            lanes: param("p=")?,
+        };
+        if params.next().is_some() {
+            return Err(Error::InvalidPhc);
+        }
+
+        let salt = fields
+            .next()
+            .and_then(b64_decode)
+            .ok_or(Error::InvalidPhc)?;
+        let hash = fields
+            .next()
+            .and_then(b64_decode)
+            .ok_or(Error::InvalidPhc)?;
+        if fields.next().is_some() || salt.len() < 8 || hash.len() < 4 {
+            return Err(Error::InvalidPhc);
+        }
+        Ok(Phc { config, salt, hash })
+    }
+
+    #[cfg_attr(not(feature = "getrandom"), allow(dead_code))]
+    pub(crate) fn format(&self) -> String {
+        let algorithm = match self.config.algorithm {
+            ArgonAlgorithm::Argon2i => "argon2i",
+            ArgonAlgorithm::Argon2d => "argon2d",
+            ArgonAlgorithm::Argon2id => "argon2id",
+        };
+        format!(
+            "${}${}$m={},t={},p={}${}${}",
+            algorithm,
+            VERSION,
+            self.config.blocks,
+            self.config.passes,
+            self.config.lanes,
+            b64_encode(&self.salt),
+            b64_encode(&self.hash)
+        )
+    }
+}
+
+#[cfg_attr(not(feature = "getrandom"), allow(dead_code))]
+pub(crate) fn b64_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let mut bytes = [0u8; 3];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..=chunk.len() {
+            let index = (bits >> (18 - 6 * i)) & 0x3f;
+            encoded.push(B64_ALPHABET[index as usize] as char);
+        }
+    }
+    encoded
+}
+
+// Decodes unpadded base64, rejecting non canonical encodings.
+pub(crate) fn b64_decode(encoded: &str) -> Option<Vec<u8>> {
+    if encoded.len() % 4 == 1 {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for c in encoded.bytes() {
+        let value = B64_ALPHABET.iter().position(|&a| a == c)? as u32;
+        bits = (bits << 6) | value;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            decoded.push((bits >> bit_count) as u8);
+        }
+    }
+    if bits & ((1 << bit_count) - 1) != 0 {
+        return None;
+    }
+    Some(decoded)
+}
+
+// Compares two hashes in constant time, only their lengths may leak. Hashes can have any length,
+// `utils::verify` compares their BLAKE2b digests.
+pub(crate) fn hash_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && utils::verify(&blake2b::easy(a), &blake2b::easy(b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn b64() {
+        for (data, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg"),
+            (b"fo", "Zm8"),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg"),
+        ] {
+            assert_eq!(b64_encode(data), encoded);
+            assert_eq!(b64_decode(encoded).unwrap(), data);
+        }
+        assert!(b64_decode("Zh").is_none());
+        assert!(b64_decode("Z").is_none());
+        assert!(b64_decode("Zm9v=").is_none());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let phc = "$argon2id$v=19$m=65536,t=3,p=4$c2FsdHNhbHRzYWx0c2FsdA$AQEBAQEBAQEBAQEBAQEBAQ";
+        let parsed = Phc::parse(phc).unwrap();
+        assert_eq!(parsed.config.algorithm, ArgonAlgorithm::Argon2id);
+        assert_eq!(parsed.config.blocks, 65536);
+        assert_eq!(parsed.config.passes, 3);
+        assert_eq!(parsed.config.lanes, 4);
+        assert_eq!(parsed.salt, b"saltsaltsaltsalt");
+        assert_eq!(parsed.hash, [1u8; 16]);
+        assert_eq!(parsed.format(), phc);
+    }
+
+    #[test]
+    fn hash_lengths() {
+        for len in [4, 20, 32, 100] {
+            let a = vec![1u8; len];
+            let mut b = a.clone();
+            assert!(hash_eq(&a, &b));
+            b[len - 1] ^= 1;
+            assert!(!hash_eq(&a, &b));
+        }
+        assert!(!hash_eq(&[1u8; 16], &[1u8; 17]));
+    }
+
+    #[test]
+    fn malformed() {
+        for phc in [
+            "",
+            "argon2id$v=19$m=8,t=1,p=1$c2FsdHNhbHRzYWx0c2FsdA$AQEBAQ",
+            "$scrypt$v=19$m=8,t=1,p=1$c2FsdHNhbHRzYWx0c2FsdA$AQEBAQ",
+            "$argon2id$v=16$m=8,t=1,p=1$c2FsdHNhbHRzYWx0c2FsdA$AQEBAQ",
+            "$argon2id$v=19$t=1,m=8,p=1$c2FsdHNhbHRzYWx0c2FsdA$AQEBAQ",
+            "$argon2id$v=19$m=8,t=1,p=1,x=2$c2FsdHNhbHRzYWx0c2FsdA$AQEBAQ",
+            "$argon2id$v=19$m=8,t=1,p=1$c2FsdA$AQEBAQ",
+            "$argon2id$v=19$m=8,t=1,p=1$c2FsdHNhbHRzYWx0c2FsdA$AQEBAQ$",
+        ] {
+            assert!(Phc::parse(phc).is_err(), "{}", phc);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Password salts
+
+use crate::password::{phc, Error};
+use derive_more::From;
+use std::ops::Deref;
+
+/// A 16 byte salt for password hashing.
+///
+/// Every password needs its own salt, generate it with [`Salt::random`] and store it next to
+/// the hash.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::Salt;
+///
+/// let salt = Salt::from_hex("0102030405060708090a0b0c0d0e0f10").unwrap();
+/// assert_eq!(salt[0], 1);
+/// ```
+#[derive(Clone, Copy, Debug, From, PartialEq, Eq)]
+pub struct Salt([u8; 16]);
+
+impl Salt {
+    /// Generates a salt with the operating system's random number generator.
+    ///
+    /// Requires the `getrandom` feature.
+    #[cfg(feature = "getrandom")]
+    pub fn random() -> Result<Salt, Error> {
+        Ok(crate::random::random_salt()?)
+    }
+
+    /// Copies a salt from a slice of exactly 16 bytes.
+    pub fn from_slice(salt: &[u8]) -> Result<Salt, Error> {
+        salt.try_into().map(Salt).map_err(|_| Error::InvalidSalt)
+    }
+
+    /// Parses a hex encoded salt.
+    pub fn from_hex(salt: &str) -> Result<Salt, Error> {
+        let salt = hex::decode(salt).map_err(|_| Error::InvalidSalt)?;
+        Salt::from_slice(&salt)
+    }
+
+    /// Parses a base64 encoded salt with the standard alphabet, padding is optional.
+    pub fn from_base64(salt: &str) -> Result<Salt, Error> {
+        let salt = phc::b64_decode(salt.trim_end_matches('=')).ok_or(Error::InvalidSalt)?;
+        Salt::from_slice(&salt)
+    }
+}
+
+impl Deref for Salt {
+    type Target = [u8; 16];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let salt = Salt::from([1u8; 16]);
+        assert_eq!(Salt::from_slice(&[1u8; 16]), Ok(salt));
+        assert_eq!(Salt::from_hex(&hex::encode([1u8; 16])), Ok(salt));
+        assert_eq!(Salt::from_base64("AQEBAQEBAQEBAQEBAQEBAQ"), Ok(salt));
+        assert_eq!(Salt::from_base64("AQEBAQEBAQEBAQEBAQEBAQ=="), Ok(salt));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Salt::from_slice(&[1u8; 15]), Err(Error::InvalidSalt));
+        assert_eq!(Salt::from_hex("0102"), Err(Error::InvalidSalt));
+        assert_eq!(Salt::from_hex("zz"), Err(Error::InvalidSalt));
+        assert_eq!(Salt::from_base64("AQEB"), Err(Error::InvalidSalt));
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn random() {
+        assert_ne!(Salt::random().unwrap(), Salt::random().unwrap());
+    }
+}
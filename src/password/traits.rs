@@ -0,0 +1,269 @@
+//! [`password_hash`] traits for Monocypher's Argon2.
+
+use crate::password::argon2::{self, ArgonAlgorithm, Config, WorkArea};
+use crate::password::Error;
+use ::password_hash::errors::InvalidValue;
+use ::password_hash::{Decimal, Ident, Output, ParamsString, PasswordHash, PasswordHasher, Salt};
+
+const ARGON2I: Ident<'static> = Ident::new_unwrap("argon2i");
+const ARGON2D: Ident<'static> = Ident::new_unwrap("argon2d");
+const ARGON2ID: Ident<'static> = Ident::new_unwrap("argon2id");
+
+// The only Argon2 version Monocypher implements, 0x13.
+const VERSION: Decimal = 19;
+
+// Argon2 requires salts of at least 8 bytes.
+const MIN_SALT_LEN: usize = 8;
+
+/// Argon2 password hasher backed by Monocypher.
+///
+/// [`PasswordVerifier`](::password_hash::PasswordVerifier) is implemented through
+/// [`PasswordHasher`], it reads the algorithm and the parameters from the hash it checks.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::password::argon2::{Argon2, ArgonAlgorithm, Config};
+/// use password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+///
+/// let argon2 = Argon2::new(Config {
+///     algorithm: ArgonAlgorithm::Argon2id,
+///     blocks: 19 * 1024,
+///     passes: 2,
+///     lanes: 1,
+/// });
+/// let salt = SaltString::encode_b64(&[1u8; 16]).unwrap();
+/// let phc = argon2.hash_password("pass".as_bytes(), &salt).unwrap().to_string();
+///
+/// let hash = PasswordHash::new(&phc).unwrap();
+/// argon2.verify_password("pass".as_bytes(), &hash).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Argon2 {
+    algorithm: ArgonAlgorithm,
+    params: Params,
+}
+
+impl Argon2 {
+    /// Creates a hasher producing 32 byte hashes with the given configuration.
+    pub fn new(config: Config) -> Argon2 {
+        Argon2 {
+            algorithm: config.algorithm,
+            params: Params {
+                blocks: config.blocks,
+                passes: config.passes,
+                lanes: config.lanes,
+                output_len: Params::DEFAULT_OUTPUT_LEN,
+            },
+        }
+    }
+}
+
+impl From<Config> for Argon2 {
+    fn from(config: Config) -> Self {
+        Argon2::new(config)
+    }
+}
+
+/// Argon2 parameters as stored in a PHC string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Params {
+    /// Memory in blocks of 1 KiB, the `m` parameter.
+    pub blocks: u32,
+    /// Number of passes over the memory, the `t` parameter.
+    pub passes: u32,
+    /// Number of lanes, the `p` parameter.
+    pub lanes: u32,
+    /// Length of the hash in bytes.
+    pub output_len: usize,
+}
+
+impl Params {
+    /// Length of the hashes produced by default.
+    pub const DEFAULT_OUTPUT_LEN: usize = 32;
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        let config = Config::default();
+        Params {
+            blocks: config.blocks,
+            passes: config.passes,
+            lanes: config.lanes,
+            output_len: Params::DEFAULT_OUTPUT_LEN,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a PasswordHash<'a>> for Params {
+    type Error = ::password_hash::Error;
+
+    fn try_from(hash: &'a PasswordHash<'a>) -> Result<Self, Self::Error> {
+        let param = |name| {
+            hash.params
+                .get_decimal(name)
+                .ok_or(::password_hash::Error::ParamValueInvalid(
+                    InvalidValue::Malformed,
+                ))
+        };
+        Ok(Params {
+            blocks: param("m")?,
+            passes: param("t")?,
+            lanes: param("p")?,
+            output_len: hash
+                .hash
+                .map_or(Params::DEFAULT_OUTPUT_LEN, |output| output.len()),
+        })
+    }
+}
+
+impl TryFrom<Params> for ParamsString {
+    type Error = ::password_hash::Error;
+
+    fn try_from(params: Params) -> Result<Self, Self::Error> {
+        let mut string = ParamsString::new();
+        string.add_decimal("m", params.blocks)?;
+        string.add_decimal("t", params.passes)?;
+        string.add_decimal("p", params.lanes)?;
+        Ok(string)
+    }
+}
+
+impl PasswordHasher for Argon2 {
+    type Params = Params;
+
+    fn hash_password<'a>(
+        &self,
+        password: &[u8],
+        salt: impl Into<Salt<'a>>,
+    ) -> ::password_hash::Result<PasswordHash<'a>> {
+        self.hash_password_customized(password, None, None, self.params, salt)
+    }
+
+    fn hash_password_customized<'a>(
+        &self,
+        password: &[u8],
+        algorithm: Option<Ident<'a>>,
+        version: Option<Decimal>,
+        params: Params,
+        salt: impl Into<Salt<'a>>,
+    ) -> ::password_hash::Result<PasswordHash<'a>> {
+        let algorithm = match algorithm {
+            None => self.algorithm,
+            Some(ident) if ident == ARGON2I => ArgonAlgorithm::Argon2i,
+            Some(ident) if ident == ARGON2D => ArgonAlgorithm::Argon2d,
+            Some(ident) if ident == ARGON2ID => ArgonAlgorithm::Argon2id,
+            Some(_) => return Err(::password_hash::Error::Algorithm),
+        };
+        if version.unwrap_or(VERSION) != VERSION {
+            return Err(::password_hash::Error::Version);
+        }
+
+        let salt = salt.into();
+        let mut salt_buf = [0u8; Salt::MAX_LENGTH];
+        let salt_bytes = salt.decode_b64(&mut salt_buf)?;
+        if salt_bytes.len() < MIN_SALT_LEN {
+            return Err(::password_hash::Error::SaltInvalid(InvalidValue::TooShort));
+        }
+
+        let config = Config {
+            algorithm,
+            blocks: params.blocks,
+            passes: params.passes,
+            lanes: params.lanes,
+        };
+        let output = Output::init_with(params.output_len, |out| {
+            let mut work_area = WorkArea::new(config.blocks).map_err(hash_error)?;
+            argon2::derive_into(
+                &mut work_area,
+                config.into(),
+                password,
+                salt_bytes,
+                argon2::NO_EXTRAS,
+                out,
+            )
+            .map_err(hash_error)
+        })?;
+
+        Ok(PasswordHash {
+            algorithm: match algorithm {
+                ArgonAlgorithm::Argon2i => ARGON2I,
+                ArgonAlgorithm::Argon2d => ARGON2D,
+                ArgonAlgorithm::Argon2id => ARGON2ID,
+            },
+            version: Some(VERSION),
+            params: params.try_into()?,
+            salt: Some(salt),
+            hash: Some(output),
+        })
+    }
+}
+
+fn hash_error(err: Error) -> ::password_hash::Error {
+    match err {
+        Error::InvalidParams => ::password_hash::Error::ParamValueInvalid(InvalidValue::Malformed),
+        _ => ::password_hash::Error::Crypto,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::password_hash::{PasswordVerifier, SaltString};
+
+    #[test]
+    fn verify() {
+        let phc = "$argon2i$v=19$m=100000,t=3,p=1$AQEBAQEBAQEBAQEBAQEBAQ$\
+                   mYLIw+rayhakE9LAocjoKKuubk146Xa89cIH1EsX27Q";
+        let hash = PasswordHash::new(phc).unwrap();
+
+        let argon2 = Argon2::default();
+        assert!(argon2.verify_password("password".as_bytes(), &hash).is_ok());
+        assert_eq!(
+            argon2.verify_password("passwort".as_bytes(), &hash),
+            Err(::password_hash::Error::Password)
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let argon2 = Argon2::new(Config {
+            algorithm: ArgonAlgorithm::Argon2id,
+            blocks: 16,
+            passes: 1,
+            lanes: 2,
+        });
+        let salt = SaltString::encode_b64(&[2u8; 16]).unwrap();
+        let phc = argon2
+            .hash_password("password".as_bytes(), &salt)
+            .unwrap()
+            .to_string();
+        assert!(phc.starts_with("$argon2id$v=19$m=16,t=1,p=2$"));
+        assert_eq!(
+            crate::password::argon2::verify_phc("password".as_bytes(), &phc),
+            Ok(true)
+        );
+
+        let hash = PasswordHash::new(&phc).unwrap();
+        assert!(Argon2::default()
+            .verify_password("password".as_bytes(), &hash)
+            .is_ok());
+    }
+
+    #[test]
+    fn rejected() {
+        let argon2 = Argon2::default();
+        let short = SaltString::encode_b64(&[2u8; 4]).unwrap();
+        assert!(argon2.hash_password("password".as_bytes(), &short).is_err());
+
+        let salt = SaltString::encode_b64(&[2u8; 16]).unwrap();
+        let ret = argon2.hash_password_customized(
+            "password".as_bytes(),
+            Some(Ident::new_unwrap("scrypt")),
+            None,
+            Params::default(),
+            &salt,
+        );
+        assert_eq!(ret, Err(::password_hash::Error::Algorithm));
+    }
+}
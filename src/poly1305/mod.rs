@@ -0,0 +1,286 @@
+//! Poly1305 is a one-time message authentication code.
+//!
+//! [Official documentation](https://monocypher.org/manual/advanced/poly1305)
+
+use crate::mac::Tag;
+use crate::utils;
+use monocypher_sys as ffi;
+use std::io::{self, Write};
+use std::mem;
+
+#[cfg(feature = "universal-hash")]
+mod traits;
+
+/// Produces a message authentication code for the given message and authentication key.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::poly1305;
+///
+/// let key = [1u8; 32];
+/// let mac = poly1305::auth("test".as_bytes(), key);
+///
+/// ```
+pub fn auth(message: &[u8], key: [u8; 32]) -> Tag<16> {
+    unsafe {
+        let mut mac = mem::MaybeUninit::<[u8; 16]>::uninit();
+        ffi::crypto_poly1305(
+            mac.as_mut_ptr() as *mut u8,
+            message.as_ptr(),
+            message.len(),
+            key.as_ptr(),
+        );
+        Tag::from(mac.assume_init())
+    }
+}
+
+/// Checks in constant time that `expected` is the authentication code of the message.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::poly1305;
+///
+/// let key = [1u8; 32];
+/// let mac = poly1305::auth("test".as_bytes(), key);
+/// assert!(poly1305::verify("test".as_bytes(), key, &mac));
+/// ```
+pub fn verify(message: &[u8], key: [u8; 32], expected: &[u8; 16]) -> bool {
+    let mac = auth(message, key);
+    unsafe { ffi::crypto_verify16(mac.as_ptr(), expected.as_ptr()) == 0 }
+}
+
+/// A Poly1305 key that authenticates a single message, wiped on drop.
+///
+/// Poly1305 keys must never be reused: two codes made with the same key reveal it and allow
+/// forgeries. Every way of using the key consumes it, so a second use does not compile.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::poly1305::OneTimeKey;
+///
+/// let key = OneTimeKey::from([1u8; 32]);
+/// let mac = key.auth("test".as_bytes());
+/// ```
+///
+/// ```compile_fail
+/// use monocypher::poly1305::OneTimeKey;
+///
+/// let key = OneTimeKey::from([1u8; 32]);
+/// let mac = key.auth("test".as_bytes());
+/// let other = key.auth("other".as_bytes());
+/// ```
+pub struct OneTimeKey([u8; 32]);
+
+impl_zeroize!(OneTimeKey);
+
+impl OneTimeKey {
+    /// Authenticates a message in one go.
+    pub fn auth(self, message: &[u8]) -> Tag<16> {
+        let mut ctx = self.into_context();
+        ctx.update(message);
+        ctx.finalize()
+    }
+
+    /// Checks in constant time that `expected` is the authentication code of the message.
+    pub fn verify(self, message: &[u8], expected: &[u8; 16]) -> bool {
+        let mac = self.auth(message);
+        unsafe { ffi::crypto_verify16(mac.as_ptr(), expected.as_ptr()) == 0 }
+    }
+
+    /// Starts authenticating a message chunk by chunk.
+    pub fn into_context(self) -> Context {
+        Context::init(&self.0)
+    }
+}
+
+impl From<[u8; 32]> for OneTimeKey {
+    fn from(key: [u8; 32]) -> Self {
+        OneTimeKey(key)
+    }
+}
+
+impl Drop for OneTimeKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
+/// Encodes the lengths of the additional data and the cipher text as the last block of
+/// ChaCha20-Poly1305, as two little endian 64 bit numbers.
+pub fn lengths_block(ad_len: u64, cipher_text_len: u64) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[..8].copy_from_slice(&ad_len.to_le_bytes());
+    block[8..].copy_from_slice(&cipher_text_len.to_le_bytes());
+    block
+}
+
+pub struct Context(ffi::crypto_poly1305_ctx);
+
+impl Context {
+    /// Initializes a new context with the given key.
+    ///
+    /// The key must not be used for another message, [`OneTimeKey`] enforces that.
+    #[inline]
+    pub fn new(key: [u8; 32]) -> Context {
+        Context::init(&key)
+    }
+
+    fn init(key: &[u8; 32]) -> Context {
+        unsafe {
+            let mut ctx = mem::MaybeUninit::<ffi::crypto_poly1305_ctx>::uninit();
+            ffi::crypto_poly1305_init(ctx.as_mut_ptr(), key.as_ptr());
+            Context(ctx.assume_init())
+        }
+    }
+
+    /// Authenticates the message chunk by chunk.
+    #[inline]
+    pub fn update(&mut self, message: &[u8]) {
+        unsafe {
+            ffi::crypto_poly1305_update(&mut self.0, message.as_ptr(), message.len());
+        }
+    }
+
+    /// Feeds the chunk followed by zeros up to a multiple of 16 bytes.
+    ///
+    /// This is the padding ChaCha20-Poly1305 (RFC 8439) applies to the additional data and to the
+    /// cipher text. The padding only depends on the length of the chunk, so everything fed
+    /// before must be a multiple of 16 bytes, as it is after a previous padded chunk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::poly1305::{lengths_block, Context};
+    ///
+    /// let (ad, cipher_text) = ("header".as_bytes(), "encrypted".as_bytes());
+    /// let mut ctx = Context::new([1u8; 32]);
+    /// ctx.update_padded16(ad);
+    /// ctx.update_padded16(cipher_text);
+    /// ctx.update(&lengths_block(ad.len() as u64, cipher_text.len() as u64));
+    /// let mac = ctx.finalize();
+    /// ```
+    pub fn update_padded16(&mut self, chunk: &[u8]) {
+        self.update(chunk);
+        self.update(&[0u8; 16][..(16 - chunk.len() % 16) % 16]);
+    }
+
+    /// Produces the message authentication code.
+    #[inline]
+    pub fn finalize(&mut self) -> Tag<16> {
+        unsafe {
+            let mut mac = mem::MaybeUninit::<[u8; 16]>::uninit();
+            ffi::crypto_poly1305_final(&mut self.0, mac.as_mut_ptr() as *mut u8);
+            Tag::from(mac.assume_init())
+        }
+    }
+}
+
+/// Feeds written data into the authentication code, so readers can be authenticated with
+/// [`io::copy`].
+///
+/// # Example
+///
+/// ```
+/// use monocypher::poly1305::{self, Context};
+/// use std::io;
+///
+/// let key = [1u8; 32];
+/// let mut ctx = Context::new(key);
+/// io::copy(&mut "test".as_bytes(), &mut ctx).unwrap();
+/// assert!(poly1305::verify("test".as_bytes(), key, &ctx.finalize()));
+/// ```
+impl Write for Context {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::poly1305;
+
+    use super::*;
+
+    #[test]
+    fn auth() {
+        let key = [1u8; 32];
+        let mac = poly1305::auth("test".as_bytes(), key);
+        assert_eq!(
+            mac,
+            [20, 62, 33, 196, 79, 94, 80, 79, 78, 94, 80, 79, 78, 94, 80, 79]
+        )
+    }
+
+    #[test]
+    fn verify() {
+        let key = [1u8; 32];
+        let mut mac = *poly1305::auth("test".as_bytes(), key);
+        assert!(poly1305::verify("test".as_bytes(), key, &mac));
+        assert!(!poly1305::verify("tent".as_bytes(), key, &mac));
+        mac[15] ^= 1;
+        assert!(!poly1305::verify("test".as_bytes(), key, &mac));
+    }
+
+    #[test]
+    fn write() {
+        let key = [2u8; 32];
+        let mut ctx = Context::new(key);
+        ctx.write_all(&[7u8; 100]).unwrap();
+        io::copy(&mut &[8u8; 1000][..], &mut ctx).unwrap();
+
+        let mut message = vec![7u8; 100];
+        message.extend_from_slice(&[8u8; 1000]);
+        assert_eq!(ctx.finalize(), poly1305::auth(&message, key));
+    }
+
+    #[test]
+    fn one_time_key() {
+        let mac = OneTimeKey::from([1u8; 32]).auth("test".as_bytes());
+        assert_eq!(mac, poly1305::auth("test".as_bytes(), [1u8; 32]));
+        assert!(OneTimeKey::from([1u8; 32]).verify("test".as_bytes(), &mac));
+        assert!(!OneTimeKey::from([2u8; 32]).verify("test".as_bytes(), &mac));
+
+        let mut ctx = OneTimeKey::from([1u8; 32]).into_context();
+        ctx.update("te".as_bytes());
+        ctx.update("st".as_bytes());
+        assert_eq!(ctx.finalize(), mac);
+    }
+
+    #[test]
+    fn padded() {
+        let key = [2u8; 32];
+        let mut ctx = Context::new(key);
+        ctx.update_padded16(&[7u8; 3]);
+        ctx.update_padded16(&[8u8; 16]);
+        ctx.update_padded16(&[]);
+        ctx.update(&lengths_block(3, 16));
+
+        let mut message = [0u8; 48];
+        message[..3].fill(7);
+        message[16..32].fill(8);
+        message[32] = 3;
+        message[40] = 16;
+        assert_eq!(ctx.finalize(), poly1305::auth(&message, key));
+    }
+
+    #[test]
+    fn ctx() {
+        let key = [2u8; 32];
+        let mut ctx = Context::new(key);
+        ctx.update("test".as_bytes());
+        let mac = ctx.finalize();
+
+        assert_eq!(
+            mac,
+            [40, 124, 66, 136, 159, 188, 160, 158, 156, 188, 160, 158, 156, 188, 160, 158]
+        )
+    }
+}
@@ -0,0 +1,67 @@
+//! [`universal_hash`] traits for the Poly1305 context.
+
+use crate::poly1305::Context;
+use ::universal_hash::consts::{U1, U16, U32};
+use ::universal_hash::crypto_common::{BlockSizeUser, KeySizeUser, ParBlocksSizeUser};
+use ::universal_hash::{Block, Key, KeyInit, UhfBackend, UhfClosure, UniversalHash};
+
+impl KeySizeUser for Context {
+    type KeySize = U32;
+}
+
+impl KeyInit for Context {
+    fn new(key: &Key<Self>) -> Self {
+        Context::new((*key).into())
+    }
+}
+
+impl BlockSizeUser for Context {
+    type BlockSize = U16;
+}
+
+impl ParBlocksSizeUser for Context {
+    type ParBlocksSize = U1;
+}
+
+impl UhfBackend for Context {
+    fn proc_block(&mut self, block: &Block<Self>) {
+        Context::update(self, block);
+    }
+}
+
+impl UniversalHash for Context {
+    fn update_with_backend(&mut self, f: impl UhfClosure<BlockSize = Self::BlockSize>) {
+        f.call(self);
+    }
+
+    fn finalize(mut self) -> Block<Self> {
+        (*Context::finalize(&mut self)).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::poly1305;
+
+    #[test]
+    fn same_as_auth() {
+        let key = [2u8; 32];
+        let mut mac = <Context as KeyInit>::new(&key.into());
+        mac.update_padded(b"a message longer than one block");
+        let tag = UniversalHash::finalize(mac);
+
+        let mut padded = b"a message longer than one block".to_vec();
+        padded.resize(32, 0);
+        assert_eq!(tag.as_slice(), &*poly1305::auth(&padded, key));
+    }
+
+    #[test]
+    fn verify() {
+        let key = [2u8; 32];
+        let tag = poly1305::auth(&[7u8; 32], key);
+        let mut mac = <Context as KeyInit>::new(&key.into());
+        UniversalHash::update(&mut mac, &[[7u8; 16].into(), [7u8; 16].into()]);
+        assert!(mac.verify(&(*tag).into()).is_ok());
+    }
+}
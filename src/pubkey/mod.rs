@@ -1,46 +1,179 @@
 use derive_more::From;
 use monocypher_sys as ffi;
-use std::mem;
+use std::io::{self, Read};
+use std::ops::Deref;
 
-use crate::{Error, KeyPair, PrivKey, PubKey, PubPrivKey, Seed, Signature};
+use crate::advanced::eddsa;
+use crate::fingerprint::Fingerprint;
+use crate::hashing::blake2b;
+use crate::signing::{Blake2bSigningKey, Blake2bVerifyingKey};
+use crate::{utils, Error, KeyPair, PrivKey, PubKey, PubPrivKey, Seed, Signature};
 
-#[derive(Debug, From)]
+pub mod sigfile;
+mod stream;
+
+pub use stream::{SigningStream, VerifyingStream};
+
+/// The seed followed by the public key.
+///
+/// A private key is generated with [`KeyPair::generate_key_pair`] or decoded with a check that
+/// the public half belongs to the seed. Signing with a wrong public half would leak the secret
+/// scalar, so there is no unchecked conversion from bytes:
+///
+/// ```compile_fail
+/// let private_key = monocypher::pubkey::PrivateKey::from([0u8; 64]);
+/// ```
+#[derive(Debug)]
 pub struct PrivateKey([u8; 64]);
 
+impl_bytes!(
+    PrivateKey,
+    64,
+    crate::signing::check_key_pair::<blake2b::Context>
+);
+impl_ct_eq!(PrivateKey);
+impl_zeroize!(PrivateKey);
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.0);
+    }
+}
+
 #[derive(Debug, From)]
 pub struct PublicKey([u8; 32]);
 
+impl_bytes!(PublicKey, 32);
+impl_ct_eq!(PublicKey);
+
+impl Deref for PublicKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl PrivateKey {
+    /// Computes the public key from the seed in the first half of the private key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::pubkey;
+    /// use monocypher::{KeyPair, PubPrivKey, Seed};
+    ///
+    /// let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+    ///     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+    /// assert_eq!(*keypair.private_key.public_key(), *keypair.public_key);
+    /// ```
+    pub fn public_key(&self) -> PublicKey {
+        let mut hash = blake2b::easy(&self.0[..32]);
+        let mut scalar = eddsa::trim_scalar(hash[..32].try_into().unwrap());
+        let public_key = eddsa::scalarbase(&scalar);
+        utils::wipe(&mut hash);
+        utils::wipe(&mut scalar);
+        PublicKey(public_key)
+    }
+}
+
+impl PublicKey {
+    /// Returns the fingerprint of the key, to compare it manually.
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new("eddsa-blake2b", &self.0)
+    }
+
+    /// Checks a signature like [`PubKey::check`], but also rejects malleable encodings.
+    ///
+    /// Monocypher's check follows RFC 8032 loosely: it rejects signatures whose `S` is not below
+    /// the group order L and whose `R` differs from the canonical encoding of `[S]B - [k]A`, but
+    /// accepts public keys with a non canonical y coordinate and public keys or `R` points of
+    /// small order. A signature by such a key can be valid for more than one message or key.
+    ///
+    /// This check additionally rejects:
+    ///
+    /// - public keys and `R` points that are not canonically encoded,
+    /// - public keys and `R` points of order 1, 2, 4 or 8.
+    ///
+    /// The equation itself stays the cofactorless one, so every signature accepted here is
+    /// accepted by [`PubKey::check`] too. Honestly generated keys and signatures always pass.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::pubkey;
+    /// use monocypher::{KeyPair, PubPrivKey, Seed};
+    ///
+    /// let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+    ///     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+    /// let signature = keypair.sign("message".as_bytes());
+    /// keypair.public_key.check_strict(signature, "message".as_bytes()).unwrap();
+    /// ```
+    pub fn check_strict(&self, signature: Signature, message: &[u8]) -> Result<(), Error> {
+        Blake2bVerifyingKey::from(self.0).check_strict(signature, message)
+    }
+
+    /// Checks a signature of the concatenation of the parts.
+    pub fn check_parts(&self, signature: Signature, parts: &[&[u8]]) -> Result<(), Error> {
+        Blake2bVerifyingKey::from(self.0).check_parts(signature, parts)
+    }
+
+    /// Checks a signature of a message read to the end of `reader`.
+    ///
+    /// The message is read once in chunks, so the reader does not need to be seekable and the
+    /// message does not need to fit in memory. Read errors are returned as [`Error::Io`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::pubkey;
+    /// use monocypher::{KeyPair, PubPrivKey, Seed};
+    ///
+    /// let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+    ///     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+    /// let image = vec![7u8; 1 << 20];
+    /// let signature = keypair.sign(&image);
+    ///
+    /// keypair.public_key.check_reader(signature, &image[..]).unwrap();
+    /// ```
+    pub fn check_reader<R: Read>(&self, signature: Signature, mut reader: R) -> Result<(), Error> {
+        let mut verifier = VerifyingStream::new(self, &signature);
+        io::copy(&mut reader, &mut verifier)?;
+        verifier.finish()
+    }
+}
+
+impl PrivateKey {
+    /// Signs the concatenation of the parts without copying them into one buffer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use monocypher::pubkey;
+    /// use monocypher::{KeyPair, PubPrivKey, Seed};
+    ///
+    /// let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+    ///     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+    /// let parts: [&[u8]; 3] = [b"header", b"body", b"trailer"];
+    ///
+    /// let signature = keypair.private_key.sign_parts(&parts);
+    /// keypair.check(signature, b"headerbodytrailer").unwrap();
+    /// ```
+    pub fn sign_parts(&self, parts: &[&[u8]]) -> Signature {
+        Blake2bSigningKey::from(self).sign_parts(parts)
+    }
+}
+
 impl PrivKey for PrivateKey {
     /// Signs a message with the secret_key.
     fn sign(&self, message: &[u8]) -> Signature {
-        unsafe {
-            let mut signature = mem::MaybeUninit::<[u8; 64]>::uninit();
-            ffi::crypto_eddsa_sign(
-                signature.as_mut_ptr() as *mut u8,
-                self.0.as_ptr(),
-                message.as_ptr(),
-                message.len(),
-            );
-
-            Signature::from(signature.assume_init())
-        }
+        Blake2bSigningKey::from(self).sign(message)
     }
 }
 
 impl PubKey for PublicKey {
     fn check(&self, signature: Signature, message: &[u8]) -> Result<(), Error> {
-        unsafe {
-            if ffi::crypto_eddsa_check(
-                signature.as_ptr(),
-                self.0.as_ptr(),
-                message.as_ptr(),
-                message.len(),
-            ) == 0
-            {
-                return Ok(());
-            }
-            Err(Error::Signature)
-        }
+        Blake2bVerifyingKey::from(self.0).check(signature, message)
     }
 }
 
@@ -59,7 +192,7 @@ impl PubPrivKey for KeyPair<PrivateKey, PublicKey> {
         }
 
         Self {
-            private_key: PrivateKey::from(secret_key),
+            private_key: PrivateKey(secret_key),
             public_key: PublicKey::from(public_key),
         }
     }
@@ -68,7 +201,7 @@ impl PubPrivKey for KeyPair<PrivateKey, PublicKey> {
 #[cfg(test)]
 mod test {
     use crate::pubkey::{self, KeyPair};
-    use crate::{PrivKey, PubPrivKey, Seed};
+    use crate::{Error, PrivKey, PubKey, PubPrivKey, Seed, Signature};
 
     #[test]
     fn sign_test() {
@@ -93,6 +226,112 @@ mod test {
         )
     }
 
+    #[test]
+    fn public_key() {
+        let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([0; 32]));
+
+        assert_eq!(*keypair.private_key.public_key(), *keypair.public_key);
+    }
+
+    #[test]
+    fn bytes() {
+        let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([0; 32]));
+        let sig = keypair.sign("test".as_bytes());
+
+        let public_key = pubkey::PublicKey::try_from(keypair.public_key.as_ref()).unwrap();
+        assert_eq!(public_key.to_bytes(), *keypair.public_key);
+        let private_key = pubkey::PrivateKey::from_hex(&keypair.private_key.to_hex()).unwrap();
+        assert_eq!(private_key.as_bytes(), keypair.private_key.as_bytes());
+        let sig = Signature::from_hex(&sig.to_hex()).unwrap();
+        assert!(public_key.check(sig, "test".as_bytes()).is_ok());
+
+        let ret = pubkey::PublicKey::try_from(&[0u8; 31][..]);
+        assert!(matches!(ret, Err(Error::Encoding)));
+        let mut mismatch = keypair.private_key.to_bytes();
+        mismatch[63] ^= 1;
+        let ret = pubkey::PrivateKey::try_from(&mismatch[..]);
+        assert!(matches!(ret, Err(Error::KeyMismatch)));
+        let ret = pubkey::PrivateKey::from_hex(&hex::encode(mismatch));
+        assert!(matches!(ret, Err(Error::KeyMismatch)));
+
+        // The seed with the public key of another key pair.
+        let other: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([1; 32]));
+        let mut foreign = keypair.private_key.to_bytes();
+        foreign[32..].copy_from_slice(other.public_key.as_bytes());
+        let ret = pubkey::PrivateKey::try_from(&foreign[..]);
+        assert!(matches!(ret, Err(Error::KeyMismatch)));
+        let ret = Signature::from_hex("zz");
+        assert!(matches!(ret, Err(Error::Encoding)));
+    }
+
+    #[test]
+    fn constant_time_eq() {
+        let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([0; 32]));
+        let sig = keypair.sign("test".as_bytes());
+
+        assert_eq!(keypair.private_key.public_key(), keypair.public_key);
+        assert_eq!(sig, keypair.sign("test".as_bytes()));
+        assert_ne!(sig, keypair.sign("not_test".as_bytes()));
+        #[cfg(feature = "subtle")]
+        {
+            use subtle::ConstantTimeEq;
+            assert!(bool::from(sig.ct_eq(&keypair.sign("test".as_bytes()))));
+        }
+    }
+
+    #[test]
+    fn check_strict() {
+        let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([0; 32]));
+        let sig = keypair.sign("test".as_bytes());
+        assert!(keypair
+            .public_key
+            .check_strict(sig, "test".as_bytes())
+            .is_ok());
+
+        // The neutral element as key and R with S = 0 is valid for every message.
+        let mut neutral = [0u8; 32];
+        neutral[0] = 1;
+        let public_key = pubkey::PublicKey::from(neutral);
+        let mut forged = [0u8; 64];
+        forged[0] = 1;
+        assert!(public_key
+            .check(Signature::from(forged), "test".as_bytes())
+            .is_ok());
+        let ret = public_key.check_strict(Signature::from(forged), "test".as_bytes());
+        assert!(matches!(ret, Err(Error::Signature)));
+    }
+
+    #[test]
+    fn check_reader() {
+        let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([0; 32]));
+        let message = vec![7u8; 100_000];
+        let sig = keypair.sign(&message);
+        assert!(keypair.public_key.check_reader(sig, &message[..]).is_ok());
+
+        let sig = keypair.sign(&message);
+        let ret = keypair.public_key.check_reader(sig, &message[1..]);
+        assert!(matches!(ret, Err(Error::Signature)));
+    }
+
+    #[test]
+    fn parts() {
+        let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([0; 32]));
+        let parts: [&[u8]; 3] = [b"head", b"", b"er"];
+        let sig = keypair.private_key.sign_parts(&parts);
+        assert_eq!(sig, keypair.sign(b"header"));
+
+        assert!(keypair.public_key.check_parts(sig, &parts).is_ok());
+        let sig = keypair.sign(b"header");
+        assert!(keypair.public_key.check_parts(sig, &[b"head"]).is_err());
+    }
+
     #[test]
     fn check_valid() {
         let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
@@ -0,0 +1,219 @@
+//! Detached signatures of files
+//!
+//! A signature file names the algorithm and the signer along with the signature, so that a
+//! verifier can tell which key to check it with. It has the following layout:
+//!
+//! | Size | Content                                            |
+//! |------|----------------------------------------------------|
+//! | 5    | Magic bytes `mcsig`                                |
+//! | 1    | Version, currently 2                               |
+//! | 1    | Algorithm, 1 for EdDSA with BLAKE2b                |
+//! | 16   | [Fingerprint](crate::fingerprint) of the signer    |
+//! | 64   | Signature of the above fields and the file content |
+//!
+//! The header fields are signed along with the file, so the algorithm and signer cannot be
+//! swapped without invalidating the signature. Files are signed and checked with
+//! [`SigningStream`] and [`VerifyingStream`], they do not have to fit in memory.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use monocypher::pubkey::{self, sigfile};
+//! use monocypher::{KeyPair, PubPrivKey, Seed};
+//! use std::fs;
+//!
+//! let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+//!     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+//!
+//! let sig = sigfile::sign_file("release.tar.gz", &keypair.private_key).unwrap();
+//! fs::write("release.tar.gz.sig", sig.to_bytes()).unwrap();
+//!
+//! let sig = sigfile::SigFile::from_bytes(&fs::read("release.tar.gz.sig").unwrap()).unwrap();
+//! sigfile::verify_file("release.tar.gz", &sig, &keypair.public_key).unwrap();
+//! ```
+
+use crate::fingerprint::{Fingerprint, FINGERPRINT_LEN};
+use crate::pubkey::{PrivateKey, PublicKey, SigningStream, VerifyingStream};
+use crate::Signature;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use thiserror::Error;
+
+const MAGIC: &[u8; 5] = b"mcsig";
+const VERSION: u8 = 2;
+const ALGORITHM_EDDSA_BLAKE2B: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 2 + FINGERPRINT_LEN;
+
+/// Size of an encoded signature file.
+pub const SIGFILE_LEN: usize = HEADER_LEN + 64;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read the signed file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Signature file is malformed.")]
+    Malformed,
+    #[error("Signature file uses an unsupported version or algorithm.")]
+    Unsupported,
+    #[error("Signature file was made by another key.")]
+    Signer,
+    #[error("Signature check failed!")]
+    Signature,
+}
+
+/// A decoded signature file.
+#[derive(Debug)]
+pub struct SigFile {
+    /// Fingerprint of the signer's public key.
+    pub fingerprint: Fingerprint,
+    /// Signature of the file content.
+    pub signature: Signature,
+}
+
+impl SigFile {
+    /// Encodes the signature file.
+    pub fn to_bytes(&self) -> [u8; SIGFILE_LEN] {
+        let mut bytes = [0u8; SIGFILE_LEN];
+        bytes[..HEADER_LEN].copy_from_slice(&header(&self.fingerprint));
+        bytes[HEADER_LEN..].copy_from_slice(&*self.signature);
+        bytes
+    }
+
+    /// Decodes a signature file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SigFile, Error> {
+        if bytes.len() != SIGFILE_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(Error::Malformed);
+        }
+        if bytes[MAGIC.len()] != VERSION || bytes[MAGIC.len() + 1] != ALGORITHM_EDDSA_BLAKE2B {
+            return Err(Error::Unsupported);
+        }
+        let (fingerprint, signature) = bytes[MAGIC.len() + 2..].split_at(FINGERPRINT_LEN);
+        Ok(SigFile {
+            fingerprint: Fingerprint::from(<[u8; FINGERPRINT_LEN]>::try_from(fingerprint).unwrap()),
+            signature: Signature::from(<[u8; 64]>::try_from(signature).unwrap()),
+        })
+    }
+}
+
+/// Signs the content of a file.
+///
+/// The file is read twice. Fails with an [`Error::Io`] of kind [`io::ErrorKind::InvalidData`]
+/// if it changed in between, see [`SigningStream::sign`].
+pub fn sign_file<P: AsRef<Path>>(path: P, private_key: &PrivateKey) -> Result<SigFile, Error> {
+    let file = BufReader::new(File::open(path)?);
+    let fingerprint = private_key.public_key().fingerprint();
+    let signature = SigningStream::new(private_key).sign_prefixed(&header(&fingerprint), file)?;
+    Ok(SigFile {
+        fingerprint,
+        signature,
+    })
+}
+
+/// Checks that a file was signed by the owner of `public_key`.
+///
+/// Fails with [`Error::Signer`] without reading the file if the signature file names another
+/// key.
+pub fn verify_file<P: AsRef<Path>>(
+    path: P,
+    sig: &SigFile,
+    public_key: &PublicKey,
+) -> Result<(), Error> {
+    if sig.fingerprint != public_key.fingerprint() {
+        return Err(Error::Signer);
+    }
+    let mut file = File::open(path)?;
+    let mut verifier = VerifyingStream::new(public_key, &sig.signature);
+    verifier.update(&header(&sig.fingerprint));
+    io::copy(&mut file, &mut verifier)?;
+    verifier.finish().map_err(|_| Error::Signature)
+}
+
+// The signed header fields.
+fn header(fingerprint: &Fingerprint) -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+    header[..MAGIC.len()].copy_from_slice(MAGIC);
+    header[MAGIC.len()] = VERSION;
+    header[MAGIC.len() + 1] = ALGORITHM_EDDSA_BLAKE2B;
+    header[MAGIC.len() + 2..].copy_from_slice(&**fingerprint);
+    header
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyPair, PubPrivKey, Seed};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn keypair(seed: u8) -> KeyPair<PrivateKey, PublicKey> {
+        KeyPair::generate_key_pair(Seed::from([seed; 32]))
+    }
+
+    fn artifact(name: &str, content: &[u8]) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("monocypher-sigfile-{}-{name}", std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn sign_verify() {
+        let keypair = keypair(1);
+        let path = artifact("sign-verify", &[7u8; 100_000]);
+
+        let sig = sign_file(&path, &keypair.private_key).unwrap();
+        let header = header(&keypair.public_key.fingerprint());
+        let signed = keypair.private_key.sign_parts(&[&header, &[7u8; 100_000]]);
+        assert_eq!(*sig.signature, *signed);
+
+        let sig = SigFile::from_bytes(&sig.to_bytes()).unwrap();
+        assert!(verify_file(&path, &sig, &keypair.public_key).is_ok());
+
+        // The header is part of the signed data.
+        let unbound = SigFile {
+            fingerprint: keypair.public_key.fingerprint(),
+            signature: keypair.sign(&[7u8; 100_000]),
+        };
+        let ret = verify_file(&path, &unbound, &keypair.public_key);
+        assert!(matches!(ret, Err(Error::Signature)));
+
+        fs::write(&path, [8u8; 100_000]).unwrap();
+        let ret = verify_file(&path, &sig, &keypair.public_key);
+        assert!(matches!(ret, Err(Error::Signature)));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn other_signer() {
+        let path = artifact("other-signer", b"release");
+        let sig = sign_file(&path, &keypair(1).private_key).unwrap();
+        let ret = verify_file(&path, &sig, &keypair(2).public_key);
+        assert!(matches!(ret, Err(Error::Signer)));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn malformed() {
+        let keypair = keypair(1);
+        let sig = SigFile {
+            fingerprint: keypair.public_key.fingerprint(),
+            signature: keypair.sign(b"release"),
+        };
+        let bytes = sig.to_bytes();
+        assert_eq!(&bytes[..MAGIC.len()], MAGIC);
+
+        let ret = SigFile::from_bytes(&bytes[..SIGFILE_LEN - 1]);
+        assert!(matches!(ret, Err(Error::Malformed)));
+        let mut unsupported = bytes;
+        unsupported[MAGIC.len() + 1] = 2;
+        let ret = SigFile::from_bytes(&unsupported);
+        assert!(matches!(ret, Err(Error::Unsupported)));
+    }
+
+    #[test]
+    fn missing_file() {
+        let ret = sign_file("/nonexistent/monocypher", &keypair(1).private_key);
+        assert!(matches!(ret, Err(Error::Io(_))));
+    }
+}
@@ -0,0 +1,94 @@
+//! Signatures of streams too large for memory
+//!
+//! The streams are the ones of [`signing`](crate::signing) with BLAKE2b. Signing reads the message
+//! twice from a seekable reader, verifying needs a single pass.
+
+use crate::hashing::blake2b::Context;
+use crate::pubkey::{PrivateKey, PublicKey};
+use crate::signing;
+use crate::Signature;
+
+/// Signs messages read from a seekable reader.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::pubkey::{self, SigningStream, VerifyingStream};
+/// use monocypher::{KeyPair, PubPrivKey, Seed};
+/// use std::io::{self, Cursor};
+///
+/// let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+///     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+/// let artifact = vec![7u8; 1 << 20];
+///
+/// let signature = SigningStream::new(&keypair.private_key)
+///     .sign(Cursor::new(&artifact))
+///     .unwrap();
+///
+/// let mut verifier = VerifyingStream::new(&keypair.public_key, &signature);
+/// io::copy(&mut &artifact[..], &mut verifier).unwrap();
+/// verifier.finish().unwrap();
+/// ```
+pub type SigningStream<'a> = signing::SigningStream<'a, Context>;
+
+/// Checks a signature of a message fed in chunks.
+pub type VerifyingStream = signing::VerifyingStream<Context>;
+
+impl<'a> SigningStream<'a> {
+    pub fn new(private_key: &'a PrivateKey) -> SigningStream<'a> {
+        signing::SigningStream::with_key(private_key.as_bytes())
+    }
+}
+
+impl VerifyingStream {
+    pub fn new(public_key: &PublicKey, signature: &Signature) -> VerifyingStream {
+        signing::VerifyingStream::with_key(public_key.as_bytes(), signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyPair, PrivKey, PubPrivKey, Seed};
+    use std::io::{Cursor, Write};
+
+    fn keypair() -> KeyPair<PrivateKey, PublicKey> {
+        KeyPair::generate_key_pair(Seed::from([1u8; 32]))
+    }
+
+    #[test]
+    fn same_as_one_shot() {
+        let keypair = keypair();
+        let message = vec![7u8; 100_000];
+        let signature = SigningStream::new(&keypair.private_key)
+            .sign(Cursor::new(&message))
+            .unwrap();
+        assert_eq!(*signature, *keypair.private_key.sign(&message));
+    }
+
+    #[test]
+    fn from_position() {
+        let keypair = keypair();
+        let mut reader = Cursor::new(b"headermessage".to_vec());
+        reader.set_position(6);
+        let signature = SigningStream::new(&keypair.private_key)
+            .sign(&mut reader)
+            .unwrap();
+        assert_eq!(*signature, *keypair.private_key.sign(b"message"));
+    }
+
+    #[test]
+    fn verify() {
+        let keypair = keypair();
+        let signature = keypair.private_key.sign(b"message");
+
+        let mut verifier = VerifyingStream::new(&keypair.public_key, &signature);
+        verifier.update(b"mess");
+        verifier.write_all(b"age").unwrap();
+        assert!(verifier.finish().is_ok());
+
+        let mut verifier = VerifyingStream::new(&keypair.public_key, &signature);
+        verifier.update(b"massage");
+        assert!(verifier.finish().is_err());
+    }
+}
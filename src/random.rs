@@ -0,0 +1,88 @@
+//! Random keys, nonces and salts from the operating system
+//!
+//! Every random value of the crate comes from [`fill`], so a failing random number generator is
+//! reported the same way everywhere.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::aead::{self, nonce::Nonce};
+use crate::password::Salt;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+}
+
+/// Fills the buffer with random bytes.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::random;
+///
+/// let mut buf = [0u8; 64];
+/// random::fill(&mut buf).unwrap();
+/// ```
+pub fn fill(buf: &mut [u8]) -> Result<(), Error> {
+    getrandom::getrandom(buf).map_err(|_| Error::Random)
+}
+
+/// Returns an array of random bytes.
+pub fn bytes<const N: usize>() -> Result<[u8; N], Error> {
+    let mut bytes = [0u8; N];
+    fill(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Generates a symmetric key for [`aead`].
+///
+/// # Example
+///
+/// ```
+/// use monocypher::aead::lock;
+/// use monocypher::random;
+///
+/// let key = random::random_key().unwrap();
+/// let nonce = random::random_nonce24().unwrap();
+/// let (cipher_text, mac) = lock::aead("plaintext".as_bytes(), *key, *nonce.as_bytes(), &[]);
+/// ```
+pub fn random_key() -> Result<aead::Key, Error> {
+    bytes().map(aead::Key::from)
+}
+
+/// Generates a 24 byte nonce for XChaCha20 and [`aead`].
+///
+/// Random nonces of this size can be used without keeping any state, collisions are negligible.
+pub fn random_nonce24() -> Result<Nonce, Error> {
+    Nonce::random()
+}
+
+/// Generates a salt for password hashing.
+pub fn random_salt() -> Result<Salt, Error> {
+    bytes().map(Salt::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Seed;
+
+    #[test]
+    fn distinct() {
+        assert_ne!(bytes::<32>().unwrap(), bytes::<32>().unwrap());
+        assert_ne!(*random_key().unwrap(), *random_key().unwrap());
+        assert_ne!(
+            random_nonce24().unwrap().as_bytes(),
+            random_nonce24().unwrap().as_bytes()
+        );
+        assert_ne!(random_salt().unwrap(), random_salt().unwrap());
+        assert_ne!(*Seed::random().unwrap(), *Seed::random().unwrap());
+    }
+
+    #[test]
+    fn fill_empty() {
+        assert_eq!(fill(&mut []), Ok(()));
+    }
+}
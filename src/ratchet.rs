@@ -0,0 +1,408 @@
+//! Double Ratchet sessions
+//!
+//! Implements the [Double Ratchet](https://signal.org/docs/specifications/doubleratchet/)
+//! algorithm. Every message is encrypted under its own key from a symmetric [`Ratchet`] chain.
+//! Whenever the sender of the messages changes, both parties mix a fresh X25519 shared secret
+//! into the root key, which starts new chains:
+//!
+//! ```text
+//! root key || chain key = BLAKE2b-512(key = root key, DH output)
+//! ```
+//!
+//! Messages are encrypted with XChaCha20-Poly1305 and an all zero nonce, the associated data of
+//! the session followed by the encoded [`Header`] is authenticated. Keys of messages that arrive
+//! out of order are kept until the message arrives, up to a limit.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::aead::{lock, unlock};
+use crate::hashing::blake2b;
+use crate::kdf::{MessageKey, Ratchet};
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::utils;
+use std::collections::VecDeque;
+use thiserror::Error;
+
+/// Length of an encoded [`Header`].
+pub const HEADER_LEN: usize = 32 + 4 + 4;
+
+/// Default number of message keys a session keeps for messages that arrive out of order.
+pub const DEFAULT_MAX_SKIP: u32 = 1000;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("Public key has a low order.")]
+    LowOrder,
+    #[error("Message is corrupt.")]
+    InvalidMac,
+    #[error("Header or message is malformed.")]
+    Malformed,
+    #[error("Message skips more messages than the session keeps keys for.")]
+    TooManySkipped,
+    #[error("Message was already decrypted or its key was dropped.")]
+    Replayed,
+    #[error("The responder can only send after the first message arrived.")]
+    NotReady,
+}
+
+/// The public part of a message, sent along with the ciphertext.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// Current ratchet public key of the sender.
+    pub public_key: PublicKey,
+    /// Number of messages in the sender's previous sending chain.
+    pub previous_len: u32,
+    /// Number of the message in the current sending chain.
+    pub number: u32,
+}
+
+impl Header {
+    /// Encodes the header as the public key followed by both counters, little endian.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..32].copy_from_slice(&*self.public_key);
+        bytes[32..36].copy_from_slice(&self.previous_len.to_le_bytes());
+        bytes[36..].copy_from_slice(&self.number.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a header encoded with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Header, Error> {
+        if bytes.len() != HEADER_LEN {
+            return Err(Error::Malformed);
+        }
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(&bytes[..32]);
+        let word = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        Ok(Header {
+            public_key: PublicKey::from(public_key),
+            previous_len: word(32),
+            number: word(36),
+        })
+    }
+}
+
+/// One side of a Double Ratchet session.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::key_exchange::SecretKey;
+/// use monocypher::ratchet::Session;
+///
+/// // Agreed on beforehand, for example with X3DH.
+/// let shared_key = [7u8; 32];
+/// let bob_key = SecretKey::generate().unwrap();
+/// let bob_public = bob_key.public_key();
+///
+/// let mut alice = Session::initiator(shared_key, bob_public, &[]).unwrap();
+/// let mut bob = Session::responder(shared_key, bob_key, &[]);
+///
+/// let (header, message) = alice.encrypt("hello".as_bytes()).unwrap();
+/// assert_eq!(bob.decrypt(&header, &message).unwrap(), "hello".as_bytes());
+/// let (header, message) = bob.encrypt("hi".as_bytes()).unwrap();
+/// assert_eq!(alice.decrypt(&header, &message).unwrap(), "hi".as_bytes());
+/// ```
+pub struct Session {
+    dh_self: SecretKey,
+    dh_remote: Option<PublicKey>,
+    root_key: [u8; 32],
+    sending: Option<Ratchet>,
+    receiving: Option<Ratchet>,
+    previous_len: u32,
+    skipped: VecDeque<SkippedKey>,
+    max_skip: u32,
+    associated_data: Vec<u8>,
+}
+
+struct SkippedKey {
+    public_key: PublicKey,
+    number: u32,
+    key: MessageKey,
+}
+
+impl Session {
+    /// Starts the session of the party sending the first message.
+    ///
+    /// `remote_public` is the responder's ratchet key, for example its signed prekey.
+    pub fn initiator(
+        shared_key: [u8; 32],
+        remote_public: PublicKey,
+        associated_data: &[u8],
+    ) -> Result<Session, Error> {
+        let dh_self = SecretKey::generate().map_err(|_| Error::Random)?;
+        let (root_key, chain_key) = kdf_root(&shared_key, &dh_self, &remote_public)?;
+        let mut session = Session::new(shared_key, dh_self, associated_data);
+        session.root_key = root_key;
+        session.dh_remote = Some(remote_public);
+        session.sending = Some(Ratchet::new(chain_key));
+        Ok(session)
+    }
+
+    /// Starts the session of the party receiving the first message.
+    pub fn responder(shared_key: [u8; 32], key_pair: SecretKey, associated_data: &[u8]) -> Session {
+        Session::new(shared_key, key_pair, associated_data)
+    }
+
+    fn new(root_key: [u8; 32], dh_self: SecretKey, associated_data: &[u8]) -> Session {
+        Session {
+            dh_self,
+            dh_remote: None,
+            root_key,
+            sending: None,
+            receiving: None,
+            previous_len: 0,
+            skipped: VecDeque::new(),
+            max_skip: DEFAULT_MAX_SKIP,
+            associated_data: associated_data.to_vec(),
+        }
+    }
+
+    /// Sets how many message keys are kept for messages that arrive out of order.
+    ///
+    /// A single message may not skip more messages than that, and the oldest keys are dropped
+    /// once the limit is reached.
+    pub fn set_max_skip(&mut self, max_skip: u32) {
+        self.max_skip = max_skip;
+        self.evict();
+    }
+
+    /// Encrypts the next message.
+    ///
+    /// Returns the header and the MAC (16 bytes) followed by the ciphertext.
+    pub fn encrypt(&mut self, plain_text: &[u8]) -> Result<(Header, Vec<u8>), Error> {
+        let sending = self.sending.as_mut().ok_or(Error::NotReady)?;
+        let header = Header {
+            public_key: self.dh_self.public_key(),
+            previous_len: self.previous_len,
+            number: sending.steps() as u32,
+        };
+        let key = sending.step();
+        let ad = [&self.associated_data[..], &header.to_bytes()].concat();
+        let (cipher_text, mac) = lock::aead(plain_text, *key, [0u8; 24], &ad);
+
+        let mut message = Vec::with_capacity(16 + cipher_text.len());
+        message.extend_from_slice(&mac);
+        message.extend_from_slice(&cipher_text);
+        Ok((header, message))
+    }
+
+    /// Decrypts a message.
+    ///
+    /// The session is only changed if the message is authentic.
+    pub fn decrypt(&mut self, header: &Header, message: &[u8]) -> Result<Vec<u8>, Error> {
+        if message.len() < 16 {
+            return Err(Error::Malformed);
+        }
+        let ad = [&self.associated_data[..], &header.to_bytes()].concat();
+
+        if let Some(i) = self
+            .skipped
+            .iter()
+            .position(|k| k.public_key == header.public_key && k.number == header.number)
+        {
+            let plain_text = open(&self.skipped[i].key, message, &ad)?;
+            self.skipped.remove(i);
+            return Ok(plain_text);
+        }
+
+        let mut skipped = Vec::new();
+        let mut ratchet_step = None;
+        let mut receiving = if self.dh_remote == Some(header.public_key) {
+            self.receiving.clone().ok_or(Error::InvalidMac)?
+        } else {
+            if let (Some(receiving), Some(dh_remote)) = (&self.receiving, self.dh_remote) {
+                let mut receiving = receiving.clone();
+                self.skip(&mut receiving, dh_remote, header.previous_len, &mut skipped)?;
+            }
+            let (root_key, receiving_key) =
+                kdf_root(&self.root_key, &self.dh_self, &header.public_key)?;
+            let dh_self = SecretKey::generate().map_err(|_| Error::Random)?;
+            let (root_key, sending_key) = kdf_root(&root_key, &dh_self, &header.public_key)?;
+            ratchet_step = Some((dh_self, root_key, sending_key));
+            Ratchet::new(receiving_key)
+        };
+        self.skip(
+            &mut receiving,
+            header.public_key,
+            header.number,
+            &mut skipped,
+        )?;
+        let plain_text = open(&receiving.step(), message, &ad)?;
+
+        if let Some((dh_self, root_key, sending_key)) = ratchet_step {
+            self.previous_len = self.sending.as_ref().map_or(0, |s| s.steps() as u32);
+            self.dh_self = dh_self;
+            self.dh_remote = Some(header.public_key);
+            self.root_key = root_key;
+            self.sending = Some(Ratchet::new(sending_key));
+        }
+        self.receiving = Some(receiving);
+        self.skipped.extend(skipped);
+        self.evict();
+        Ok(plain_text)
+    }
+
+    // Steps the chain up to message `until`, keeping the keys of the messages in between.
+    fn skip(
+        &self,
+        chain: &mut Ratchet,
+        public_key: PublicKey,
+        until: u32,
+        skipped: &mut Vec<SkippedKey>,
+    ) -> Result<(), Error> {
+        let until = u64::from(until);
+        if until < chain.steps() {
+            return Err(Error::Replayed);
+        }
+        if until - chain.steps() > u64::from(self.max_skip) {
+            return Err(Error::TooManySkipped);
+        }
+        while chain.steps() < until {
+            let number = chain.steps() as u32;
+            skipped.push(SkippedKey {
+                public_key,
+                number,
+                key: chain.step(),
+            });
+        }
+        Ok(())
+    }
+
+    // Drops the oldest skipped keys above the limit.
+    fn evict(&mut self) {
+        while self.skipped.len() > self.max_skip as usize {
+            self.skipped.pop_front();
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.root_key);
+    }
+}
+
+// Mixes the X25519 shared secret into the root key, returns the next root key and a chain key.
+fn kdf_root(
+    root_key: &[u8; 32],
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+) -> Result<([u8; 32], [u8; 32]), Error> {
+    let shared_secret = secret_key
+        .diffie_hellman(public_key)
+        .map_err(|_| Error::LowOrder)?;
+    let mut ctx = blake2b::Context::with_key(root_key);
+    ctx.update(&*shared_secret);
+    let mut output = ctx.finalize();
+
+    let mut keys = ([0u8; 32], [0u8; 32]);
+    keys.0.copy_from_slice(&output[..32]);
+    keys.1.copy_from_slice(&output[32..]);
+    utils::wipe(&mut output);
+    Ok(keys)
+}
+
+fn open(key: &MessageKey, message: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut mac = [0u8; 16];
+    mac.copy_from_slice(&message[..16]);
+    unlock::aead(&message[16..], **key, [0u8; 24], mac, ad).map_err(|_| Error::InvalidMac)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sessions() -> (Session, Session) {
+        let bob_key = SecretKey::from([2u8; 32]);
+        let alice = Session::initiator([1u8; 32], bob_key.public_key(), b"ad").unwrap();
+        let bob = Session::responder([1u8; 32], bob_key, b"ad");
+        (alice, bob)
+    }
+
+    #[test]
+    fn conversation() {
+        let (mut alice, mut bob) = sessions();
+        for round in 0..3u8 {
+            for i in 0..3u8 {
+                let (header, message) = alice.encrypt(&[round, i]).unwrap();
+                assert_eq!(header.number, u32::from(i));
+                assert_eq!(bob.decrypt(&header, &message).unwrap(), [round, i]);
+            }
+            let (header, message) = bob.encrypt(&[round]).unwrap();
+            assert_eq!(alice.decrypt(&header, &message).unwrap(), [round]);
+        }
+    }
+
+    #[test]
+    fn out_of_order() {
+        let (mut alice, mut bob) = sessions();
+        let first = alice.encrypt(b"first").unwrap();
+        let second = alice.encrypt(b"second").unwrap();
+
+        // Bob answers after the second message, alice starts a new chain.
+        assert_eq!(bob.decrypt(&second.0, &second.1).unwrap(), b"second");
+        let (header, message) = bob.encrypt(b"reply").unwrap();
+        alice.decrypt(&header, &message).unwrap();
+        let third = alice.encrypt(b"third").unwrap();
+        assert_eq!(third.0.previous_len, 2);
+
+        assert_eq!(bob.decrypt(&third.0, &third.1).unwrap(), b"third");
+        assert_eq!(bob.decrypt(&first.0, &first.1).unwrap(), b"first");
+        assert_eq!(bob.decrypt(&first.0, &first.1), Err(Error::Replayed));
+    }
+
+    #[test]
+    fn max_skip() {
+        let (mut alice, mut bob) = sessions();
+        bob.set_max_skip(2);
+        let messages: Vec<_> = (0..4).map(|_| alice.encrypt(b"message").unwrap()).collect();
+        assert_eq!(
+            bob.decrypt(&messages[3].0, &messages[3].1),
+            Err(Error::TooManySkipped)
+        );
+        assert!(bob.decrypt(&messages[2].0, &messages[2].1).is_ok());
+        assert_eq!(bob.skipped.len(), 2);
+
+        bob.set_max_skip(1);
+        assert_eq!(
+            bob.decrypt(&messages[0].0, &messages[0].1),
+            Err(Error::Replayed)
+        );
+        assert!(bob.decrypt(&messages[1].0, &messages[1].1).is_ok());
+    }
+
+    #[test]
+    fn corrupt_message_keeps_state() {
+        let (mut alice, mut bob) = sessions();
+        let (header, mut message) = alice.encrypt(b"message").unwrap();
+        message[16] ^= 1;
+        assert_eq!(bob.decrypt(&header, &message), Err(Error::InvalidMac));
+        assert!(bob.dh_remote.is_none());
+
+        message[16] ^= 1;
+        let mut forged = header;
+        forged.previous_len = 1;
+        assert_eq!(bob.decrypt(&forged, &message), Err(Error::InvalidMac));
+        assert_eq!(bob.decrypt(&header, &message).unwrap(), b"message");
+    }
+
+    #[test]
+    fn responder_waits() {
+        let (_, mut bob) = sessions();
+        assert_eq!(bob.encrypt(b"message").err(), Some(Error::NotReady));
+    }
+
+    #[test]
+    fn header_bytes() {
+        let header = Header {
+            public_key: PublicKey::from([3u8; 32]),
+            previous_len: 1,
+            number: 2,
+        };
+        assert_eq!(Header::from_bytes(&header.to_bytes()), Ok(header));
+        assert_eq!(Header::from_bytes(&[0u8; 39]), Err(Error::Malformed));
+    }
+}
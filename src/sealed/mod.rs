@@ -0,0 +1,161 @@
+//! Public key encryption with ephemeral X25519 keys
+//!
+//! [`seal`] encrypts a message anonymously to a public key, like libsodium's `crypto_box_seal`.
+//! A sealed box starts with the ephemeral public key (32 bytes), followed by the MAC (16 bytes)
+//! and the encrypted message. The message is encrypted with XChaCha20-Poly1305 under a key
+//! derived with BLAKE2b from the X25519 shared secret and both public keys. The key is only used
+//! once, so the nonce is all zero.
+//!
+//! Requires the `getrandom` feature.
+
+use crate::aead::{lock, unlock};
+use crate::hashing::blake2b;
+use crate::key_exchange::{self, PublicKey, SecretKey};
+use crate::utils;
+use thiserror::Error;
+
+pub mod multi;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to generate random bytes.")]
+    Random,
+    #[error("At least one recipient is required.")]
+    NoRecipients,
+    #[error("Envelope is malformed.")]
+    Malformed,
+    #[error("Message is corrupt.")]
+    InvalidMac,
+    #[error("Recipient public key has a low order.")]
+    LowOrder,
+}
+
+impl From<crate::random::Error> for Error {
+    fn from(_: crate::random::Error) -> Self {
+        Error::Random
+    }
+}
+
+/// Size a sealed box adds to the message.
+pub const OVERHEAD: usize = 32 + 16;
+
+/// Encrypts the plaintext to the recipient public key.
+///
+/// Only the recipient can open the box, but it does not learn who sealed it.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::key_exchange::SecretKey;
+/// use monocypher::sealed;
+///
+/// let secret_key = SecretKey::from([1u8; 32]);
+///
+/// let sealed = sealed::seal("plaintext".as_bytes(), &secret_key.public_key()).unwrap();
+/// let plaintext = sealed::open(&sealed, &secret_key).unwrap();
+/// assert_eq!(plaintext, "plaintext".as_bytes());
+/// ```
+pub fn seal(plain_text: &[u8], recipient_pk: &PublicKey) -> Result<Vec<u8>, Error> {
+    let (ephemeral_sk, ephemeral_pk) = ephemeral_key_pair()?;
+    let mut key = shared_key(&ephemeral_sk, recipient_pk, &ephemeral_pk, recipient_pk)
+        .map_err(|_| Error::LowOrder)?;
+    drop(ephemeral_sk);
+
+    let (cipher_text, mac) = lock::easy(plain_text, key, [0u8; 24]);
+    utils::wipe(&mut key);
+
+    let mut sealed = Vec::with_capacity(OVERHEAD + plain_text.len());
+    sealed.extend_from_slice(&*ephemeral_pk);
+    sealed.extend_from_slice(&mac);
+    sealed.extend_from_slice(&cipher_text);
+    Ok(sealed)
+}
+
+/// Decrypts a box sealed to the public key of `recipient_sk`.
+pub fn open(sealed: &[u8], recipient_sk: &SecretKey) -> Result<Vec<u8>, Error> {
+    if sealed.len() < OVERHEAD {
+        return Err(Error::Malformed);
+    }
+    let mut ephemeral_pk = [0u8; 32];
+    ephemeral_pk.copy_from_slice(&sealed[..32]);
+    let ephemeral_pk = PublicKey::from(ephemeral_pk);
+    let mut mac = [0u8; 16];
+    mac.copy_from_slice(&sealed[32..OVERHEAD]);
+
+    let recipient_pk = recipient_sk.public_key();
+    let mut key = shared_key(recipient_sk, &ephemeral_pk, &ephemeral_pk, &recipient_pk)
+        .map_err(|_| Error::Malformed)?;
+    let ret = unlock::easy(&sealed[OVERHEAD..], key, [0u8; 24], mac);
+    utils::wipe(&mut key);
+    ret.map_err(|_| Error::InvalidMac)
+}
+
+// Generates a random X25519 key pair.
+fn ephemeral_key_pair() -> Result<(SecretKey, PublicKey), Error> {
+    let secret_key = SecretKey::generate().map_err(|_| Error::Random)?;
+    let public_key = secret_key.public_key();
+    Ok((secret_key, public_key))
+}
+
+// Derives a key from the X25519 shared secret between `secret_key` and `their_public_key`.
+//
+// Both public keys are hashed along with the shared secret, so that the key is bound to the
+// ephemeral and the recipient key.
+fn shared_key(
+    secret_key: &SecretKey,
+    their_public_key: &PublicKey,
+    ephemeral_pk: &PublicKey,
+    recipient_pk: &PublicKey,
+) -> Result<[u8; 32], key_exchange::Error> {
+    let shared_secret = secret_key.diffie_hellman(their_public_key)?;
+    let mut ctx = blake2b::Context::<32>::new_n();
+    ctx.update(&*shared_secret);
+    ctx.update(&**ephemeral_pk);
+    ctx.update(&**recipient_pk);
+    Ok(ctx.finalize())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn seal_open() {
+        let secret_key = SecretKey::from([1u8; 32]);
+        let sealed = seal("secret".as_bytes(), &secret_key.public_key()).unwrap();
+        assert_eq!(sealed.len(), OVERHEAD + 6);
+        assert_eq!(open(&sealed, &secret_key).unwrap(), "secret".as_bytes());
+
+        let other = seal("secret".as_bytes(), &secret_key.public_key()).unwrap();
+        assert_ne!(sealed, other);
+    }
+
+    #[test]
+    fn wrong_recipient() {
+        let recipient = SecretKey::from([1u8; 32]).public_key();
+        let sealed = seal("secret".as_bytes(), &recipient).unwrap();
+        let ret = open(&sealed, &SecretKey::from([2u8; 32]));
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn corrupt() {
+        let secret_key = SecretKey::from([1u8; 32]);
+        let sealed = seal("secret".as_bytes(), &secret_key.public_key()).unwrap();
+        // Ephemeral key, MAC and ciphertext.
+        for i in [0, 32, OVERHEAD] {
+            let mut forged = sealed.clone();
+            forged[i] ^= 1;
+            assert!(open(&forged, &secret_key).is_err());
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let secret_key = SecretKey::from([1u8; 32]);
+        let ret = open(&[0u8; OVERHEAD - 1], &secret_key);
+        assert_eq!(ret, Err(Error::Malformed));
+        let ret = seal("secret".as_bytes(), &PublicKey::from([0u8; 32]));
+        assert_eq!(ret, Err(Error::LowOrder));
+    }
+}
@@ -0,0 +1,210 @@
+//! Encryption of one message to several recipients
+//!
+//! The payload is encrypted once under a random content key, which is then wrapped for every
+//! recipient. The envelope has the following layout:
+//!
+//! | Size      | Content                                                 |
+//! |-----------|---------------------------------------------------------|
+//! | 1         | Version, currently 1                                    |
+//! | 32        | Ephemeral X25519 public key                             |
+//! | 4         | Number of recipients `n`, little endian                 |
+//! | `n` * 48  | Wrapped content key (32) and its MAC (16) per recipient |
+//! | 16        | MAC of the payload                                      |
+//! | remaining | Encrypted payload                                       |
+//!
+//! Every content key is wrapped with XChaCha20-Poly1305 under a key derived with BLAKE2b from the
+//! X25519 shared secret of the ephemeral and the recipient key. The payload is encrypted under the
+//! content key, with the header as additional data. Both keys are only used once, so the nonces
+//! are all zero.
+
+use crate::aead::{lock, unlock};
+use crate::key_exchange::{PublicKey, SecretKey};
+use crate::sealed::{ephemeral_key_pair, shared_key, Error};
+use crate::utils;
+
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 1 + 32 + 4;
+const WRAPPED_KEY_SIZE: usize = 32 + 16;
+
+/// Encrypts the plaintext to all recipient public keys.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::key_exchange::SecretKey;
+/// use monocypher::sealed::multi;
+///
+/// let alice = SecretKey::from([1u8; 32]);
+/// let bob = SecretKey::from([2u8; 32]);
+/// let recipients = [alice.public_key(), bob.public_key()];
+///
+/// let envelope = multi::seal("plaintext".as_bytes(), &recipients).unwrap();
+/// let plaintext = multi::open(&envelope, &alice).unwrap();
+/// ```
+pub fn seal(plain_text: &[u8], recipients: &[PublicKey]) -> Result<Vec<u8>, Error> {
+    if recipients.is_empty() {
+        return Err(Error::NoRecipients);
+    }
+    let count = u32::try_from(recipients.len()).map_err(|_| Error::Malformed)?;
+    if recipients.iter().any(PublicKey::is_low_order) {
+        return Err(Error::LowOrder);
+    }
+
+    let mut content_key = [0u8; 32];
+    crate::random::fill(&mut content_key)?;
+    let (ephemeral_sk, ephemeral_pk) = ephemeral_key_pair()?;
+
+    let header_size = HEADER_SIZE + recipients.len() * WRAPPED_KEY_SIZE;
+    let mut envelope = Vec::with_capacity(header_size + 16 + plain_text.len());
+    envelope.push(VERSION);
+    envelope.extend_from_slice(&*ephemeral_pk);
+    envelope.extend_from_slice(&count.to_le_bytes());
+
+    for recipient in recipients {
+        let mut wrap_key = shared_key(&ephemeral_sk, recipient, &ephemeral_pk, recipient)
+            .map_err(|_| Error::LowOrder)?;
+        let (wrapped, mac) = lock::easy(&content_key, wrap_key, [0u8; 24]);
+        utils::wipe(&mut wrap_key);
+        envelope.extend_from_slice(&wrapped);
+        envelope.extend_from_slice(&mac);
+    }
+    drop(ephemeral_sk);
+
+    let (cipher_text, mac) = lock::aead(plain_text, content_key, [0u8; 24], &envelope);
+    utils::wipe(&mut content_key);
+    envelope.extend_from_slice(&mac);
+    envelope.extend_from_slice(&cipher_text);
+    Ok(envelope)
+}
+
+/// Decrypts an envelope with the secret key of one of its recipients.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::key_exchange::SecretKey;
+/// use monocypher::sealed::multi;
+///
+/// let secret_key = SecretKey::from([1u8; 32]);
+///
+/// let envelope = multi::seal("plaintext".as_bytes(), &[secret_key.public_key()]).unwrap();
+/// let plaintext = multi::open(&envelope, &secret_key).unwrap();
+/// ```
+pub fn open(envelope: &[u8], secret_key: &SecretKey) -> Result<Vec<u8>, Error> {
+    if envelope.len() < HEADER_SIZE || envelope[0] != VERSION {
+        return Err(Error::Malformed);
+    }
+    let mut ephemeral_pk = [0u8; 32];
+    ephemeral_pk.copy_from_slice(&envelope[1..33]);
+    let ephemeral_pk = PublicKey::from(ephemeral_pk);
+    let mut count = [0u8; 4];
+    count.copy_from_slice(&envelope[33..HEADER_SIZE]);
+    let count = u32::from_le_bytes(count) as usize;
+
+    let header_size = count
+        .checked_mul(WRAPPED_KEY_SIZE)
+        .and_then(|size| size.checked_add(HEADER_SIZE))
+        .filter(|size| envelope.len() >= size + 16)
+        .ok_or(Error::Malformed)?;
+    let (header, payload) = envelope.split_at(header_size);
+
+    let public_key = secret_key.public_key();
+    let mut wrap_key = shared_key(secret_key, &ephemeral_pk, &ephemeral_pk, &public_key)
+        .map_err(|_| Error::Malformed)?;
+    let mut content_key = None;
+    for wrapped in header[HEADER_SIZE..].chunks(WRAPPED_KEY_SIZE) {
+        let mut mac = [0u8; 16];
+        mac.copy_from_slice(&wrapped[32..]);
+        if let Ok(key) = unlock::easy(&wrapped[..32], wrap_key, [0u8; 24], mac) {
+            content_key = Some(key);
+        }
+    }
+    utils::wipe(&mut wrap_key);
+    let mut content_key = content_key.ok_or(Error::InvalidMac)?;
+
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&content_key);
+    utils::wipe(&mut content_key);
+    let mut mac = [0u8; 16];
+    mac.copy_from_slice(&payload[..16]);
+    let ret = unlock::aead(&payload[16..], key, [0u8; 24], mac, header);
+    utils::wipe(&mut key);
+    ret.map_err(|_| Error::InvalidMac)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn all_recipients() {
+        let secret_keys = [[1u8; 32], [2u8; 32], [3u8; 32]].map(SecretKey::from);
+        let recipients = secret_keys.each_ref().map(SecretKey::public_key);
+
+        let envelope = seal("secret".as_bytes(), &recipients).unwrap();
+        assert_eq!(envelope.len(), HEADER_SIZE + 3 * WRAPPED_KEY_SIZE + 16 + 6);
+
+        for secret_key in &secret_keys {
+            assert_eq!(open(&envelope, secret_key).unwrap(), "secret".as_bytes());
+        }
+    }
+
+    #[test]
+    fn not_a_recipient() {
+        let recipient = SecretKey::from([1u8; 32]).public_key();
+        let envelope = seal("secret".as_bytes(), &[recipient]).unwrap();
+        let ret = open(&envelope, &SecretKey::from([2u8; 32]));
+        assert_eq!(ret, Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn corrupt_payload() {
+        let secret_key = SecretKey::from([1u8; 32]);
+        let mut envelope = seal("secret".as_bytes(), &[secret_key.public_key()]).unwrap();
+        let last = envelope.len() - 1;
+        envelope[last] ^= 1;
+        assert_eq!(open(&envelope, &secret_key), Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn corrupt_header() {
+        let secret_key = SecretKey::from([1u8; 32]);
+        let recipients = [
+            secret_key.public_key(),
+            SecretKey::from([2u8; 32]).public_key(),
+        ];
+        let mut envelope = seal("secret".as_bytes(), &recipients).unwrap();
+        envelope[HEADER_SIZE + WRAPPED_KEY_SIZE] ^= 1;
+        assert_eq!(open(&envelope, &secret_key), Err(Error::InvalidMac));
+    }
+
+    #[test]
+    fn malformed() {
+        let secret_key = SecretKey::from([1u8; 32]);
+        let envelope = seal("secret".as_bytes(), &[secret_key.public_key()]).unwrap();
+        assert_eq!(
+            open(&envelope[..HEADER_SIZE + 10], &secret_key),
+            Err(Error::Malformed)
+        );
+        assert_eq!(open(&[], &secret_key), Err(Error::Malformed));
+    }
+
+    #[test]
+    fn low_order() {
+        let recipients = [
+            SecretKey::from([1u8; 32]).public_key(),
+            PublicKey::from([0u8; 32]),
+        ];
+        assert_eq!(seal("secret".as_bytes(), &recipients), Err(Error::LowOrder));
+
+        let secret_key = SecretKey::from([1u8; 32]);
+        let mut envelope = seal("secret".as_bytes(), &[secret_key.public_key()]).unwrap();
+        envelope[1..33].fill(0);
+        assert_eq!(open(&envelope, &secret_key), Err(Error::Malformed));
+    }
+
+    #[test]
+    fn no_recipients() {
+        assert_eq!(seal("secret".as_bytes(), &[]), Err(Error::NoRecipients));
+    }
+}
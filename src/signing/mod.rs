@@ -0,0 +1,412 @@
+//! EdDSA signatures generic over the hash function
+//!
+//! All EdDSA variants of Monocypher share the curve arithmetic and only differ in the hash:
+//! [`pubkey`](crate::pubkey) uses BLAKE2b, [`ed25519`](crate::ed25519) the SHA-512 of RFC 8032.
+//! [`SigningKey`] and [`VerifyingKey`] take the hash as a type parameter implementing
+//! [`EdDsaHash`], so both variants have the same API. The keys and signatures are the same as
+//! the ones of the respective module, which signs and checks through these types.
+//!
+//! The curve primitives themselves are in [`advanced::eddsa`](crate::advanced::eddsa).
+//!
+//! # Example
+//!
+//! ```
+//! use monocypher::signing::{Blake2bSigningKey, Blake2bVerifyingKey};
+//! use monocypher::{KeyPair, PrivKey, PubKey, PubPrivKey, Seed};
+//!
+//! let keypair: KeyPair<Blake2bSigningKey, Blake2bVerifyingKey> =
+//!     KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+//! let signature = keypair.sign("message".as_bytes());
+//! keypair.check(signature, "message".as_bytes()).unwrap();
+//! ```
+
+use crate::advanced::eddsa;
+use crate::hashing::blake2b;
+#[cfg(feature = "ed25519")]
+use crate::hashing::sha512;
+use crate::{pubkey, utils, Error, KeyPair, PrivKey, PubKey, PubPrivKey, Seed, Signature};
+use std::convert::Infallible;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+mod stream;
+#[cfg(feature = "signature-traits")]
+mod traits;
+
+pub use stream::{SigningStream, VerifyingStream};
+
+/// A hash function with a 64 byte digest to instantiate EdDSA with.
+pub trait EdDsaHash {
+    /// Starts a new hash.
+    fn new() -> Self;
+
+    /// Absorbs the next part of the input.
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the digest.
+    fn finalize(self) -> [u8; 64];
+}
+
+impl EdDsaHash for blake2b::Context {
+    fn new() -> Self {
+        blake2b::Context::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        blake2b::Context::update(self, data)
+    }
+
+    fn finalize(mut self) -> [u8; 64] {
+        blake2b::Context::finalize(&mut self)
+    }
+}
+
+/// Requires the `ed25519` feature.
+#[cfg(feature = "ed25519")]
+impl EdDsaHash for sha512::Context {
+    fn new() -> Self {
+        sha512::Context::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        sha512::Context::update(self, data)
+    }
+
+    fn finalize(mut self) -> [u8; 64] {
+        sha512::Context::finalize(&mut self)
+    }
+}
+
+/// EdDSA with BLAKE2b, compatible with [`pubkey`](crate::pubkey).
+pub type Blake2bSigningKey = SigningKey<blake2b::Context>;
+/// EdDSA with BLAKE2b, compatible with [`pubkey`](crate::pubkey).
+pub type Blake2bVerifyingKey = VerifyingKey<blake2b::Context>;
+/// Ed25519 of RFC 8032, compatible with [`ed25519`](crate::ed25519).
+#[cfg(feature = "ed25519")]
+pub type Ed25519SigningKey = SigningKey<sha512::Context>;
+/// Ed25519 of RFC 8032, compatible with [`ed25519`](crate::ed25519).
+#[cfg(feature = "ed25519")]
+pub type Ed25519VerifyingKey = VerifyingKey<sha512::Context>;
+
+/// The seed followed by the public key, wiped on drop.
+pub struct SigningKey<H> {
+    key: [u8; 64],
+    hash: PhantomData<H>,
+}
+
+impl<H: EdDsaHash> SigningKey<H> {
+    /// Derives the key from a random seed.
+    pub fn from_seed(seed: &[u8; 32]) -> SigningKey<H> {
+        let mut a = hash::<H>(&[seed], &[]);
+        let mut scalar = eddsa::trim_scalar(a[..32].try_into().unwrap());
+        let mut key = [0u8; 64];
+        key[..32].copy_from_slice(seed);
+        key[32..].copy_from_slice(&eddsa::scalarbase(&scalar));
+        utils::wipe(&mut a);
+        utils::wipe(&mut scalar);
+        SigningKey {
+            key,
+            hash: PhantomData,
+        }
+    }
+
+    /// Returns the public half of the key.
+    pub fn verifying_key(&self) -> VerifyingKey<H> {
+        VerifyingKey {
+            key: self.key[32..].try_into().unwrap(),
+            hash: PhantomData,
+        }
+    }
+}
+
+impl<H: EdDsaHash> SigningKey<H> {
+    /// Signs the concatenation of the parts without copying them into one buffer.
+    pub fn sign_parts(&self, parts: &[&[u8]]) -> Signature {
+        self.sign_domain(&[], parts)
+    }
+
+    // Signs the parts with `domain` hashed in front of both hashes, like dom2 of Ed25519ctx.
+    pub(crate) fn sign_domain(&self, domain: &[&[u8]], parts: &[&[u8]]) -> Signature {
+        let message = |ctx: &mut H| {
+            parts.iter().for_each(|part| ctx.update(part));
+            Ok::<_, Infallible>(())
+        };
+        sign_with(&self.key, domain, message).unwrap_or_else(|never| match never {})
+    }
+}
+
+impl<H: EdDsaHash> PrivKey for SigningKey<H> {
+    fn sign(&self, message: &[u8]) -> Signature {
+        self.sign_parts(&[message])
+    }
+}
+
+impl<H> Drop for SigningKey<H> {
+    fn drop(&mut self) {
+        utils::wipe(&mut self.key);
+    }
+}
+
+/// Requires the `zeroize` feature.
+#[cfg(feature = "zeroize")]
+impl<H> zeroize::Zeroize for SigningKey<H> {
+    fn zeroize(&mut self) {
+        utils::wipe(&mut self.key);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<H> zeroize::ZeroizeOnDrop for SigningKey<H> {}
+
+impl From<&pubkey::PrivateKey> for SigningKey<blake2b::Context> {
+    fn from(private_key: &pubkey::PrivateKey) -> Self {
+        SigningKey {
+            key: private_key.to_bytes(),
+            hash: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<&crate::ed25519::PrivateKey> for SigningKey<sha512::Context> {
+    fn from(private_key: &crate::ed25519::PrivateKey) -> Self {
+        SigningKey {
+            key: private_key.to_bytes(),
+            hash: PhantomData,
+        }
+    }
+}
+
+/// A public key checking signatures made with the same hash.
+pub struct VerifyingKey<H> {
+    key: [u8; 32],
+    hash: PhantomData<H>,
+}
+
+impl<H: EdDsaHash> PubKey for VerifyingKey<H> {
+    fn check(&self, signature: Signature, message: &[u8]) -> Result<(), Error> {
+        self.check_parts(signature, &[message])
+    }
+}
+
+impl<H> From<[u8; 32]> for VerifyingKey<H> {
+    fn from(key: [u8; 32]) -> Self {
+        VerifyingKey {
+            key,
+            hash: PhantomData,
+        }
+    }
+}
+
+impl<H> PartialEq for VerifyingKey<H> {
+    fn eq(&self, other: &Self) -> bool {
+        utils::verify(&self.key, &other.key)
+    }
+}
+
+impl<H> Eq for VerifyingKey<H> {}
+
+/// Requires the `subtle` feature.
+#[cfg(feature = "subtle")]
+impl<H> subtle::ConstantTimeEq for VerifyingKey<H> {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        self.key[..].ct_eq(&other.key[..])
+    }
+}
+
+impl<H> Deref for VerifyingKey<H> {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.key
+    }
+}
+
+impl<H: EdDsaHash> PubPrivKey for KeyPair<SigningKey<H>, VerifyingKey<H>> {
+    fn generate_key_pair(mut seed: Seed) -> Self {
+        let private_key = SigningKey::from_seed(&seed);
+        utils::wipe(&mut *seed);
+        Self {
+            public_key: private_key.verifying_key(),
+            private_key,
+        }
+    }
+}
+
+impl<H: EdDsaHash> VerifyingKey<H> {
+    /// Checks a signature and rejects malleable encodings, see
+    /// [`pubkey::PublicKey::check_strict`].
+    pub fn check_strict(&self, signature: Signature, message: &[u8]) -> Result<(), Error> {
+        strict_encoding(&signature, &self.key)?;
+        self.check(signature, message)
+    }
+
+    /// Checks a signature of the concatenation of the parts.
+    pub fn check_parts(&self, signature: Signature, parts: &[&[u8]]) -> Result<(), Error> {
+        self.check_domain(signature, &[], parts)
+    }
+
+    // Checks a signature made with `SigningKey::sign_domain` and the same domain.
+    pub(crate) fn check_domain(
+        &self,
+        signature: Signature,
+        domain: &[&[u8]],
+        parts: &[&[u8]],
+    ) -> Result<(), Error> {
+        let mut verifier = VerifyingStream::<H>::with_domain(&self.key, &signature, domain);
+        parts.iter().for_each(|part| verifier.update(part));
+        verifier.finish()
+    }
+}
+
+// Checks that the public key in the second half of `key` belongs to the seed in the first.
+pub(crate) fn check_key_pair<H: EdDsaHash>(key: &[u8; 64]) -> Result<(), Error> {
+    let seed = key[..32].try_into().unwrap();
+    let signing_key = SigningKey::<H>::from_seed(seed);
+    if !utils::verify(&signing_key.key, key) {
+        return Err(Error::KeyMismatch);
+    }
+    Ok(())
+}
+
+// The signature equation, every signature of the crate is made here. `domain` is hashed first
+// in both hashes and `message` feeds the message to each of them. If it fails, nothing is
+// signed.
+pub(crate) fn sign_with<H: EdDsaHash, E>(
+    key: &[u8; 64],
+    domain: &[&[u8]],
+    mut message: impl FnMut(&mut H) -> Result<(), E>,
+) -> Result<Signature, E> {
+    // Secret scalar and nonce prefix.
+    let mut a = hash::<H>(&[&key[..32]], &[]);
+    let mut scalar = eddsa::trim_scalar(a[..32].try_into().unwrap());
+    let mut nonce = [0u8; 32];
+    let ret = sign_equation(key, domain, &a[32..], &scalar, &mut nonce, &mut message);
+    utils::wipe(&mut a);
+    utils::wipe(&mut scalar);
+    utils::wipe(&mut nonce);
+    ret
+}
+
+fn sign_equation<H: EdDsaHash, E>(
+    key: &[u8; 64],
+    domain: &[&[u8]],
+    prefix: &[u8],
+    scalar: &[u8; 32],
+    nonce: &mut [u8; 32],
+    message: &mut impl FnMut(&mut H) -> Result<(), E>,
+) -> Result<Signature, E> {
+    let mut ctx = H::new();
+    domain.iter().for_each(|part| ctx.update(part));
+    ctx.update(prefix);
+    message(&mut ctx)?;
+    *nonce = reduce(ctx.finalize());
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&eddsa::scalarbase(nonce));
+
+    let mut ctx = H::new();
+    domain.iter().for_each(|part| ctx.update(part));
+    ctx.update(&signature[..32]);
+    ctx.update(&key[32..]);
+    message(&mut ctx)?;
+    let challenge = reduce(ctx.finalize());
+
+    signature[32..].copy_from_slice(&eddsa::mul_add(&challenge, scalar, nonce));
+    Ok(Signature::from(signature))
+}
+
+// The encoding checks of `check_strict`, on top of the ones of the signature equation.
+fn strict_encoding(signature: &Signature, public_key: &[u8; 32]) -> Result<(), Error> {
+    let r = signature[..32].try_into().unwrap();
+    let s = signature[32..].try_into().unwrap();
+    let canonical = eddsa::is_canonical_scalar(s)
+        && eddsa::is_canonical_point(r)
+        && eddsa::is_canonical_point(public_key);
+    if !canonical || eddsa::has_small_order(r) || eddsa::has_small_order(public_key) {
+        return Err(Error::Signature);
+    }
+    Ok(())
+}
+
+fn hash<H: EdDsaHash>(prefix: &[&[u8]], parts: &[&[u8]]) -> [u8; 64] {
+    let mut ctx = H::new();
+    prefix.iter().chain(parts).for_each(|part| ctx.update(part));
+    ctx.finalize()
+}
+
+// Reduces a hash modulo L and wipes it.
+fn reduce(mut hash: [u8; 64]) -> [u8; 32] {
+    let reduced = eddsa::reduce(&hash);
+    utils::wipe(&mut hash);
+    reduced
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use monocypher_sys as ffi;
+
+    #[test]
+    fn same_as_pubkey() {
+        let keypair: KeyPair<pubkey::PrivateKey, pubkey::PublicKey> =
+            KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+        let signing_key = Blake2bSigningKey::from_seed(&[1u8; 32]);
+        assert_eq!(*signing_key.verifying_key(), *keypair.public_key);
+        assert_eq!(signing_key.key, keypair.private_key.to_bytes());
+
+        // The one shot functions of Monocypher.
+        let message = b"message";
+        let mut signature = [0u8; 64];
+        let ret = unsafe {
+            ffi::crypto_eddsa_sign(
+                signature.as_mut_ptr(),
+                signing_key.key.as_ptr(),
+                message.as_ptr(),
+                message.len(),
+            );
+            ffi::crypto_eddsa_check(
+                signing_key.sign(message).as_ptr(),
+                keypair.public_key.as_ptr(),
+                message.as_ptr(),
+                message.len(),
+            )
+        };
+        assert_eq!(ret, 0);
+        assert_eq!(*signing_key.sign(message), signature);
+        assert!(keypair.check(Signature::from(signature), message).is_ok());
+    }
+
+    #[test]
+    fn check() {
+        let keypair: KeyPair<Blake2bSigningKey, Blake2bVerifyingKey> =
+            KeyPair::generate_key_pair(Seed::from([1u8; 32]));
+        let signature = keypair.sign(b"message");
+        let forged = Signature::from(*signature);
+        assert!(keypair.check(signature, b"message").is_ok());
+        assert!(keypair.check(forged, b"massage").is_err());
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn ed25519_vector() {
+        // RFC 8032 section 7.1, test 1.
+        let seed: [u8; 32] =
+            hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let signing_key = Ed25519SigningKey::from_seed(&seed);
+        assert_eq!(
+            hex::encode(*signing_key.verifying_key()),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+        let signature = signing_key.sign(&[]);
+        assert_eq!(
+            hex::encode(*signature),
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555\
+             fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+        );
+        assert!(signing_key.verifying_key().check(signature, &[]).is_ok());
+    }
+}
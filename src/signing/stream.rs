@@ -0,0 +1,264 @@
+//! Signatures of streams too large for memory
+//!
+//! EdDSA hashes the message twice: once for the nonce, once for the challenge. The signing
+//! stream reads the message twice from a seekable reader, the verifying stream needs a single
+//! pass. Both produce and check the same signatures as the one shot functions.
+//!
+//! # Security
+//!
+//! The nonce is derived from the first pass, the challenge from the second. If the message
+//! changed in between, the two signatures of different challenges under the same nonce would
+//! reveal the secret scalar. The signing stream hashes the message in both passes and fails
+//! without signing if the digests differ, but the reader should still not be shared with other
+//! writers while signing.
+
+use super::{reduce, sign_with, EdDsaHash, SigningKey, VerifyingKey};
+use crate::{utils, Error, Signature};
+use monocypher_sys as ffi;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+
+/// Signs messages read from a seekable reader, with any hash.
+///
+/// [`pubkey::SigningStream`](crate::pubkey::SigningStream) and `ed25519::SigningStream` are
+/// this type for BLAKE2b and SHA-512, so code generic over the hash works with both.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::signing::{EdDsaHash, SigningKey, VerifyingKey};
+/// use monocypher::hashing::blake2b;
+/// use std::io::{self, Cursor};
+///
+/// fn round_trip<H: EdDsaHash>(artifact: &[u8]) {
+///     let signing_key = SigningKey::<H>::from_seed(&[1u8; 32]);
+///     let signature = signing_key
+///         .signing_stream()
+///         .sign(Cursor::new(artifact))
+///         .unwrap();
+///
+///     let mut verifier = signing_key.verifying_key().verifying_stream(&signature);
+///     io::copy(&mut &artifact[..], &mut verifier).unwrap();
+///     verifier.finish().unwrap();
+/// }
+///
+/// round_trip::<blake2b::Context>(&[7u8; 1 << 20]);
+/// ```
+pub struct SigningStream<'a, H> {
+    key: &'a [u8; 64],
+    hash: PhantomData<H>,
+}
+
+impl<'a, H: EdDsaHash> SigningStream<'a, H> {
+    // Signs with the seed and public key in `key`.
+    pub(crate) fn with_key(key: &'a [u8; 64]) -> SigningStream<'a, H> {
+        SigningStream {
+            key,
+            hash: PhantomData,
+        }
+    }
+
+    /// Signs the message from the current position of the reader to its end.
+    ///
+    /// The reader is read twice and left at its end.
+    ///
+    /// # Security
+    ///
+    /// Both passes must see the same message, otherwise the signature would leak the key. The
+    /// message is hashed in both passes and an error of kind [`io::ErrorKind::InvalidData`] is
+    /// returned if the digests differ, before the signature is computed.
+    pub fn sign<R: Read + Seek>(&self, reader: R) -> io::Result<Signature> {
+        self.sign_prefixed(&[], reader)
+    }
+
+    // Signs `message_prefix` followed by the rest of the reader.
+    pub(crate) fn sign_prefixed<R: Read + Seek>(
+        &self,
+        message_prefix: &[u8],
+        mut reader: R,
+    ) -> io::Result<Signature> {
+        let start = reader.stream_position()?;
+        let mut first = None;
+        sign_with(self.key, &[], |ctx: &mut H| {
+            if first.is_some() {
+                reader.seek(SeekFrom::Start(start))?;
+            }
+            ctx.update(message_prefix);
+            let digest = hash_pass(&mut reader, ctx)?;
+            match &first {
+                None => first = Some(digest),
+                Some(first) if !utils::verify(first, &digest) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "the message changed between the two passes",
+                    ));
+                }
+                Some(_) => (),
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<H: EdDsaHash> SigningKey<H> {
+    /// Returns a stream signing with this key.
+    pub fn signing_stream(&self) -> SigningStream<'_, H> {
+        SigningStream::with_key(&self.key)
+    }
+}
+
+/// Checks a signature of a message fed in chunks, with any hash.
+///
+/// The message is passed with [`update`](Self::update) or written to the stream, for example
+/// with [`io::copy`].
+pub struct VerifyingStream<H> {
+    public_key: [u8; 32],
+    signature: [u8; 64],
+    ctx: H,
+}
+
+impl<H: EdDsaHash> VerifyingStream<H> {
+    pub(crate) fn with_key(public_key: &[u8; 32], signature: &Signature) -> VerifyingStream<H> {
+        VerifyingStream::with_domain(public_key, signature, &[])
+    }
+
+    // Checks with `domain` hashed in front of the message, see `SigningKey::sign_domain`.
+    pub(crate) fn with_domain(
+        public_key: &[u8; 32],
+        signature: &Signature,
+        domain: &[&[u8]],
+    ) -> VerifyingStream<H> {
+        let mut ctx = H::new();
+        domain.iter().for_each(|part| ctx.update(part));
+        ctx.update(&signature[..32]);
+        ctx.update(public_key);
+        VerifyingStream {
+            public_key: *public_key,
+            signature: **signature,
+            ctx,
+        }
+    }
+
+    /// Feeds the next chunk of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.ctx.update(data);
+    }
+
+    /// Checks the signature against the message fed so far.
+    pub fn finish(self) -> Result<(), Error> {
+        let challenge = reduce(self.ctx.finalize());
+        let ret = unsafe {
+            ffi::crypto_eddsa_check_equation(
+                self.signature.as_ptr(),
+                self.public_key.as_ptr(),
+                challenge.as_ptr(),
+            )
+        };
+        match ret {
+            0 => Ok(()),
+            _ => Err(Error::Signature),
+        }
+    }
+}
+
+impl<H: EdDsaHash> Write for VerifyingStream<H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<H: EdDsaHash> VerifyingKey<H> {
+    /// Returns a stream checking `signature` with this key.
+    pub fn verifying_stream(&self, signature: &Signature) -> VerifyingStream<H> {
+        VerifyingStream::with_key(&self.key, signature)
+    }
+}
+
+// Feeds the rest of the reader to `ctx` and returns the digest of the bytes read.
+fn hash_pass<H: EdDsaHash, R: Read>(reader: &mut R, ctx: &mut H) -> io::Result<[u8; 64]> {
+    let mut digest = H::new();
+    io::copy(reader, &mut HashWriter(ctx, &mut digest))?;
+    Ok(digest.finalize())
+}
+
+// Feeds everything written to both hashes.
+struct HashWriter<'a, H>(&'a mut H, &'a mut H);
+
+impl<H: EdDsaHash> Write for HashWriter<'_, H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        self.1.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hashing::blake2b;
+    use crate::PrivKey;
+    use std::io::Cursor;
+
+    fn same_as_one_shot<H: EdDsaHash>() {
+        let signing_key = SigningKey::<H>::from_seed(&[1u8; 32]);
+        let message = vec![7u8; 100_000];
+        let signature = signing_key
+            .signing_stream()
+            .sign(Cursor::new(&message))
+            .unwrap();
+        assert_eq!(signature, signing_key.sign(&message));
+
+        let mut verifier = signing_key.verifying_key().verifying_stream(&signature);
+        verifier.write_all(&message).unwrap();
+        assert!(verifier.finish().is_ok());
+
+        let mut verifier = signing_key.verifying_key().verifying_stream(&signature);
+        verifier.update(&message[1..]);
+        assert!(verifier.finish().is_err());
+    }
+
+    // A reader whose content changes when it is rewound.
+    struct Changing(Cursor<Vec<u8>>);
+
+    impl Read for Changing {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Seek for Changing {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.0.get_mut()[0] ^= 1;
+            self.0.seek(pos)
+        }
+    }
+
+    #[test]
+    fn changed_message() {
+        let signing_key = SigningKey::<blake2b::Context>::from_seed(&[1u8; 32]);
+        let ret = signing_key
+            .signing_stream()
+            .sign(Changing(Cursor::new(vec![7u8; 1000])));
+        assert_eq!(ret.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn blake2b() {
+        same_as_one_shot::<blake2b::Context>();
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn sha512() {
+        same_as_one_shot::<crate::hashing::sha512::Context>();
+    }
+}
@@ -0,0 +1,43 @@
+//! [`signature`] traits for the EdDSA keys of both hashes.
+
+use crate::signing::{EdDsaHash, SigningKey, VerifyingKey};
+use crate::{PrivKey, PubKey, Signature};
+use ::signature::{Error, Signer, Verifier};
+
+impl<H: EdDsaHash> Signer<Signature> for SigningKey<H> {
+    fn try_sign(&self, message: &[u8]) -> Result<Signature, Error> {
+        Ok(PrivKey::sign(self, message))
+    }
+}
+
+impl<H: EdDsaHash> Verifier<Signature> for VerifyingKey<H> {
+    fn verify(&self, message: &[u8], signature: &Signature) -> Result<(), Error> {
+        self.check(Signature::from(**signature), message)
+            .map_err(|_| Error::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sign_verify<H: EdDsaHash>() {
+        let signing_key = SigningKey::<H>::from_seed(&[1u8; 32]);
+        let signature: Signature = Signer::sign(&signing_key, b"message");
+
+        let verifying_key = signing_key.verifying_key();
+        assert!(Verifier::verify(&verifying_key, b"message", &signature).is_ok());
+        assert!(Verifier::verify(&verifying_key, b"massage", &signature).is_err());
+    }
+
+    #[test]
+    fn blake2b() {
+        sign_verify::<crate::hashing::blake2b::Context>();
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn sha512() {
+        sign_verify::<crate::hashing::sha512::Context>();
+    }
+}
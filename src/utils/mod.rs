@@ -0,0 +1,289 @@
+//! Util functions for constant time comparison and memory wiping.
+//!
+//! [Official documentation](https://monocypher.org/manual/wipe)
+
+use monocypher_sys as ffi;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::os::raw::c_void;
+
+pub mod pad;
+mod secret_box;
+
+pub use secret_box::SecretBox;
+
+/// Constant time comparison of two equal sized buffers.
+///
+/// The lengths can be 16, 32 or 64. Everything else will return false.
+/// If the length or the buffer content differ false will be returned.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::utils::verify;
+///
+/// if verify("one".as_bytes(), "one".as_bytes()) {
+///     //continue
+/// } else {
+///     //abort
+/// }
+/// ```
+#[inline]
+pub fn verify(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && verify_internal(a, b) == 0
+}
+
+/// Constant time comparison like [`verify`], returning a [`subtle::Choice`] instead of a bool.
+///
+/// Lets the result be combined with other constant time conditions without branching.
+///
+/// Requires the `subtle` feature.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::utils::verify_choice;
+///
+/// let tag = [1u8; 16];
+/// let other = [2u8; 16];
+/// let valid = verify_choice(&tag, &tag) & !verify_choice(&tag, &other);
+/// assert!(bool::from(valid));
+/// ```
+#[cfg(feature = "subtle")]
+pub fn verify_choice(a: &[u8], b: &[u8]) -> subtle::Choice {
+    if a.len() != b.len() {
+        return subtle::Choice::from(0);
+    }
+    // 1 if all bits of the difference are 0.
+    let diff = verify_internal(a, b) as u16;
+    subtle::Choice::from((diff.wrapping_sub(1) >> 15) as u8)
+}
+
+#[inline(never)]
+fn verify_internal(a: &[u8], b: &[u8]) -> u8 {
+    //be paranoid here
+    if a.len() != b.len() {
+        return 1;
+    }
+    //"useless", but lets the optimizer skip bounds checks.
+    let len = a.len();
+    let a = &a[..len];
+    let b = &b[..len];
+
+    let cmp = {
+        let mut ret = 0;
+        for i in 0..len {
+            ret |= a[i] ^ b[i]
+        }
+        ret
+    };
+
+    match len {
+        16 => cmp,
+        32 => cmp,
+        64 => cmp,
+        _ => 1,
+    }
+}
+
+/// Clears a memory region.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::utils::wipe;
+///
+/// let mut secret: [u8; 16] = [255; 16];
+/// wipe(&mut secret);
+/// ```
+pub fn wipe(secret: &mut [u8]) {
+    unsafe { ffi::crypto_wipe(secret.as_mut_ptr() as *mut c_void, secret.len()) }
+}
+
+/// Secret bytes on the heap that are wiped when dropped.
+///
+/// `Debug` prints `[REDACTED]` instead of the contents, so secrets do not end up in logs.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::utils::SecretBytes;
+///
+/// let secret = SecretBytes::from("hunter2".as_bytes());
+/// assert_eq!(&secret[..], "hunter2".as_bytes());
+/// assert_eq!(format!("{:?}", secret), "[REDACTED]");
+/// ```
+#[derive(Clone)]
+pub struct SecretBytes(Box<[u8]>);
+
+impl_zeroize!(SecretBytes);
+
+impl SecretBytes {
+    /// Allocates `len` zero bytes to be filled in place.
+    pub fn zeroed(len: usize) -> SecretBytes {
+        SecretBytes(vec![0u8; len].into_boxed_slice())
+    }
+}
+
+impl From<&[u8]> for SecretBytes {
+    fn from(bytes: &[u8]) -> Self {
+        SecretBytes(Box::from(bytes))
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(mut bytes: Vec<u8>) -> Self {
+        // Shrinking the vector could reallocate and leave the old buffer behind, copy and wipe
+        // it instead.
+        let ret = SecretBytes::from(&bytes[..]);
+        wipe(&mut bytes);
+        ret
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretBytes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn wipe() {
+        let mut a: [u8; 16] = [0; 16];
+
+        for i in 0..a.len() {
+            a[i] = i as u8;
+        }
+
+        for i in 0..a.len() {
+            assert_eq!(a[i], i as u8);
+        }
+
+        utils::wipe(&mut a);
+
+        for i in 0..a.len() {
+            assert_eq!(a[i], 0);
+        }
+    }
+
+    #[test]
+    fn secret_bytes() {
+        let mut secret = SecretBytes::from(vec![7u8; 40]);
+        assert_eq!(&secret[..], &[7u8; 40][..]);
+        assert_eq!(format!("{:?}", secret), "[REDACTED]");
+
+        secret[..8].copy_from_slice(&[1u8; 8]);
+        assert_eq!(secret[..9], [1, 1, 1, 1, 1, 1, 1, 1, 7]);
+        assert_eq!(&SecretBytes::zeroed(3)[..], &[0u8; 3][..]);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize() {
+        use zeroize::Zeroize;
+
+        let mut secret = SecretBytes::from(vec![7u8; 40]);
+        secret.zeroize();
+        assert_eq!(&secret[..], &[0u8; 40][..]);
+    }
+
+    #[test]
+    fn verify_mix() {
+        let a = [4; 16];
+        let b = [4; 32];
+        assert_eq!(verify(&a, &b), false)
+    }
+
+    #[cfg(feature = "subtle")]
+    #[test]
+    fn verify_choice() {
+        let a = [1u8; 64];
+        let mut b = [1u8; 64];
+        assert!(bool::from(utils::verify_choice(&a, &b)));
+        b[63] = 0x81;
+        assert!(!bool::from(utils::verify_choice(&a, &b)));
+        assert!(!bool::from(utils::verify_choice(&a[..16], &b)));
+        assert!(!bool::from(utils::verify_choice(&[1u8; 8], &[1u8; 8])));
+    }
+
+    #[test]
+    fn verify16() {
+        let a = [1u8; 16];
+        let b = [1u8; 16];
+
+        assert!(verify(&a, &b))
+    }
+
+    #[test]
+    fn verify16_fail() {
+        let a = [1u8; 16];
+        let b = [3u8; 16];
+
+        assert_eq!(verify(&a, &b), false)
+    }
+
+    #[test]
+    fn verify32() {
+        let a = [1u8; 32];
+        let b = [1u8; 32];
+
+        assert!(verify(&a, &b))
+    }
+
+    #[test]
+    fn verify32_fail() {
+        let a = [1u8; 32];
+        let b = [3u8; 32];
+
+        assert_eq!(verify(&a, &b), false)
+    }
+
+    #[test]
+    fn verify64() {
+        let a = [1u8; 64];
+        let b = [1u8; 64];
+
+        assert!(verify(&a, &b))
+    }
+
+    #[test]
+    fn verify64_fail() {
+        let a = [1u8; 64];
+        let b = [3u8; 64];
+
+        assert_eq!(verify(&a, &b), false)
+    }
+
+    #[test]
+    fn verify_unsupported_length() {
+        let a = [1u8; 1];
+        let b = [1u8; 1];
+
+        assert_eq!(verify(&a, &b), false)
+    }
+}
@@ -0,0 +1,115 @@
+//! Length hiding padding
+//!
+//! ISO/IEC 7816-4 padding appends the marker byte `0x80` followed by zero bytes and is removed in
+//! constant time. The Padmé length leaks at most `O(log log L)` bits of the message length.
+
+/// Pads `data` to a multiple of `block_size` with ISO/IEC 7816-4 padding.
+///
+/// At least the marker byte is appended, a block size of 0 is treated as 1.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::utils::pad;
+///
+/// let padded = pad::iso7816("secret".as_bytes(), 16);
+/// assert_eq!(padded.len(), 16);
+/// assert_eq!(pad::unpad_iso7816(&padded), Some("secret".as_bytes()));
+/// ```
+pub fn iso7816(data: &[u8], block_size: usize) -> Vec<u8> {
+    let block_size = block_size.max(1);
+    let mut padded = vec![0u8; (data.len() / block_size + 1) * block_size];
+    padded[..data.len()].copy_from_slice(data);
+    padded[data.len()] = 0x80;
+    padded
+}
+
+/// Removes ISO/IEC 7816-4 padding.
+///
+/// Every byte is checked regardless of where the marker is, so the time only depends on the
+/// padded length. Returns `None` if there is no marker or a non zero byte follows it.
+pub fn unpad_iso7816(padded: &[u8]) -> Option<&[u8]> {
+    let mut found = 0usize;
+    let mut invalid = 0usize;
+    let mut index = 0usize;
+    for (i, &byte) in padded.iter().enumerate().rev() {
+        let zero = is_zero(byte);
+        let marker = is_zero(byte ^ 0x80);
+        let searching = 1 - found;
+        let take = searching & marker;
+        index |= i & take.wrapping_neg();
+        invalid |= searching & (1 - zero) & (1 - marker);
+        found |= take;
+    }
+    if found & (1 - invalid) == 1 {
+        Some(&padded[..index])
+    } else {
+        None
+    }
+}
+
+/// Returns the Padmé length of `len`, see <https://lbarman.ch/blog/padme/>.
+///
+/// # Example
+///
+/// Padding to the Padmé length of the message and the marker byte:
+///
+/// ```
+/// use monocypher::utils::pad;
+///
+/// let message = [1u8; 100];
+/// let padded = pad::iso7816(&message, pad::padme_len(message.len() + 1));
+/// assert_eq!(padded.len(), 104);
+/// ```
+pub fn padme_len(len: usize) -> usize {
+    if len < 2 {
+        return len;
+    }
+    let exponent = usize::BITS - 1 - len.leading_zeros();
+    let bits = exponent - (u32::BITS - exponent.leading_zeros());
+    let mask = (1usize << bits) - 1;
+    (len + mask) & !mask
+}
+
+// Returns 1 if `byte` is zero, 0 otherwise, without branching.
+#[inline]
+fn is_zero(byte: u8) -> usize {
+    ((byte as usize).wrapping_sub(1) >> (usize::BITS - 1)) & 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn iso7816_blocks() {
+        let padded = iso7816("secret".as_bytes(), 16);
+        assert_eq!(padded.len(), 16);
+        assert_eq!(padded[6], 0x80);
+        assert_eq!(unpad_iso7816(&padded), Some("secret".as_bytes()));
+
+        let padded = iso7816(&[1u8; 16], 16);
+        assert_eq!(padded.len(), 32);
+        assert_eq!(unpad_iso7816(&padded), Some(&[1u8; 16][..]));
+
+        assert_eq!(iso7816(&[1u8; 3], 0), [1, 1, 1, 0x80]);
+    }
+
+    #[test]
+    fn padme() {
+        assert_eq!(padme_len(0), 0);
+        assert_eq!(padme_len(9), 10);
+        assert_eq!(padme_len(33), 36);
+        assert_eq!(padme_len(101), 104);
+        assert_eq!(padme_len(1001), 1024);
+        assert_eq!(padme_len(65537), 67584);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(unpad_iso7816(&[]), None);
+        assert_eq!(unpad_iso7816(&[0u8; 16]), None);
+        assert_eq!(unpad_iso7816(&[0x80, 1, 0]), None);
+        assert_eq!(unpad_iso7816(&[0x80, 0x80, 0]), Some(&[0x80][..]));
+    }
+}
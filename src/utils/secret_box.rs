@@ -0,0 +1,208 @@
+//! Secrets in locked memory
+
+use monocypher_sys as ffi;
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::os::raw::c_void;
+use std::ptr::{self, NonNull};
+
+/// A heap allocated value in memory that is locked against swapping.
+///
+/// Similar to `sodium_malloc` of libsodium, every box gets pages of its own, so unlocking one box
+/// never unlocks another. When the box is dropped the value is dropped in place, the pages are
+/// wiped with `crypto_wipe`, unlocked and freed.
+///
+/// The memory is locked with `mlock` on unix and `VirtualLock` on windows. Locking fails when
+/// the limit of locked memory is reached, the box still works then but
+/// [`is_locked`](SecretBox::is_locked) returns false.
+///
+/// Moving a value into the box leaves a copy at its old place, byte arrays can be filled in place
+/// with [`SecretBox::zeroed`] instead.
+///
+/// # Example
+///
+/// ```
+/// use monocypher::utils::SecretBox;
+///
+/// let mut key = SecretBox::<[u8; 64]>::zeroed();
+/// key.copy_from_slice(&[137u8; 64]);
+///
+/// assert_eq!(key[..], [137u8; 64]);
+/// assert_eq!(format!("{:?}", key), "[REDACTED]");
+/// ```
+pub struct SecretBox<T> {
+    ptr: NonNull<T>,
+    layout: Layout,
+    locked: bool,
+    value: PhantomData<T>,
+}
+
+// The box owns its value like a `Box<T>`.
+unsafe impl<T: Send> Send for SecretBox<T> {}
+unsafe impl<T: Sync> Sync for SecretBox<T> {}
+
+impl<T> SecretBox<T> {
+    /// Moves the value into locked memory.
+    pub fn new(value: T) -> SecretBox<T> {
+        let page = page_size();
+        let size = mem::size_of::<T>().max(1);
+        let layout = Layout::from_size_align((size + page - 1) & !(page - 1), page)
+            .and_then(|layout| layout.align_to(mem::align_of::<T>()))
+            .expect("secret is too large");
+
+        let memory = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = match NonNull::new(memory as *mut T) {
+            Some(ptr) => ptr,
+            None => alloc::handle_alloc_error(layout),
+        };
+        let locked = unsafe { lock(memory, layout.size()) };
+        unsafe { ptr::write(ptr.as_ptr(), value) };
+
+        SecretBox {
+            ptr,
+            layout,
+            locked,
+            value: PhantomData,
+        }
+    }
+
+    /// Returns true if the memory is locked against swapping.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl<const N: usize> SecretBox<[u8; N]> {
+    /// Allocates zero bytes in locked memory to be filled in place.
+    pub fn zeroed() -> SecretBox<[u8; N]> {
+        SecretBox::new([0u8; N])
+    }
+}
+
+impl<T> Deref for SecretBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for SecretBox<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T> fmt::Debug for SecretBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl<T> Drop for SecretBox<T> {
+    fn drop(&mut self) {
+        let memory = self.ptr.as_ptr() as *mut u8;
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr());
+            ffi::crypto_wipe(memory as *mut c_void, self.layout.size());
+            if self.locked {
+                unlock(memory, self.layout.size());
+            }
+            alloc::dealloc(memory, self.layout);
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T> zeroize::ZeroizeOnDrop for SecretBox<T> {}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => 4096,
+    }
+}
+
+#[cfg(unix)]
+unsafe fn lock(memory: *mut u8, len: usize) -> bool {
+    libc::mlock(memory as *const c_void, len) == 0
+}
+
+#[cfg(unix)]
+unsafe fn unlock(memory: *mut u8, len: usize) {
+    libc::munlock(memory as *const c_void, len);
+}
+
+#[cfg(windows)]
+fn page_size() -> usize {
+    use windows_sys::Win32::System::SystemInformation::{GetSystemInfo, SYSTEM_INFO};
+
+    let mut info: SYSTEM_INFO = unsafe { mem::zeroed() };
+    unsafe { GetSystemInfo(&mut info) };
+    info.dwPageSize as usize
+}
+
+#[cfg(windows)]
+unsafe fn lock(memory: *mut u8, len: usize) -> bool {
+    windows_sys::Win32::System::Memory::VirtualLock(memory as *const c_void, len) != 0
+}
+
+#[cfg(windows)]
+unsafe fn unlock(memory: *mut u8, len: usize) {
+    windows_sys::Win32::System::Memory::VirtualUnlock(memory as *const c_void, len);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn page_size() -> usize {
+    4096
+}
+
+#[cfg(not(any(unix, windows)))]
+unsafe fn lock(_memory: *mut u8, _len: usize) -> bool {
+    false
+}
+
+#[cfg(not(any(unix, windows)))]
+unsafe fn unlock(_memory: *mut u8, _len: usize) {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn zeroed() {
+        let mut key = SecretBox::<[u8; 32]>::zeroed();
+        assert_eq!(*key, [0u8; 32]);
+        key[0] = 1;
+        assert_eq!(key[..2], [1, 0]);
+    }
+
+    #[test]
+    fn own_pages() {
+        let a = SecretBox::new(1u64);
+        let b = SecretBox::new(2u64);
+        assert_eq!(a.ptr.as_ptr() as usize % page_size(), 0);
+        assert_eq!(b.ptr.as_ptr() as usize % page_size(), 0);
+        assert_eq!((*a, *b), (1, 2));
+    }
+
+    #[test]
+    fn drops_value() {
+        let value = Rc::new(());
+        let secret = SecretBox::new(value.clone());
+        assert_eq!(Rc::strong_count(&value), 2);
+        drop(secret);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn debug() {
+        let secret = SecretBox::new(*b"password");
+        assert_eq!(format!("{:?}", secret), "[REDACTED]");
+    }
+}
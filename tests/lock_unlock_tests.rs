@@ -26,5 +26,28 @@ fn aead_lock_unlock_mac_corrupt() {
     let clear = unlock::aead(&cymac.0, key, nonce, wrong_mac, ad.as_bytes());
 
     assert_eq!(clear.is_err(), true);
-    assert_eq!(clear.err().unwrap(), "Message is corrupt.".to_owned())
+    assert_eq!(clear.err().unwrap(), Error::InvalidMac)
+}
+
+#[test]
+fn easy_lock_unlock() {
+    let key: [u8; 32] = [1; 32];
+    let nonce: [u8; 24] = [2; 24];
+    let plaintext = "secret";
+
+    let cymac = lock::easy(plaintext.as_bytes(), key, nonce);
+    let clear = unlock::easy(&cymac.0, key, nonce, cymac.1).unwrap();
+
+    assert_eq!(&String::from_utf8(clear).unwrap(), "secret")
+}
+
+#[test]
+fn easy_lock_aead_unlock_ad_mismatch() {
+    let key: [u8; 32] = [1; 32];
+    let nonce: [u8; 24] = [2; 24];
+
+    let cymac = lock::easy("secret".as_bytes(), key, nonce);
+    let clear = unlock::aead(&cymac.0, key, nonce, cymac.1, "add".as_bytes());
+
+    assert_eq!(clear.err().unwrap(), Error::InvalidMac)
 }